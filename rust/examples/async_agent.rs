@@ -0,0 +1,29 @@
+//! A2A Protocol Rust Example: async task handler
+//!
+//! Run with: cargo run --example async_agent
+
+use a2a::A2AServer;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = A2AServer::new(
+        "slow-echo-agent",
+        "Slow Echo Agent",
+        vec!["echo".to_string()],
+        9002,
+    );
+
+    // The handler can await anything: a database query, another agent, or a timer.
+    server.handle_task_async(|ctx, input| async move {
+        println!("📥 Received task {}: action={} from={}", ctx.task_id, ctx.action, ctx.sender);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        json!({"echo": input["message"], "delayed_ms": 500})
+    });
+
+    server.run().await?;
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! A2A Protocol Rust Example: local directory
+//!
+//! Run with: cargo run --example directory
+//!
+//! Then start `example_agent`, which registers itself here on port 8080.
+
+use a2a::A2ADirectory;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("📋 A2A directory on http://localhost:8080");
+
+    A2ADirectory::new(8080)
+        .with_ttl(Duration::from_secs(120))
+        .run_with_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
@@ -2,7 +2,7 @@
 //!
 //! Run with: cargo run --example example_agent
 
-use a2a::{A2AAgent, run_server};
+use a2a::{A2AAgent, TaskError, run_server};
 use serde_json::json;
 
 #[tokio::main]
@@ -25,20 +25,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Calculator Agent",
         vec!["math".to_string(), "calculate".to_string(), "add".to_string()],
         9001,
-        |action, input, sender| {
-            println!("📥 Received task: action={} from={}", action, sender);
+        |ctx, input| {
+            println!("📥 Received task {}: action={} from={}", ctx.task_id, ctx.action, ctx.sender);
             
-            match action.as_str() {
+            match ctx.action.as_str() {
                 "add" => {
                     let a = input["a"].as_f64().unwrap_or(0.0);
                     let b = input["b"].as_f64().unwrap_or(0.0);
-                    json!({"result": a + b})
+                    Ok(json!({"result": a + b}))
                 }
                 "echo" => {
-                    json!({"echo": input["message"]})
+                    Ok(json!({"echo": input["message"]}))
                 }
                 _ => {
-                    json!({"error": "Unknown action"})
+                    Err(TaskError::failed(format!("Unknown action: {}", ctx.action)))
                 }
             }
         },
@@ -0,0 +1,48 @@
+//! A2A Protocol Rust Example: two agents wired together in process
+//!
+//! Run with: cargo run --example in_process
+//!
+//! Nothing listens on a socket: an `InMemoryTransport` stands in for both
+//! the directory and the network, which is how you would exercise agents
+//! in a unit test.
+
+use a2a::{A2AAgent, A2AServer, InMemoryTransport};
+use serde_json::json;
+use std::sync::Arc;
+
+const DIRECTORY: &str = "mem://directory";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let transport = InMemoryTransport::new();
+
+    // The calculator answers `add` tasks.
+    let mut calculator_server = A2AServer::new("calculator", "Calculator", vec!["math".to_string()], 0);
+    calculator_server.handle_task(|ctx, input| {
+        println!("📥 calculator: {} from {}", ctx.action, ctx.sender);
+        json!({"sum": input["a"].as_i64().unwrap_or(0) + input["b"].as_i64().unwrap_or(0)})
+    });
+    transport.mount("mem://calculator", &calculator_server);
+
+    let mut calculator = A2AAgent::new("calculator", "Calculator", vec!["math".to_string()])
+        .with_transport(Arc::new(transport.clone()));
+    calculator.register("mem://calculator", DIRECTORY).await?;
+
+    // The accountant discovers the calculator and delegates to it.
+    let accountant = A2AAgent::new("accountant", "Accountant", vec![]).with_transport(Arc::new(transport.clone()));
+    let peer = accountant
+        .discover(vec!["math".to_string()], DIRECTORY)
+        .await?
+        .ok_or("no math agent registered")?;
+    println!("🔍 found {} at {}", peer.agent_id, peer.endpoint);
+
+    let result = accountant
+        .send_task(&peer.agent_id, "add", json!({"a": 10, "b": 20}), DIRECTORY)
+        .await?;
+    println!("✅ {:?}: {}", result.status, result.output.unwrap_or_default());
+
+    calculator.deregister(DIRECTORY).await?;
+    println!("📋 registered agents left: {}", transport.agents().await.len());
+
+    Ok(())
+}
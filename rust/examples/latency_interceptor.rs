@@ -0,0 +1,46 @@
+//! A2A Protocol Rust Example: interceptor that logs call latency
+//!
+//! Run with: cargo run --example latency_interceptor
+
+use a2a::{A2AAgent, A2AServer, Interceptor, RequestParts, ResponseParts};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+struct LatencyLogger;
+
+#[async_trait]
+impl Interceptor for LatencyLogger {
+    async fn on_request(&self, request: &mut RequestParts) {
+        request
+            .headers
+            .insert("x-request-id", uuid::Uuid::new_v4().to_string().parse().unwrap());
+    }
+
+    async fn on_response(&self, response: &ResponseParts) {
+        match response.status {
+            Some(status) => println!(
+                "⏱️  {} {} -> {} in {:?}",
+                response.method, response.url, status, response.elapsed
+            ),
+            None => println!("⏱️  {} {} failed after {:?}", response.method, response.url, response.elapsed),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = A2AServer::new("echo-agent", "Echo Agent", vec!["echo".to_string()], 9003);
+    server.handle_task(|_ctx, input| json!({"echo": input}));
+    tokio::spawn(async move { server.run().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let agent = A2AAgent::new("client-agent", "Client Agent", vec![]).with_interceptor(Arc::new(LatencyLogger));
+
+    for _ in 0..3 {
+        agent.ping("http://localhost:9003").await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! A2A Protocol Rust Example: an interaction test on the `testing` harness
+//!
+//! Run with: cargo run --example testing_harness --features testing
+//!
+//! `MockDirectory` and `MockAgent` serve real HTTP on free loopback ports,
+//! so the client below is an ordinary `A2AAgent`. The same code works as
+//! the body of a `#[tokio::test]`.
+
+use a2a::testing::{MockAgent, MockDirectory};
+use a2a::{A2AAgent, A2AServer, AgentInfo, CapabilityQuery, SelectionStrategy};
+use serde_json::json;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = MockDirectory::start().await?;
+    println!("📋 directory at {}", directory.url());
+
+    // A calculator that registers itself over HTTP, like a real peer.
+    let mut calculator_server = A2AServer::new("calculator", "Calculator", vec!["math".to_string()], 0);
+    calculator_server.handle_task(|_ctx, input| json!({"sum": input["a"].as_i64().unwrap_or(0) + input["b"].as_i64().unwrap_or(0)}));
+    let calculator = MockAgent::start(&calculator_server).await?;
+    let mut calculator_agent = A2AAgent::new("calculator", "Calculator", vec!["math".to_string()]);
+    calculator_agent.register(calculator.url(), directory.url()).await?;
+
+    // A second math agent, seeded directly, whose server is already gone.
+    directory
+        .seed(AgentInfo {
+            agent_id: "abacus".to_string(),
+            name: "Abacus".to_string(),
+            capabilities: vec!["math".into()],
+            endpoint: "http://127.0.0.1:9".to_string(),
+            registered_at: None,
+            version: None,
+            description: None,
+            tags: Default::default(),
+        })
+        .await?;
+    assert_eq!(directory.agents().await.len(), 2);
+
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let result = client
+        .send_task("calculator", "add", json!({"a": 2, "b": 3}), directory.url())
+        .await?;
+    assert_eq!(result.output, Some(json!({"sum": 5})));
+    println!("✅ direct: {}", result.output.unwrap_or_default());
+
+    // The dead abacus sorts first, so this shows the fallback at work.
+    let query = CapabilityQuery::All(vec!["math".to_string()]);
+    let result = client
+        .send_task_any(query, SelectionStrategy::First, "add", json!({"a": 4, "b": 4}), directory.url())
+        .await?;
+    assert_eq!(result.output, Some(json!({"sum": 8})));
+    println!("✅ with fallback: {}", result.output.unwrap_or_default());
+
+    // Dropping the harness shuts its servers down; `stop` also waits.
+    calculator.stop().await?;
+    assert!(client
+        .send_task("calculator", "add", json!({"a": 1, "b": 1}), directory.url())
+        .await
+        .is_err());
+    println!("🛑 calculator stopped");
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! A2A Protocol Rust Example: tasks and pushed chunks over a WebSocket
+//!
+//! Run with: cargo run --example websocket --features ws
+
+use a2a::{A2AAgent, A2AServer};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = A2AServer::new("counter-agent", "Counter Agent", vec!["count".to_string()], 9004);
+    server.handle_task(|_ctx, input| json!({"echo": input}));
+    server.handle_task_stream(|_ctx, input| {
+        let to = input["to"].as_u64().unwrap_or(3);
+        futures::stream::iter((1..=to).map(|n| Ok(json!(n))))
+    });
+    tokio::spawn(async move { server.run().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let agent = A2AAgent::new("client-agent", "Client Agent", vec![]);
+    let session = agent.connect_ws("ws://localhost:9004/ws").await?;
+
+    let result = session.send_task("echo", json!({"hello": "world"})).await?;
+    println!("📨 Echo: {:?}", result.output);
+
+    let stream = session.stream_task("count", json!({"to": 5}));
+    let chunks = async {
+        for _ in 0..5 {
+            let notification = session.recv().await?;
+            println!("📦 {}: {}", notification.method, notification.params["data"]);
+        }
+        Ok::<_, a2a::A2AError>(())
+    };
+    let (result, chunks) = tokio::join!(stream, chunks);
+    chunks?;
+    println!("✅ Stream finished: {:?}", result?.status);
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Telling a peer to stop a task whose caller stopped waiting for it.
+
+use serde_json::json;
+
+use crate::{A2AAgent, AgentInfo};
+
+/// Armed while a task call is in flight; if the call's future is dropped
+/// before disarming it, asks the target to cancel the task in the
+/// background.
+pub(crate) struct CancelOnDrop {
+    armed: Option<(A2AAgent, AgentInfo, String)>,
+}
+
+impl CancelOnDrop {
+    pub(crate) fn new(agent: &A2AAgent, agent_info: &AgentInfo, task_id: &str) -> Self {
+        Self {
+            armed: Some((agent.clone(), agent_info.clone(), task_id.to_string())),
+        }
+    }
+
+    /// The call finished; nothing to cancel.
+    pub(crate) fn disarm(mut self) {
+        self.armed = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let Some((agent, agent_info, task_id)) = self.armed.take() else {
+            return;
+        };
+        // Without a runtime, e.g. while one shuts down, there is no one
+        // to send the cancel.
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        debug!(task_id = %task_id, target = %agent_info.agent_id, "task call dropped, cancelling task");
+        runtime.spawn(async move {
+            let params = json!({"taskId": task_id, "sender": agent.agent_id});
+            if let Err(e) = agent.call_peer(&agent_info, &agent.method("task/cancel"), params, &[]).await {
+                debug!(task_id = %task_id, error = %e, "cancelling dropped task failed");
+            }
+        });
+    }
+}
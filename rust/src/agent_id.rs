@@ -0,0 +1,105 @@
+//! Rules for well-formed agent IDs and names, checked before a
+//! registration reaches the directory.
+
+use crate::A2AError;
+
+/// What an `agent_id` and `name` must look like. Both must be non-empty,
+/// free of leading and trailing whitespace, and at most `max_len`
+/// characters; every character of an `agent_id` must also pass `allowed`.
+///
+/// The default allows ASCII letters, digits, `-` and `_`, up to 128
+/// characters. [`A2AAgent::register`](crate::A2AAgent::register) checks
+/// the agent's own ID and name before sending the registration, and an
+/// [`A2ADirectory`](crate::A2ADirectory) refuses registrations that break
+/// its policy, so a malformed ID never reaches discovery.
+///
+/// ```
+/// use a2a::AgentIdPolicy;
+///
+/// let policy = AgentIdPolicy::new();
+/// assert!(policy.check_agent_id("billing-agent_2").is_ok());
+/// assert!(policy.check_agent_id(" billing").is_err());
+/// assert!(policy.check_agent_id("billing.eu").is_err());
+///
+/// // Allow dotted, namespaced IDs as well.
+/// let dotted = AgentIdPolicy::new().allowed_chars(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+/// assert!(dotted.check_agent_id("billing.eu").is_ok());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AgentIdPolicy {
+    /// The most characters an ID or name may have.
+    pub max_len: usize,
+    /// Whether a character may appear in an ID.
+    pub allowed: fn(char) -> bool,
+}
+
+impl AgentIdPolicy {
+    pub fn new() -> Self {
+        Self {
+            max_len: 128,
+            allowed: |c| c.is_ascii_alphanumeric() || c == '-' || c == '_',
+        }
+    }
+
+    /// Only require IDs and names to be non-empty and trimmed, with any
+    /// characters and no length limit.
+    pub fn permissive() -> Self {
+        Self {
+            max_len: usize::MAX,
+            allowed: |_| true,
+        }
+    }
+
+    /// Allow IDs and names of up to `max_len` characters.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Allow the characters `allowed` accepts in IDs.
+    pub fn allowed_chars(mut self, allowed: fn(char) -> bool) -> Self {
+        self.allowed = allowed;
+        self
+    }
+
+    /// Fail with [`A2AError::InvalidAgentId`] unless `agent_id` is well
+    /// formed.
+    pub fn check_agent_id(&self, agent_id: &str) -> Result<(), A2AError> {
+        self.check_text("agent ID", agent_id)?;
+        match agent_id.chars().find(|&c| !(self.allowed)(c)) {
+            Some(c) => Err(A2AError::InvalidAgentId(format!("agent ID {:?} contains {:?}", agent_id, c))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail with [`A2AError::InvalidAgentId`] unless `name` is non-empty,
+    /// trimmed and short enough. Any characters may appear in a name.
+    pub fn check_name(&self, name: &str) -> Result<(), A2AError> {
+        self.check_text("name", name)
+    }
+
+    /// Check both halves of a registration.
+    pub(crate) fn check(&self, agent_id: &str, name: &str) -> Result<(), A2AError> {
+        self.check_agent_id(agent_id)?;
+        self.check_name(name)
+    }
+
+    fn check_text(&self, what: &str, text: &str) -> Result<(), A2AError> {
+        if text.trim().is_empty() {
+            return Err(A2AError::InvalidAgentId(format!("{} is empty", what)));
+        }
+        if text.trim() != text {
+            return Err(A2AError::InvalidAgentId(format!("{} {:?} has leading or trailing whitespace", what, text)));
+        }
+        if text.chars().count() > self.max_len {
+            return Err(A2AError::InvalidAgentId(format!("{} is longer than {} characters", what, self.max_len)));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AgentIdPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,193 @@
+//! Binary parts sent alongside a task, as `multipart/related` bodies.
+//!
+//! A task with attachments goes out as a `multipart/related` request whose
+//! first part is the JSON-RPC envelope, as `application/json`, followed by
+//! one part per attachment. Each attachment part carries its name as
+//! `Content-ID: <name>` and its own `Content-Type`, and its bytes as they
+//! are, with no base64.
+
+use serde_json::Value;
+
+use crate::{A2AAgent, A2AError, TaskResult};
+
+/// A named binary part of a task, such as an image or a PDF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn new(name: impl Into<String>, content_type: impl Into<String>, bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            content_type: content_type.into(),
+            bytes: bytes.into(),
+        }
+    }
+}
+
+impl A2AAgent {
+    /// Like [`send_task`](Self::send_task), but send `attachments` with
+    /// the task as binary parts; the handler finds them in
+    /// [`TaskContext::attachments`](crate::TaskContext::attachments).
+    ///
+    /// Fails with [`A2AError::InvalidAttachment`] if a name or content type
+    /// cannot go in a MIME header, or if the agent's transport cannot
+    /// carry attachments.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, Attachment};
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let scan = Attachment::new("scan.pdf", "application/pdf", std::fs::read("scan.pdf")?);
+    /// let result = agent
+    ///     .send_task_with_attachments("ocr", "extract", json!({}), vec![scan], "http://localhost:8080")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_task_with_attachments(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        attachments: Vec<Attachment>,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        for attachment in &attachments {
+            check_header_safe(attachment)?;
+        }
+        self.dispatch_task(target_agent_id, action, input, &attachments, directory_url, &self.method("task"))
+            .await
+    }
+}
+
+/// Refuse a name or content type that would break out of its MIME header.
+fn check_header_safe(attachment: &Attachment) -> Result<(), A2AError> {
+    if attachment.name.is_empty() || attachment.name.contains(['\r', '\n', '<', '>']) {
+        return Err(A2AError::InvalidAttachment(format!("invalid name: {:?}", attachment.name)));
+    }
+    if attachment.content_type.contains(['\r', '\n']) {
+        return Err(A2AError::InvalidAttachment(format!(
+            "invalid content type for {}: {:?}",
+            attachment.name, attachment.content_type
+        )));
+    }
+    Ok(())
+}
+
+/// The `Content-Type` and body of a request carrying `envelope` as its
+/// root part and `attachments` after it.
+pub(crate) fn encode(envelope: &[u8], attachments: &[Attachment]) -> (String, Vec<u8>) {
+    let boundary = format!("a2a-{}", uuid::Uuid::new_v4().simple());
+    let content_type = format!("multipart/related; boundary=\"{}\"; type=\"application/json\"", boundary);
+
+    let size = envelope.len() + attachments.iter().map(|a| a.bytes.len() + 256).sum::<usize>() + 256;
+    let mut body = Vec::with_capacity(size);
+    body.extend_from_slice(format!("--{}\r\nContent-Type: application/json\r\n\r\n", boundary).as_bytes());
+    body.extend_from_slice(envelope);
+    for attachment in attachments {
+        body.extend_from_slice(
+            format!(
+                "\r\n--{}\r\nContent-Type: {}\r\nContent-ID: <{}>\r\n\r\n",
+                boundary, attachment.content_type, attachment.name
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&attachment.bytes);
+    }
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (content_type, body)
+}
+
+/// Whether `content_type` declares a body [`decode`] reads.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn is_multipart(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("multipart/related"))
+}
+
+/// Split a `multipart/related` `body` into its root part and the
+/// attachments after it. Attachment parts without a `Content-Type` are
+/// read as `application/octet-stream`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn decode<'a>(content_type: &str, body: &'a [u8]) -> Result<(&'a [u8], Vec<Attachment>), String> {
+    let boundary = boundary(content_type).ok_or("missing boundary")?;
+    let delimiter = format!("--{}", boundary);
+    let start = find(body, delimiter.as_bytes()).ok_or("missing first boundary")?;
+    let mut rest = &body[start + delimiter.len()..];
+
+    let delimiter = format!("\r\n--{}", boundary);
+    let mut parts = Vec::new();
+    while !rest.starts_with(b"--") {
+        rest = rest.strip_prefix(b"\r\n").ok_or("malformed boundary")?;
+        let end = find(rest, delimiter.as_bytes()).ok_or("unterminated part")?;
+        parts.push(&rest[..end]);
+        rest = &rest[end + delimiter.len()..];
+    }
+
+    let mut parts = parts.into_iter().map(part);
+    let (_, root) = parts.next().ok_or("no root part")??;
+    let attachments = parts
+        .map(|part| {
+            let (headers, bytes) = part?;
+            let name = header(&headers, "content-id")
+                .map(|id| id.trim_start_matches('<').trim_end_matches('>').to_string())
+                .ok_or("attachment without Content-ID")?;
+            let content_type = header(&headers, "content-type").unwrap_or("application/octet-stream");
+            Ok(Attachment::new(name, content_type, bytes))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok((root, attachments))
+}
+
+/// The `boundary` parameter of a `Content-Type`, unquoted.
+#[cfg(not(target_arch = "wasm32"))]
+fn boundary(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"'))
+            .filter(|boundary| !boundary.is_empty())
+    })
+}
+
+/// A part's headers, each as `(lowercase name, value)`.
+#[cfg(not(target_arch = "wasm32"))]
+type PartHeaders = Vec<(String, String)>;
+
+/// A part's headers and its content.
+#[cfg(not(target_arch = "wasm32"))]
+fn part(part: &[u8]) -> Result<(PartHeaders, &[u8]), String> {
+    let (head, content) = match part.strip_prefix(b"\r\n") {
+        Some(content) => (&[][..], content),
+        None => {
+            let end = find(part, b"\r\n\r\n").ok_or("part without headers")?;
+            (&part[..end], &part[end + 4..])
+        }
+    };
+    let head = std::str::from_utf8(head).map_err(|_| "part headers are not UTF-8")?;
+    let headers = head
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Ok((headers, content))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
@@ -0,0 +1,54 @@
+//! Credentials attached to outbound directory and peer calls.
+
+use async_trait::async_trait;
+use reqwest::header::HeaderValue;
+use reqwest::RequestBuilder;
+use std::fmt;
+
+use crate::A2AError;
+
+/// Static credentials sent with every outbound request.
+///
+/// The `Debug` output never contains the secret itself.
+#[derive(Clone)]
+pub enum AuthConfig {
+    /// `Authorization: Bearer <token>`
+    Bearer(String),
+    /// An arbitrary header carrying an API key, e.g. `X-Api-Key`.
+    ApiKey { header: String, value: String },
+}
+
+impl AuthConfig {
+    pub(crate) fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            AuthConfig::Bearer(token) => request.bearer_auth(token),
+            AuthConfig::ApiKey { header, value } => match HeaderValue::from_str(value) {
+                Ok(mut value) => {
+                    value.set_sensitive(true);
+                    request.header(header.as_str(), value)
+                }
+                // Let reqwest surface the invalid header when the request is sent.
+                Err(_) => request.header(header.as_str(), value.as_str()),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthConfig::Bearer(_) => f.write_str("Bearer(***)"),
+            AuthConfig::ApiKey { header, .. } => write!(f, "ApiKey {{ header: {:?}, value: *** }}", header),
+        }
+    }
+}
+
+/// Supplies a bearer token before every outbound request, for credentials
+/// that rotate (e.g. an OAuth client-credentials flow).
+///
+/// Caching and refreshing are up to the implementation; `token` is called
+/// once per request, including retries.
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    async fn token(&self) -> Result<String, A2AError>;
+}
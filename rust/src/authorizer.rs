@@ -0,0 +1,122 @@
+//! Deciding which callers an [`A2AServer`](crate::A2AServer) runs tasks
+//! for.
+
+use async_trait::async_trait;
+use axum::http::{header, HeaderMap};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::TaskContext;
+
+/// Why an [`Authorizer`] refused a task. The message is sent back to the
+/// caller, so it should not reveal more than the caller may know.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthError {
+    pub message: String,
+}
+
+impl AuthError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Decides whether a server runs a task, before any handler sees it.
+///
+/// Install one with [`A2AServer::with_authorizer`](crate::A2AServer::with_authorizer).
+/// It is asked about every `a2a/task`, `a2a/task/submit` and
+/// `a2a/task/stream` call, already knowing the claimed `sender`, the
+/// action and the request's headers; a refused task fails with JSON-RPC
+/// error `-32003` "Unauthorized". The context's `attachments` are empty
+/// and its `cancellation` token is not yet live at this point.
+///
+/// It is asked about `a2a/task/status` and `a2a/task/cancel` calls too,
+/// with the method called as the `action`. These only reach tasks of the
+/// claimed `sender`, so a caller may look up or cancel exactly the tasks
+/// of the senders the authorizer lets it act as.
+///
+/// ```
+/// use a2a::{AuthError, Authorizer, TaskContext};
+/// use async_trait::async_trait;
+/// use axum::http::HeaderMap;
+///
+/// /// Lets only the planner ask for deletions.
+/// struct OnlyPlannerDeletes;
+///
+/// #[async_trait]
+/// impl Authorizer for OnlyPlannerDeletes {
+///     async fn authorize(&self, ctx: &TaskContext, _headers: &HeaderMap) -> Result<(), AuthError> {
+///         if ctx.action == "delete" && ctx.sender != "planner" {
+///             return Err(AuthError::new("only the planner may delete"));
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Authorizer: Send + Sync {
+    async fn authorize(&self, ctx: &TaskContext, headers: &HeaderMap) -> Result<(), AuthError>;
+}
+
+/// Accepts tasks sent with `Authorization: Bearer <token>` for one of a
+/// fixed set of tokens, such as those configured with
+/// [`AuthConfig::Bearer`](crate::AuthConfig::Bearer) on the calling agents.
+///
+/// A token added with [`with_sender_token`](Self::with_sender_token) only
+/// admits tasks whose `sender` is that agent, so one leaked token cannot
+/// be used to impersonate another.
+#[derive(Clone, Default)]
+pub struct BearerAuthorizer {
+    /// Each accepted token, with the one sender it is bound to, if any.
+    tokens: HashMap<String, Option<String>>,
+}
+
+impl BearerAuthorizer {
+    /// Accept `token` from any sender.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self::default().with_token(token)
+    }
+
+    /// Also accept `token` from any sender.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.tokens.insert(token.into(), None);
+        self
+    }
+
+    /// Also accept `token`, but only on tasks sent by `sender`.
+    pub fn with_sender_token(mut self, sender: impl Into<String>, token: impl Into<String>) -> Self {
+        self.tokens.insert(token.into(), Some(sender.into()));
+        self
+    }
+}
+
+impl fmt::Debug for BearerAuthorizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BearerAuthorizer {{ tokens: {} }}", self.tokens.len())
+    }
+}
+
+#[async_trait]
+impl Authorizer for BearerAuthorizer {
+    async fn authorize(&self, ctx: &TaskContext, headers: &HeaderMap) -> Result<(), AuthError> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| AuthError::new("missing bearer token"))?;
+        match self.tokens.get(token.trim()) {
+            Some(None) => Ok(()),
+            Some(Some(sender)) if *sender == ctx.sender => Ok(()),
+            Some(Some(_)) => Err(AuthError::new(format!("token not valid for sender {}", ctx.sender))),
+            None => Err(AuthError::new("invalid bearer token")),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Bulk task dispatch using JSON-RPC batch requests.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::{A2AAgent, A2AError, AgentInfo, JSONRPCRequest, JSONRPCResponse, TaskResult, JSONRPC_VERSION};
+
+impl A2AAgent {
+    /// Send many tasks at once, given as `(agent_id, action, input)`.
+    ///
+    /// Tasks for agents sharing an endpoint travel in one JSON-RPC batch
+    /// array. The returned vector lines up with `tasks`, and each entry
+    /// succeeds or fails on its own: an unknown agent or a rejected task
+    /// only affects its own slot. When a whole batch request fails, every
+    /// task in it reports [`A2AError::BatchFailed`].
+    pub async fn send_tasks_batch(
+        &self,
+        tasks: Vec<(String, String, Value)>,
+        directory_url: &str,
+    ) -> Result<Vec<Result<TaskResult, A2AError>>, A2AError> {
+        let mut results: Vec<Option<Result<TaskResult, A2AError>>> = tasks.iter().map(|_| None).collect();
+
+        let mut agents: HashMap<String, Result<AgentInfo, A2AError>> = HashMap::new();
+        for (agent_id, _, _) in &tasks {
+            if !agents.contains_key(agent_id) {
+                let agent_info = self.resolve_agent(agent_id, directory_url).await;
+                agents.insert(agent_id.clone(), agent_info);
+            }
+        }
+
+        // endpoint -> (index into `tasks`, request)
+        let mut groups: HashMap<String, Vec<(usize, JSONRPCRequest)>> = HashMap::new();
+        // index into `tasks` -> target agent_id, for checking signatures
+        let mut targets: HashMap<usize, String> = HashMap::new();
+        for (index, (agent_id, action, input)) in tasks.into_iter().enumerate() {
+            let agent_info = match &agents[&agent_id] {
+                Ok(agent_info) => agent_info,
+                Err(A2AError::AgentNotFound(_)) => {
+                    results[index] = Some(Err(A2AError::AgentNotFound(agent_id)));
+                    continue;
+                }
+                Err(e) => {
+                    let message = format!("lookup of {} failed: {}", agent_id, e);
+                    results[index] = Some(Err(A2AError::BatchFailed(message)));
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.check_task(agent_info, &action, None, &input) {
+                results[index] = Some(Err(e));
+                continue;
+            }
+            if let Err(e) = self.throttle(&agent_id).await {
+                results[index] = Some(Err(e));
+                continue;
+            }
+
+            let params = self.task_params(&action, input);
+            let request = JSONRPCRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: Value::String(uuid::Uuid::new_v4().to_string()),
+                method: self.method("task"),
+                params: Some(serde_json::to_value(params)?),
+            };
+            groups.entry(agent_info.endpoint.clone()).or_default().push((index, request));
+            targets.insert(index, agent_id);
+        }
+
+        for (endpoint, group) in groups {
+            let (indices, requests): (Vec<usize>, Vec<JSONRPCRequest>) = group.into_iter().unzip();
+
+            match self.send_batch(&endpoint, &requests).await {
+                Ok(responses) => {
+                    let mut by_id: HashMap<String, JSONRPCResponse> = responses
+                        .into_iter()
+                        .map(|response| (response.id.to_string(), response))
+                        .collect();
+
+                    for (index, request) in indices.into_iter().zip(&requests) {
+                        let result = match by_id.remove(&request.id.to_string()) {
+                            Some(response) => response
+                                .into_result()
+                                .and_then(|result| self.task_result(&targets[&index], result)),
+                            None => Err(A2AError::Protocol(format!("batch response has no answer for request {}", request.id))),
+                        };
+                        results[index] = Some(result);
+                    }
+                }
+                Err(e) => {
+                    let message = format!("batch request to {} failed: {}", endpoint, e);
+                    for index in indices {
+                        results[index] = Some(Err(A2AError::BatchFailed(message.clone())));
+                    }
+                }
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(A2AError::EmptyResponse)))
+            .collect())
+    }
+
+    async fn send_batch(&self, url: &str, requests: &[JSONRPCRequest]) -> Result<Vec<JSONRPCResponse>, A2AError> {
+        let method = self.method("task");
+        self.with_retries(url, &method, || async {
+            let response = self.http.post_json(url, &method, requests).await?;
+            let body: Value = self.http.read_json(response).await?;
+
+            // A server that cannot parse the batch answers with one error object.
+            if body.is_object() {
+                let response: JSONRPCResponse = serde_json::from_value(body)?;
+                response.into_result()?;
+                return Err(A2AError::Protocol("batch was answered with a single response".to_string()));
+            }
+
+            Ok(serde_json::from_value(body)?)
+        })
+        .await
+    }
+}
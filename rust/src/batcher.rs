@@ -0,0 +1,135 @@
+//! Buffering tasks and sending them in batches.
+
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::{A2AAgent, A2AError, TaskResult};
+
+/// Collects tasks and sends them with
+/// [`send_tasks_batch`](A2AAgent::send_tasks_batch), so a producer that
+/// makes many small tasks pays for few requests.
+///
+/// A batch goes out once it holds `max_batch` tasks, or `max_delay` after
+/// its first task was enqueued, whichever comes first. One batch is in
+/// flight at a time; tasks enqueued meanwhile wait for the next.
+/// [`close`](Self::close) sends what is still buffered and waits for it;
+/// dropping the batcher sends it too, in the background.
+///
+/// ```rust,no_run
+/// use a2a::{A2AAgent, TaskBatcher};
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// let agent = A2AAgent::new("producer", "Producer", vec![]);
+/// let batcher = TaskBatcher::new(agent, "http://localhost:8080", 50, Duration::from_millis(20));
+///
+/// let pending: Vec<_> = (0..200)
+///     .map(|n| batcher.enqueue("calculator", "square", json!({ "n": n })))
+///     .collect();
+/// for result in futures::future::join_all(pending).await {
+///     println!("{:?}", result?.output);
+/// }
+/// batcher.close().await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TaskBatcher {
+    queue: mpsc::UnboundedSender<Queued>,
+    worker: JoinHandle<()>,
+}
+
+/// A task waiting for its batch, with where to send its result.
+struct Queued {
+    agent_id: String,
+    action: String,
+    input: Value,
+    reply: oneshot::Sender<Result<TaskResult, A2AError>>,
+}
+
+impl TaskBatcher {
+    /// Batch tasks for `agent` to send, looking targets up in
+    /// `directory_url`. `max_batch` is raised to at least 1. Must be called
+    /// within a Tokio runtime, where the batches are sent from.
+    pub fn new(agent: A2AAgent, directory_url: &str, max_batch: usize, max_delay: Duration) -> Self {
+        let (queue, tasks) = mpsc::unbounded_channel();
+        let worker = tokio::spawn(run(agent, directory_url.to_string(), tasks, max_batch.max(1), max_delay));
+        Self { queue, worker }
+    }
+
+    /// Buffer the task `action` for `agent_id`. It is queued at once; the
+    /// returned future resolves to its result once its batch came back,
+    /// and need not be polled for the task to be sent.
+    pub fn enqueue(
+        &self,
+        agent_id: &str,
+        action: &str,
+        input: Value,
+    ) -> impl Future<Output = Result<TaskResult, A2AError>> + Send + 'static {
+        let (reply, result) = oneshot::channel();
+        let queued = self.queue.send(Queued {
+            agent_id: agent_id.to_string(),
+            action: action.to_string(),
+            input,
+            reply,
+        });
+        async move {
+            queued.map_err(|_| closed())?;
+            result.await.unwrap_or_else(|_| Err(closed()))
+        }
+    }
+
+    /// Send the tasks still buffered and wait until their batch came back.
+    pub async fn close(self) {
+        drop(self.queue);
+        let _ = self.worker.await;
+    }
+}
+
+fn closed() -> A2AError {
+    A2AError::BatchFailed("the batcher stopped before sending the task".to_string())
+}
+
+/// Gather batches from `tasks` and send them, until every
+/// [`TaskBatcher`] handle is gone and the queue is drained.
+async fn run(agent: A2AAgent, directory_url: String, mut tasks: mpsc::UnboundedReceiver<Queued>, max_batch: usize, max_delay: Duration) {
+    while let Some(first) = tasks.recv().await {
+        let mut batch = vec![first];
+        let flush_at = tokio::time::sleep(max_delay);
+        tokio::pin!(flush_at);
+        while batch.len() < max_batch {
+            tokio::select! {
+                queued = tasks.recv() => match queued {
+                    Some(queued) => batch.push(queued),
+                    None => break,
+                },
+                _ = &mut flush_at => break,
+            }
+        }
+        send(&agent, &directory_url, batch).await;
+    }
+}
+
+async fn send(agent: &A2AAgent, directory_url: &str, batch: Vec<Queued>) {
+    debug!(tasks = batch.len(), "flushing task batch");
+    let (tasks, replies): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .map(|queued| ((queued.agent_id, queued.action, queued.input), queued.reply))
+        .unzip();
+    match agent.send_tasks_batch(tasks, directory_url).await {
+        Ok(results) => {
+            for (reply, result) in replies.into_iter().zip(results) {
+                let _ = reply.send(result);
+            }
+        }
+        Err(e) => {
+            let message = e.to_string();
+            for reply in replies {
+                let _ = reply.send(Err(A2AError::BatchFailed(message.clone())));
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! A synchronous [`A2AAgent`] for programs without an async runtime, such
+//! as command-line tools (`blocking` feature).
+//!
+//! Each call drives the async agent to completion on a runtime private to
+//! the blocking agent. Do not call it from async code: blocking inside a
+//! runtime panics. There, use [`crate::A2AAgent`] directly.
+//!
+//! ```rust,no_run
+//! use a2a::blocking::A2AAgent;
+//! use serde_json::json;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let agent = A2AAgent::new("cli", "CLI", vec![])?;
+//! let result = agent.send_task("calculator", "add", json!({"a": 1, "b": 2}), "http://localhost:8080")?;
+//! println!("{:?}", result.output);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::{A2AError, AgentInfo, CapabilityQuery, TaskResult};
+
+/// The blocking counterpart of [`crate::A2AAgent`], with the same
+/// methods minus `.await`. Configure it through the async agent's builder
+/// and convert with [`from_async`](Self::from_async).
+///
+/// Heartbeats, streaming and WebSocket sessions need a running runtime
+/// and are not offered.
+pub struct A2AAgent {
+    inner: crate::A2AAgent,
+    runtime: Runtime,
+}
+
+impl A2AAgent {
+    pub fn new(agent_id: &str, name: &str, capabilities: Vec<String>) -> Result<Self, A2AError> {
+        Self::from_async(crate::A2AAgent::new(agent_id, name, capabilities))
+    }
+
+    /// Wrap a configured async agent.
+    ///
+    /// ```rust,no_run
+    /// # fn example() -> Result<(), a2a::A2AError> {
+    /// use std::time::Duration;
+    ///
+    /// let agent = a2a::blocking::A2AAgent::from_async(
+    ///     a2a::A2AAgent::builder("cli", "CLI").timeout(Duration::from_secs(5)).build()?,
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_async(agent: crate::A2AAgent) -> Result<Self, A2AError> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { inner: agent, runtime })
+    }
+
+    /// The wrapped async agent, e.g. to read its `agent_id` or endpoint.
+    pub fn get_ref(&self) -> &crate::A2AAgent {
+        &self.inner
+    }
+
+    /// Unwrap the async agent, for use inside a runtime.
+    pub fn into_async(self) -> crate::A2AAgent {
+        self.inner
+    }
+
+    pub fn register(&mut self, endpoint: &str, directory_url: &str) -> Result<(), A2AError> {
+        self.runtime.block_on(self.inner.register(endpoint, directory_url))
+    }
+
+    pub fn deregister(&mut self, directory_url: &str) -> Result<(), A2AError> {
+        self.runtime.block_on(self.inner.deregister(directory_url))
+    }
+
+    pub fn ping(&self, endpoint: &str) -> Result<Duration, A2AError> {
+        self.runtime.block_on(self.inner.ping(endpoint))
+    }
+
+    pub fn fetch_card(&self, endpoint: &str) -> Result<AgentInfo, A2AError> {
+        self.runtime.block_on(self.inner.fetch_card(endpoint))
+    }
+
+    pub fn discover(&self, wanted_capabilities: Vec<String>, directory_url: &str) -> Result<Option<AgentInfo>, A2AError> {
+        self.runtime.block_on(self.inner.discover(wanted_capabilities, directory_url))
+    }
+
+    pub fn discover_all(&self, wanted_capabilities: Vec<String>, directory_url: &str) -> Result<Vec<AgentInfo>, A2AError> {
+        self.runtime.block_on(self.inner.discover_all(wanted_capabilities, directory_url))
+    }
+
+    pub fn discover_matching(&self, query: CapabilityQuery, directory_url: &str) -> Result<Vec<AgentInfo>, A2AError> {
+        self.runtime.block_on(self.inner.discover_matching(query, directory_url))
+    }
+
+    pub fn discover_filtered(
+        &self,
+        query: CapabilityQuery,
+        tags: HashMap<String, String>,
+        directory_url: &str,
+    ) -> Result<Vec<AgentInfo>, A2AError> {
+        self.runtime.block_on(self.inner.discover_filtered(query, tags, directory_url))
+    }
+
+    pub fn send_task(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        self.runtime
+            .block_on(self.inner.send_task(target_agent_id, action, input, directory_url))
+    }
+
+    pub fn send_task_typed<O: DeserializeOwned>(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<O, A2AError> {
+        self.runtime
+            .block_on(self.inner.send_task_typed(target_agent_id, action, input, directory_url))
+    }
+
+    pub fn send_task_with<I: Serialize + ?Sized>(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: &I,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        self.runtime
+            .block_on(self.inner.send_task_with(target_agent_id, action, input, directory_url))
+    }
+
+    pub fn send_tasks_batch(
+        &self,
+        tasks: Vec<(String, String, Value)>,
+        directory_url: &str,
+    ) -> Result<Vec<Result<TaskResult, A2AError>>, A2AError> {
+        self.runtime.block_on(self.inner.send_tasks_batch(tasks, directory_url))
+    }
+
+    pub fn submit_task(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        self.runtime
+            .block_on(self.inner.submit_task(target_agent_id, action, input, directory_url))
+    }
+
+    pub fn get_task_status(&self, agent_id: &str, task_id: &str, directory_url: &str) -> Result<TaskResult, A2AError> {
+        self.runtime
+            .block_on(self.inner.get_task_status(agent_id, task_id, directory_url))
+    }
+
+    pub fn cancel_task(&self, agent_id: &str, task_id: &str, directory_url: &str) -> Result<TaskResult, A2AError> {
+        self.runtime.block_on(self.inner.cancel_task(agent_id, task_id, directory_url))
+    }
+
+    pub fn wait_for_task(
+        &self,
+        agent_id: &str,
+        task_id: &str,
+        directory_url: &str,
+        interval: Duration,
+        deadline: Duration,
+    ) -> Result<TaskResult, A2AError> {
+        self.runtime.block_on(
+            self.inner
+                .wait_for_task(agent_id, task_id, directory_url, interval, deadline),
+        )
+    }
+}
@@ -0,0 +1,73 @@
+//! Debug logging of JSON-RPC bodies, with sensitive fields masked.
+
+use serde_json::Value;
+
+/// What a redacted field is logged as.
+const MASK: &str = "***";
+
+/// The paths to mask in every logged body, each split into its keys.
+#[derive(Debug)]
+pub(crate) struct BodyLog {
+    redact: Vec<Vec<String>>,
+}
+
+impl BodyLog {
+    /// Mask each dot-separated path in `redact`, such as
+    /// `params.input.password`.
+    pub(crate) fn new(redact: Vec<String>) -> Self {
+        Self {
+            redact: redact
+                .iter()
+                .map(|path| path.split('.').map(str::to_string).collect())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn request(&self, url: &str, body: &impl serde::Serialize) {
+        #[cfg(feature = "tracing")]
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            if let Ok(body) = serde_json::to_value(body) {
+                tracing::debug!(url, body = %self.redacted(body), "request body");
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (url, body);
+    }
+
+    pub(crate) fn response(&self, url: &str, body: &Value) {
+        #[cfg(feature = "tracing")]
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            tracing::debug!(url, body = %self.redacted(body.clone()), "response body");
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (url, body);
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    fn redacted(&self, mut body: Value) -> Value {
+        for path in &self.redact {
+            mask(&mut body, path);
+        }
+        body
+    }
+}
+
+/// Replace the value at `path` under `value` with [`MASK`]. Arrays along
+/// the way are descended into element by element, so `params.items.token`
+/// masks the token of every item.
+fn mask(value: &mut Value, path: &[String]) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| mask(item, path)),
+        Value::Object(fields) => {
+            let Some((key, rest)) = path.split_first() else {
+                return;
+            };
+            match fields.get_mut(key) {
+                Some(field) if rest.is_empty() => *field = Value::String(MASK.to_string()),
+                Some(field) => mask(field, rest),
+                None => {}
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,116 @@
+//! Client-side circuit breaking of peer calls, per target agent.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::time::Instant;
+use crate::{retry, A2AError};
+
+/// Stops calling a target agent that keeps failing, so callers fail fast
+/// instead of each waiting out the timeout.
+///
+/// Each target starts closed. After `failure_threshold` consecutive
+/// failures its circuit opens, and tasks, status polls and cancellations
+/// sent to it fail at once with [`A2AError::CircuitOpen`]. Once `cooldown`
+/// has passed the circuit half-opens: the next call goes through as a
+/// probe while others still fail fast. A successful probe closes the
+/// circuit; a failed one opens it for another `cooldown`.
+///
+/// Only connection failures, timeouts and HTTP 5xx responses count as
+/// failures. A JSON-RPC error means the peer is up, and resets the count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+        }
+    }
+}
+
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// A probe went out at `since`; if it never reports back, e.g.
+    /// because its caller gave up, another is let through after
+    /// `cooldown`.
+    HalfOpen { since: Instant },
+}
+
+pub(crate) struct Breakers {
+    config: CircuitBreaker,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl Breakers {
+    pub(crate) fn new(config: CircuitBreaker) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Let a call to `agent_id` through, or fail it if the circuit is open.
+    pub(crate) fn admit(&self, agent_id: &str) -> Result<(), A2AError> {
+        let now = Instant::now();
+        let mut circuits = self.circuits.lock().unwrap();
+        let Some(circuit) = circuits.get_mut(agent_id) else {
+            return Ok(());
+        };
+
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } if now < until => Err(A2AError::CircuitOpen(agent_id.to_string())),
+            Circuit::HalfOpen { since } if now.duration_since(since) < self.config.cooldown => {
+                Err(A2AError::CircuitOpen(agent_id.to_string()))
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => {
+                debug!(agent_id = %agent_id, "circuit half-open, probing");
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+        }
+    }
+
+    /// Record how an admitted call to `agent_id` ended.
+    pub(crate) fn record<T>(&self, agent_id: &str, result: &Result<T, A2AError>) {
+        let failed = result.as_ref().err().is_some_and(is_failure);
+        let mut circuits = self.circuits.lock().unwrap();
+        if !failed {
+            if let Some(Circuit::HalfOpen { .. } | Circuit::Open { .. }) = circuits.get(agent_id) {
+                info!(agent_id = %agent_id, "circuit closed");
+            }
+            circuits.remove(agent_id);
+            return;
+        }
+
+        let open = Circuit::Open {
+            until: Instant::now() + self.config.cooldown,
+        };
+        let circuit = circuits.entry(agent_id.to_string()).or_insert(Circuit::Closed { failures: 0 });
+        match circuit {
+            Circuit::Closed { failures } if *failures + 1 < self.config.failure_threshold => *failures += 1,
+            Circuit::Closed { .. } | Circuit::HalfOpen { .. } => {
+                warn!(agent_id = %agent_id, cooldown_ms = self.config.cooldown.as_millis() as u64, "circuit opened");
+                *circuit = open;
+            }
+            // A call admitted before the circuit opened.
+            Circuit::Open { .. } => {}
+        }
+    }
+}
+
+/// A peer that answers 429 is up, just busy, so throttling does not
+/// count against it.
+fn is_failure(error: &A2AError) -> bool {
+    match error {
+        A2AError::RateLimited { .. } => false,
+        _ => matches!(error, A2AError::Timeout(_)) || retry::is_retryable(error),
+    }
+}
@@ -0,0 +1,67 @@
+//! Sending one task to every agent that matches a query.
+
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+
+use crate::{A2AAgent, A2AError, AgentInfo, CapabilityQuery, TaskResult};
+
+/// How many broadcast tasks are in flight at once unless configured with
+/// [`A2AAgent::with_broadcast_concurrency`].
+pub(crate) const DEFAULT_BROADCAST_CONCURRENCY: usize = 16;
+
+impl A2AAgent {
+    /// Discover every agent satisfying `query` and send each the same task,
+    /// e.g. to warm caches, collecting every outcome rather than stopping
+    /// at the first success as [`send_task_any`](Self::send_task_any) does.
+    ///
+    /// Tasks run concurrently, at most
+    /// [`with_broadcast_concurrency`](Self::with_broadcast_concurrency) at
+    /// a time, and one agent failing does not affect the others. The
+    /// result pairs each discovered agent with its outcome, in discovery
+    /// order; only a failed discovery fails the whole call.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, CapabilityQuery};
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let query = CapabilityQuery::All(vec!["cache".to_string()]);
+    /// for (peer, outcome) in agent.broadcast_task(query, "warm", json!({}), "http://localhost:8080").await? {
+    ///     if let Err(e) = outcome {
+    ///         eprintln!("{} failed to warm: {}", peer.agent_id, e);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn broadcast_task(
+        &self,
+        query: CapabilityQuery,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<Vec<(AgentInfo, Result<TaskResult, A2AError>)>, A2AError> {
+        let agents = self.discover_matching(query, directory_url).await?;
+        debug!(agents = agents.len(), action, "broadcasting task");
+        let method = self.method("task");
+
+        let outcomes = stream::iter(agents)
+            .map(|agent| {
+                let (input, method) = (input.clone(), &method);
+                async move {
+                    let outcome = self
+                        .measured(action, self.send_to(&agent, action, None, input, &[], method))
+                        .await;
+                    if let Err(e) = &outcome {
+                        warn!(agent_id = %agent.agent_id, error = %e, "broadcast task failed");
+                    }
+                    (agent, outcome)
+                }
+            })
+            .buffered(self.broadcast_concurrency.max(1))
+            .collect()
+            .await;
+        Ok(outcomes)
+    }
+}
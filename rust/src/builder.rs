@@ -0,0 +1,598 @@
+//! Fluent construction of [`A2AAgent`].
+
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::body_log::BodyLog;
+use crate::breaker::Breakers;
+use crate::broadcast::DEFAULT_BROADCAST_CONCURRENCY;
+use crate::cache::DiscoveryCache;
+use crate::ratelimit::RateLimiter;
+use crate::{
+    normalize_path_prefix, transport::DEFAULT_MAX_RESPONSE_BYTES, transport::DEFAULT_USER_AGENT, A2AAgent, A2AError,
+    AgentIdPolicy, AuthConfig, Capability, CircuitBreaker, Codec, HttpTransport, Interceptor, Outbox, RateLimit,
+    RetryPolicy, TokenProvider, Transport,
+    DEFAULT_METHOD_NAMESPACE, DEFAULT_PATH_PREFIX, DEFAULT_TIMEOUT,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(50);
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 16;
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Builder for [`A2AAgent`], obtained from [`A2AAgent::builder`].
+///
+/// ```
+/// use a2a::{A2AAgent, AuthConfig};
+/// use std::time::Duration;
+///
+/// let agent = A2AAgent::builder("my-agent", "My Agent")
+///     .capabilities(["search", "summarize"])
+///     .timeout(Duration::from_secs(10))
+///     .auth(AuthConfig::Bearer("secret".to_string()))
+///     .max_retries(5)
+///     .build()?;
+///
+/// assert_eq!(agent.capabilities.len(), 2);
+/// # Ok::<(), a2a::A2AError>(())
+/// ```
+pub struct A2AAgentBuilder {
+    agent_id: String,
+    name: String,
+    capabilities: Vec<Capability>,
+    version: Option<String>,
+    description: Option<String>,
+    tags: HashMap<String, String>,
+    timeout: Duration,
+    client: Option<Client>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<reqwest::Proxy>,
+    #[cfg(not(target_arch = "wasm32"))]
+    env_proxy: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_idle_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_max_idle_per_host: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    tcp_keepalive: Option<Duration>,
+    retry: RetryPolicy,
+    validate_input: bool,
+    check_actions: bool,
+    dry_run: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    cancel_on_drop: bool,
+    broadcast_concurrency: usize,
+    agent_id_policy: AgentIdPolicy,
+    default_headers: HeaderMap,
+    user_agent: String,
+    max_response_bytes: usize,
+    body_logging: Option<Vec<String>>,
+    auth: Option<AuthConfig>,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    discovery_cache_ttl: Option<Duration>,
+    directory_url: Option<String>,
+    fallback_directories: Vec<String>,
+    outbox: Option<Arc<dyn Outbox>>,
+    rate_limit: Option<RateLimit>,
+    circuit_breaker: Option<CircuitBreaker>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    transport: Option<Arc<dyn Transport>>,
+    path_prefix: String,
+    method_namespace: String,
+    #[cfg(feature = "tls")]
+    tls: Option<crate::TlsConfig>,
+    #[cfg(feature = "signing")]
+    verifying_keys: HashMap<String, crate::VerifyingKey>,
+    codec: Option<Arc<dyn Codec>>,
+    #[cfg(feature = "compression")]
+    compression: Option<crate::Compression>,
+}
+
+impl A2AAgentBuilder {
+    pub(crate) fn new(agent_id: &str, name: &str) -> Self {
+        Self {
+            agent_id: agent_id.to_string(),
+            name: name.to_string(),
+            capabilities: Vec::new(),
+            version: None,
+            description: None,
+            tags: HashMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+            client: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            env_proxy: true,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            #[cfg(not(target_arch = "wasm32"))]
+            tcp_keepalive: Some(DEFAULT_TCP_KEEPALIVE),
+            retry: RetryPolicy::default(),
+            validate_input: false,
+            check_actions: false,
+            dry_run: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            cancel_on_drop: false,
+            broadcast_concurrency: DEFAULT_BROADCAST_CONCURRENCY,
+            agent_id_policy: AgentIdPolicy::new(),
+            default_headers: HeaderMap::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            body_logging: None,
+            auth: None,
+            token_provider: None,
+            discovery_cache_ttl: None,
+            directory_url: None,
+            fallback_directories: Vec::new(),
+            outbox: None,
+            rate_limit: None,
+            circuit_breaker: None,
+            interceptors: Vec::new(),
+            transport: None,
+            path_prefix: DEFAULT_PATH_PREFIX.to_string(),
+            method_namespace: DEFAULT_METHOD_NAMESPACE.to_string(),
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "signing")]
+            verifying_keys: HashMap::new(),
+            codec: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+        }
+    }
+
+    /// Capabilities to advertise, as plain names or described [`Capability`] values.
+    pub fn capabilities<I, C>(mut self, capabilities: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Capability>,
+    {
+        self.capabilities = capabilities.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Version to advertise on registration.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Description to advertise on registration.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add the tag `key = value`; see [`A2AAgent::with_tag`].
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Request and connect timeout. Ignored when a custom [`client`](Self::client) is set.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Client identity and extra root CAs for mutual TLS. Ignored when a
+    /// custom [`client`](Self::client) is set.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: crate::TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Send every request through the HTTP proxy at `url`, authenticating
+    /// with `credentials` (user name and password) if given. Fails if
+    /// `url` is not a valid proxy URL.
+    ///
+    /// The explicit proxy takes precedence over `HTTP_PROXY` and
+    /// `HTTPS_PROXY`; hosts listed in `NO_PROXY` still connect directly
+    /// unless [`env_proxy(false)`](Self::env_proxy). Ignored when a custom
+    /// [`client`](Self::client) is set.
+    ///
+    /// ```
+    /// let agent = a2a::A2AAgent::builder("my-agent", "My Agent")
+    ///     .proxy("http://proxy.internal:3128", Some(("user", "secret")))?
+    ///     .build()?;
+    /// # Ok::<(), a2a::A2AError>(())
+    /// ```
+    ///
+    /// Not available on `wasm32`, where the browser picks the proxy.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, url: &str, credentials: Option<(&str, &str)>) -> Result<Self, crate::A2AError> {
+        let proxy = reqwest::Proxy::all(url)?;
+        self.proxy = Some(match credentials {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        });
+        Ok(self)
+    }
+
+    /// Whether to honor the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables (default `true`). With `false` and no
+    /// explicit [`proxy`](Self::proxy), every request connects directly.
+    /// Ignored when a custom [`client`](Self::client) is set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn env_proxy(mut self, env_proxy: bool) -> Self {
+        self.env_proxy = env_proxy;
+        self
+    }
+
+    /// Close pooled connections left idle for `timeout`, or never with
+    /// `None` (default: 50s). Keep it below the idle timeout of the peers
+    /// and of any proxy or load balancer in between: a connection they
+    /// close first is reset when the client reuses it. Each client draws
+    /// its timeout from the 10% below `timeout`, so a mesh of agents does
+    /// not drop and reopen its connections in lockstep. Ignored when a
+    /// custom [`client`](Self::client) is set.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let agent = a2a::A2AAgent::builder("my-agent", "My Agent")
+    ///     .pool_idle_timeout(Some(Duration::from_secs(25)))
+    ///     .pool_max_idle_per_host(64)
+    ///     .tcp_keepalive(Some(Duration::from_secs(15)))
+    ///     .build()?;
+    /// # Ok::<(), a2a::A2AError>(())
+    /// ```
+    ///
+    /// Not available on `wasm32`, where the browser manages connections,
+    /// nor are the other connection settings.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Keep at most `max` idle connections per host for reuse (default:
+    /// 16). Raise it for agents sending many concurrent tasks to one peer,
+    /// so bursts reuse connections instead of opening new ones; `0`
+    /// disables reuse. Ignored when a custom [`client`](Self::client) is
+    /// set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Send TCP keepalive probes on connections idle for `interval`, or
+    /// none with `None` (default: 30s). The probes keep NAT and firewall
+    /// state alive for pooled connections and find dead peers early.
+    /// Ignored when a custom [`client`](Self::client) is set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Send requests through an existing, possibly shared, client.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Headers for every request; see [`A2AAgent::with_default_headers`].
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// The `User-Agent` to send; see [`A2AAgent::with_user_agent`].
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// How calls are encoded; see [`A2AAgent::with_codec`].
+    pub fn codec(mut self, codec: Arc<dyn Codec>) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    /// Body compression; see [`A2AAgent::with_compression`].
+    #[cfg(feature = "compression")]
+    pub fn compression(mut self, compression: crate::Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Response body limit; see [`A2AAgent::with_max_response_bytes`].
+    pub fn max_response_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = max_bytes;
+        self
+    }
+
+    /// Redacted body logging; see [`A2AAgent::with_body_logging`].
+    pub fn body_logging(mut self, redact: Vec<String>) -> Self {
+        self.body_logging = Some(redact);
+        self
+    }
+
+    pub fn auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = Some(provider);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.retry.max_retries = max_retries;
+        self
+    }
+
+    pub fn base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.retry.base_backoff = base_backoff;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn validate_input(mut self, validate_input: bool) -> Self {
+        self.validate_input = validate_input;
+        self
+    }
+
+    /// Check actions against the target's capabilities; see [`A2AAgent::with_action_check`].
+    pub fn check_actions(mut self, check_actions: bool) -> Self {
+        self.check_actions = check_actions;
+        self
+    }
+
+    /// Build tasks without sending them; see [`A2AAgent::with_dry_run`].
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Cancel tasks whose call is dropped; see [`A2AAgent::with_cancel_on_drop`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cancel_on_drop(mut self, cancel_on_drop: bool) -> Self {
+        self.cancel_on_drop = cancel_on_drop;
+        self
+    }
+
+    /// Bound a broadcast's parallelism; see [`A2AAgent::with_broadcast_concurrency`].
+    pub fn broadcast_concurrency(mut self, max: usize) -> Self {
+        self.broadcast_concurrency = max;
+        self
+    }
+
+    /// Check the ID and name against `policy`; see [`A2AAgent::with_agent_id_policy`].
+    pub fn agent_id_policy(mut self, policy: AgentIdPolicy) -> Self {
+        self.agent_id_policy = policy;
+        self
+    }
+
+    /// Cache discovery results for `ttl`; see [`A2AAgent::with_discovery_cache`].
+    pub fn discovery_cache(mut self, ttl: Duration) -> Self {
+        self.discovery_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Append to the interceptor chain; see [`Interceptor`].
+    pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Route calls through `transport`; see [`A2AAgent::with_transport`].
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Require signed results from `agent_id`; see [`A2AAgent::with_verifying_key`].
+    #[cfg(feature = "signing")]
+    pub fn verifying_key(mut self, agent_id: impl Into<String>, key: crate::VerifyingKey) -> Self {
+        self.verifying_keys.insert(agent_id.into(), key);
+        self
+    }
+
+    /// Directory path prefix; see [`A2AAgent::with_path_prefix`].
+    pub fn path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefix = normalize_path_prefix(prefix);
+        self
+    }
+
+    /// JSON-RPC method namespace; see [`A2AAgent::with_method_namespace`].
+    pub fn method_namespace(mut self, namespace: &str) -> Self {
+        self.method_namespace = namespace.trim_matches('/').to_string();
+        self
+    }
+
+    /// Per-target task rate limit; see [`RateLimit`].
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Per-target circuit breaker; see [`CircuitBreaker`].
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// The directory the agent is meant to use; see [`A2AAgent::directory_url`].
+    pub fn directory_url(mut self, directory_url: impl Into<String>) -> Self {
+        self.directory_url = Some(directory_url.into());
+        self
+    }
+
+    /// Directories to fail over to; see [`A2AAgent::with_fallback_directories`].
+    pub fn fallback_directories<I, S>(mut self, directory_urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fallback_directories = directory_urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Where to keep unacknowledged submits; see [`A2AAgent::with_outbox`].
+    pub fn outbox(mut self, outbox: Arc<dyn Outbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// The configured agent. Fails with [`A2AError::Tls`] if its TLS
+    /// settings cannot be used, or [`A2AError::Config`] if the HTTP client
+    /// cannot be built from the rest, such as the proxy.
+    pub fn build(self) -> Result<A2AAgent, A2AError> {
+        #[cfg(target_arch = "wasm32")]
+        let timeout = self.client.is_none().then_some(self.timeout);
+        #[cfg(target_arch = "wasm32")]
+        let client = self.client.unwrap_or_default();
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let builder = Client::builder()
+                    .timeout(self.timeout)
+                    .connect_timeout(self.timeout)
+                    .pool_idle_timeout(self.pool_idle_timeout.map(jittered))
+                    .pool_max_idle_per_host(self.pool_max_idle_per_host)
+                    .tcp_keepalive(self.tcp_keepalive);
+                let builder = match self.proxy {
+                    Some(proxy) => {
+                        let no_proxy = if self.env_proxy { reqwest::NoProxy::from_env() } else { None };
+                        builder.proxy(proxy.no_proxy(no_proxy))
+                    }
+                    None if !self.env_proxy => builder.no_proxy(),
+                    None => builder,
+                };
+                #[cfg(feature = "tls")]
+                let builder = match &self.tls {
+                    Some(tls) => tls.apply(builder),
+                    None => builder,
+                };
+                builder.build().map_err(|e| {
+                    #[cfg(feature = "tls")]
+                    if self.tls.is_some() {
+                        return A2AError::Tls(e);
+                    }
+                    A2AError::Config(format!("cannot build the HTTP client: {}", e))
+                })?
+            }
+        };
+
+        Ok(A2AAgent {
+            agent_id: self.agent_id,
+            name: self.name,
+            capabilities: self.capabilities,
+            version: self.version,
+            description: self.description,
+            tags: self.tags,
+            endpoint: None,
+            directory_url: self.directory_url,
+            http: HttpTransport {
+                client,
+                default_headers: self.default_headers,
+                user_agent: self.user_agent,
+                auth: self.auth,
+                token_provider: self.token_provider,
+                interceptors: self.interceptors,
+                max_response_bytes: self.max_response_bytes,
+                body_log: self.body_logging.map(|redact| Arc::new(BodyLog::new(redact))),
+                codec: self.codec,
+                #[cfg(feature = "compression")]
+                compression: self.compression,
+                #[cfg(target_arch = "wasm32")]
+                timeout,
+            },
+            transport: self.transport,
+            retry: self.retry,
+            validate_input: self.validate_input,
+            check_actions: self.check_actions,
+            dry_run: self.dry_run,
+            #[cfg(not(target_arch = "wasm32"))]
+            cancel_on_drop: self.cancel_on_drop,
+            broadcast_concurrency: self.broadcast_concurrency,
+            agent_id_policy: self.agent_id_policy,
+            discovery_cache: self.discovery_cache_ttl.map(|ttl| Arc::new(DiscoveryCache::new(ttl))),
+            fallback_directories: self.fallback_directories,
+            outbox: self.outbox,
+            rate_limiter: self.rate_limit.map(|limit| Arc::new(RateLimiter::new(limit))),
+            circuit_breakers: self.circuit_breaker.map(|breaker| Arc::new(Breakers::new(breaker))),
+            selector: Arc::default(),
+            #[cfg(feature = "signing")]
+            verifying_keys: self.verifying_keys,
+            path_prefix: self.path_prefix,
+            method_namespace: self.method_namespace,
+        })
+    }
+}
+
+/// `timeout` shortened by up to a tenth, at random.
+#[cfg(not(target_arch = "wasm32"))]
+fn jittered(timeout: Duration) -> Duration {
+    timeout.mul_f64(1.0 - fastrand::f64() / 10.0)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::A2AServer;
+
+    #[test]
+    fn connection_settings_reach_the_builder() {
+        let builder = A2AAgent::builder("calc", "Calc");
+        assert_eq!(builder.pool_idle_timeout, Some(DEFAULT_POOL_IDLE_TIMEOUT));
+        assert_eq!(builder.pool_max_idle_per_host, DEFAULT_POOL_MAX_IDLE_PER_HOST);
+        assert_eq!(builder.tcp_keepalive, Some(DEFAULT_TCP_KEEPALIVE));
+
+        let builder = builder
+            .pool_idle_timeout(Some(Duration::from_secs(25)))
+            .pool_max_idle_per_host(64)
+            .tcp_keepalive(None);
+        assert_eq!(builder.pool_idle_timeout, Some(Duration::from_secs(25)));
+        assert_eq!(builder.pool_max_idle_per_host, 64);
+        assert_eq!(builder.tcp_keepalive, None);
+    }
+
+    #[test]
+    fn idle_timeouts_are_jittered_down_by_a_tenth_at_most() {
+        let timeout = Duration::from_secs(50);
+        for _ in 0..100 {
+            let jittered = jittered(timeout);
+            assert!(jittered <= timeout && jittered >= Duration::from_secs(45), "{:?}", jittered);
+        }
+    }
+
+    #[tokio::test]
+    async fn agents_built_with_any_settings_send_requests() {
+        let server = A2AServer::new("echo", "Echo", vec![], 0);
+        let (addr, serving) = server.bind().await.unwrap();
+        tokio::spawn(serving);
+        let endpoint = format!("http://127.0.0.1:{}", addr.port());
+
+        let tuned = A2AAgent::builder("client", "Client")
+            .pool_idle_timeout(Some(Duration::from_millis(1)))
+            .pool_max_idle_per_host(64)
+            .tcp_keepalive(Some(Duration::from_secs(1)));
+        let disabled = A2AAgent::builder("client", "Client")
+            .pool_idle_timeout(None)
+            .pool_max_idle_per_host(0)
+            .tcp_keepalive(None);
+        for agent in [A2AAgent::builder("client", "Client"), tuned, disabled] {
+            let agent = agent.build().unwrap();
+            agent.ping(&endpoint).await.unwrap();
+            agent.ping(&endpoint).await.unwrap();
+        }
+    }
+}
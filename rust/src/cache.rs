@@ -0,0 +1,54 @@
+//! In-memory cache of discovery results.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::time::Instant;
+use crate::{AgentInfo, CapabilityQuery};
+
+/// Directory URL, the normalized query and the sorted tag filter.
+type CacheKey = (String, CapabilityQuery, Vec<(String, String)>);
+
+/// Discovery results remembered for `ttl`, shared by clones of an agent.
+pub(crate) struct DiscoveryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, (Instant, Vec<AgentInfo>)>>,
+}
+
+impl DiscoveryCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn key(directory_url: &str, query: &CapabilityQuery, tags: &HashMap<String, String>) -> CacheKey {
+        let mut tags: Vec<_> = tags.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+        tags.sort();
+        (directory_url.trim_end_matches('/').to_string(), query.normalized(), tags)
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Vec<AgentInfo>> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored, agents)) if stored.elapsed() < self.ttl => Some(agents.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: CacheKey, agents: Vec<AgentInfo>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), agents));
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,52 @@
+//! Tokens for cancelling running tasks through `a2a/task/cancel`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// Tokens of the tasks currently running, by sender and `task_id`, so a
+/// caller can only cancel the tasks it sent.
+#[derive(Clone, Default)]
+pub(crate) struct Cancellations {
+    tokens: Arc<Mutex<HashMap<(String, String), CancellationToken>>>,
+}
+
+impl Cancellations {
+    /// A token for `sender`'s task `task_id`, cancellable until the
+    /// [`Registration`] is dropped.
+    pub(crate) fn register(&self, sender: &str, task_id: &str) -> (CancellationToken, Registration) {
+        let token = CancellationToken::new();
+        let key = (sender.to_string(), task_id.to_string());
+        self.tokens.lock().unwrap().insert(key.clone(), token.clone());
+        let registration = Registration {
+            tokens: self.tokens.clone(),
+            key,
+        };
+        (token, registration)
+    }
+
+    /// Cancel `sender`'s task `task_id` if it is running. Returns whether
+    /// it was.
+    pub(crate) fn cancel(&self, sender: &str, task_id: &str) -> bool {
+        let key = (sender.to_string(), task_id.to_string());
+        match self.tokens.lock().unwrap().get(&key) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Keeps a task cancellable for as long as it is held.
+pub(crate) struct Registration {
+    tokens: Arc<Mutex<HashMap<(String, String), CancellationToken>>>,
+    key: (String, String),
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.tokens.lock().unwrap().remove(&self.key);
+    }
+}
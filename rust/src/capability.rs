@@ -0,0 +1,141 @@
+//! Capability descriptions carried in registrations and agent info.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{Version, VersionReq};
+
+/// A named capability, optionally documented with JSON schemas for the
+/// task input it accepts and the output it produces.
+///
+/// An agent declaring several versions of one action's contract lists one
+/// capability per version; tasks pick one with
+/// [`send_task_versioned`](crate::A2AAgent::send_task_versioned).
+///
+/// On the wire a capability without any schema or description is written
+/// as a plain string, so directories that only understand string lists
+/// keep working. Both forms are accepted when deserializing.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Capability {
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: Option<Value>,
+    pub output_schema: Option<Value>,
+    pub version: Option<Version>,
+}
+
+impl Capability {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_input_schema(mut self, schema: Value) -> Self {
+        self.input_schema = Some(schema);
+        self
+    }
+
+    pub fn with_output_schema(mut self, schema: Value) -> Self {
+        self.output_schema = Some(schema);
+        self
+    }
+
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Whether this capability fits `wanted`, either a plain name or
+    /// `name@requirement`, e.g. `add@^2.0`, which only an equally named
+    /// capability whose version satisfies the requirement fits.
+    pub(crate) fn fits(&self, wanted: &str) -> bool {
+        let Some((name, requirement)) = wanted.split_once('@') else {
+            return self.name == wanted;
+        };
+        let (Some(version), Ok(requirement)) = (&self.version, requirement.parse::<VersionReq>()) else {
+            return false;
+        };
+        self.name == name && requirement.matches(version)
+    }
+
+    /// Whether this and `other` describe the same contract, i.e. the same
+    /// version of the same capability, so one replaces the other.
+    pub(crate) fn replaces(&self, other: &Capability) -> bool {
+        self.name == other.name && self.version == other.version
+    }
+
+    fn is_bare(&self) -> bool {
+        self.description.is_none() && self.input_schema.is_none() && self.output_schema.is_none() && self.version.is_none()
+    }
+}
+
+impl From<&str> for Capability {
+    fn from(name: &str) -> Self {
+        Capability::new(name)
+    }
+}
+
+impl From<String> for Capability {
+    fn from(name: String) -> Self {
+        Capability::new(name)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CapabilityObject {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "inputSchema", alias = "input_schema", default, skip_serializing_if = "Option::is_none")]
+    input_schema: Option<Value>,
+    #[serde(rename = "outputSchema", alias = "output_schema", default, skip_serializing_if = "Option::is_none")]
+    output_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<Version>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CapabilityRepr {
+    Name(String),
+    Object(CapabilityObject),
+}
+
+impl Serialize for Capability {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_bare() {
+            return serializer.serialize_str(&self.name);
+        }
+
+        CapabilityObject {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            input_schema: self.input_schema.clone(),
+            output_schema: self.output_schema.clone(),
+            version: self.version,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Capability {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match CapabilityRepr::deserialize(deserializer)? {
+            CapabilityRepr::Name(name) => Capability::new(name),
+            CapabilityRepr::Object(object) => Capability {
+                name: object.name,
+                description: object.description,
+                input_schema: object.input_schema,
+                output_schema: object.output_schema,
+                version: object.version,
+            },
+        })
+    }
+}
@@ -0,0 +1,247 @@
+//! How JSON-RPC messages are put into bytes on the wire.
+//!
+//! JSON is the default and what every peer speaks. An agent with
+//! [`A2AAgent::with_codec`](crate::A2AAgent::with_codec) sends its calls
+//! in another codec and asks for answers in it; an
+//! [`A2AServer`](crate::A2AServer) or
+//! [`A2ADirectory`](crate::A2ADirectory) reads calls in any codec it knows,
+//! picked by `Content-Type`, and answers in the first type of the caller's
+//! `Accept` it speaks, else in the codec of the call. Streams, `GET`
+//! endpoints and the root part of `multipart/related` calls stay JSON.
+
+use serde_json::Value;
+
+use crate::A2AError;
+
+/// Turns JSON-RPC messages into bodies of one media type and back.
+///
+/// Messages are handled as [`serde_json::Value`]s, so a codec must carry
+/// at least what JSON can: `null`, booleans, numbers, strings, arrays and
+/// objects with string keys.
+///
+/// ```
+/// use a2a::{Codec, JsonCodec};
+/// use serde_json::json;
+///
+/// let message = json!({"jsonrpc": "2.0", "id": 1, "method": "a2a/task"});
+/// let bytes = JsonCodec.encode(&message)?;
+/// assert_eq!(JsonCodec.decode(&bytes)?, message);
+/// # Ok::<(), a2a::A2AError>(())
+/// ```
+pub trait Codec: Send + Sync {
+    /// The media type of encoded bodies, e.g. `application/json`.
+    fn content_type(&self) -> &str;
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, A2AError>;
+
+    /// Decode a whole body; failures are [`A2AError::Serde`].
+    fn decode(&self, bytes: &[u8]) -> Result<Value, A2AError>;
+}
+
+/// JSON, the interoperable default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn content_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, A2AError> {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, A2AError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// [MessagePack](https://msgpack.org), a compact binary encoding of the
+/// same data, as `application/msgpack` (`msgpack` feature), through
+/// `rmp-serde`. Maps keep their string keys, integers use the smallest
+/// format that holds them, and floats are 64-bit. Binary and extension
+/// values cannot be decoded, having no JSON counterpart.
+///
+/// ```
+/// use a2a::{Codec, MsgPackCodec};
+/// use serde_json::json;
+///
+/// let message = json!({"taskId": "t-1", "output": [1, -2, 3.5, null, true]});
+/// let bytes = MsgPackCodec.encode(&message)?;
+/// assert!(bytes.len() < serde_json::to_vec(&message)?.len());
+/// assert_eq!(MsgPackCodec.decode(&bytes)?, message);
+/// # Ok::<(), a2a::A2AError>(())
+/// ```
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgPackCodec {
+    fn content_type(&self) -> &str {
+        "application/msgpack"
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, A2AError> {
+        rmp_serde::to_vec_named(value).map_err(invalid_msgpack)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, A2AError> {
+        let mut deserializer = rmp_serde::Deserializer::new(bytes);
+        let value = serde::Deserialize::deserialize(&mut deserializer).map_err(invalid_msgpack)?;
+        if !deserializer.get_ref().is_empty() {
+            return Err(invalid_msgpack("trailing bytes after the value"));
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn invalid_msgpack(e: impl std::fmt::Display) -> A2AError {
+    A2AError::Serde(serde::de::Error::custom(format!("invalid MessagePack: {}", e)))
+}
+
+/// The media type of a `Content-Type` or `Accept` entry, without its
+/// parameters.
+pub(crate) fn media_type(value: &str) -> &str {
+    value.split(';').next().unwrap_or_default().trim()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use server::{decode, respond, Codecs};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::Json;
+    use serde::Serialize;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    use super::{media_type, Codec};
+    use crate::A2AError;
+
+    /// The codecs a server or directory reads and answers in besides JSON,
+    /// shared by clones.
+    #[derive(Clone)]
+    pub(crate) struct Codecs(Arc<Vec<Arc<dyn Codec>>>);
+
+    impl Default for Codecs {
+        /// The codecs built in with the enabled features.
+        fn default() -> Self {
+            #[cfg(feature = "msgpack")]
+            let codecs: Vec<Arc<dyn Codec>> = vec![Arc::new(super::MsgPackCodec)];
+            #[cfg(not(feature = "msgpack"))]
+            let codecs = Vec::new();
+            Self(Arc::new(codecs))
+        }
+    }
+
+    /// The codec picked for one exchange; `None` is JSON.
+    pub(crate) type Negotiated = Option<Arc<dyn Codec>>;
+
+    impl Codecs {
+        /// Also speak `codec`, ahead of any for the same media type.
+        pub(crate) fn with(self, codec: Arc<dyn Codec>) -> Self {
+            let mut codecs = vec![codec];
+            codecs.extend(self.0.iter().cloned());
+            Self(Arc::new(codecs))
+        }
+
+        fn find(&self, value: &str) -> Option<&Arc<dyn Codec>> {
+            let media_type = media_type(value);
+            self.0
+                .iter()
+                .find(|codec| codec.content_type().eq_ignore_ascii_case(media_type))
+        }
+
+        /// The codec of a call with `headers`. Calls declaring no type, or
+        /// one no codec is for, are read as JSON.
+        pub(crate) fn for_request(&self, headers: &HeaderMap) -> Negotiated {
+            let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok())?;
+            self.find(content_type).cloned()
+        }
+
+        /// The codec to answer a call with `headers` in: the first type
+        /// listed in its `Accept` that is spoken, else `request`'s.
+        pub(crate) fn for_response(&self, headers: &HeaderMap, request: &Negotiated) -> Negotiated {
+            let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or_default();
+            for entry in accept.split(',') {
+                if is_json(entry) {
+                    return None;
+                }
+                if let Some(codec) = self.find(entry) {
+                    return Some(codec.clone());
+                }
+            }
+            request.clone()
+        }
+    }
+
+    /// Whether `value` declares JSON, as `application/json` or a `+json`
+    /// type.
+    fn is_json(value: &str) -> bool {
+        let media_type = media_type(value);
+        media_type.eq_ignore_ascii_case("application/json") || media_type.ends_with("+json")
+    }
+
+    /// Parse a call body in `codec`.
+    pub(crate) fn decode(codec: &Negotiated, body: &[u8]) -> Result<Value, String> {
+        match codec {
+            None => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            Some(codec) => codec.decode(body).map_err(|e| match e {
+                A2AError::Serde(e) => e.to_string(),
+                e => e.to_string(),
+            }),
+        }
+    }
+
+    /// `message` as a response body in `codec`.
+    pub(crate) fn respond<T: Serialize>(codec: &Negotiated, message: &T) -> Response {
+        let Some(codec) = codec else {
+            return Json(message).into_response();
+        };
+        let body = serde_json::to_value(message)
+            .map_err(Into::into)
+            .and_then(|value| codec.encode(&value));
+        match (body, HeaderValue::from_str(codec.content_type())) {
+            (Ok(body), Ok(content_type)) => ([(header::CONTENT_TYPE, content_type)], body).into_response(),
+            (Err(e), _) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            (_, Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgpack"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn msgpack_round_trips_json_values() {
+        let message = json!({
+            "small": 1, "negative": -200, "large": u64::MAX, "min": i64::MIN,
+            "float": 0.1, "text": "héllo", "nested": [[{"a": null}], true, false],
+        });
+        let bytes = MsgPackCodec.encode(&message).unwrap();
+        assert_eq!(MsgPackCodec.decode(&bytes).unwrap(), message);
+
+        // As a map with named keys, not a positional array.
+        assert_eq!(MsgPackCodec.encode(&json!({"a": 1})).unwrap(), [0x81, 0xa1, b'a', 0x01]);
+    }
+
+    #[test]
+    fn msgpack_refuses_what_json_cannot_hold() {
+        let refused = [
+            &[0xc4, 0x01, 0x00][..], // binary
+            &[0xd4, 0x01, 0x00],     // fixext 1
+            &[0x81, 0x01, 0x02],     // integer map key
+            &[0x92, 0x01],           // truncated array
+            &[0x01, 0x02],           // trailing bytes
+        ];
+        for bytes in refused {
+            assert!(matches!(MsgPackCodec.decode(bytes), Err(A2AError::Serde(_))), "{:x?}", bytes);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! `Content-Encoding` compression of request and response bodies
+//! (`compression` feature), through `flate2`.
+//!
+//! An agent with [`A2AAgent::with_compression`](crate::A2AAgent::with_compression)
+//! advertises `Accept-Encoding: gzip, deflate`, decompresses responses and
+//! compresses request bodies of at least `min_bytes`. A server with
+//! [`A2AServer::with_compression`](crate::A2AServer::with_compression)
+//! decompresses such requests and compresses JSON responses of at least
+//! `min_bytes` for callers that accept it. Task streams are sent as is.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use serde_json::Value;
+use std::io::{Read, Write};
+
+use crate::server::{error_response, PARSE_ERROR};
+use crate::A2AError;
+
+/// The largest request body a server inflates, 16 MiB.
+const MAX_INFLATED_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+/// A `Content-Encoding` this crate can produce and read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    /// The zlib format, which is what HTTP calls `deflate`.
+    Deflate,
+}
+
+impl Encoding {
+    /// The header token, as in `"gzip"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            _ => None,
+        }
+    }
+}
+
+/// How to compress bodies: with `encoding`, once they reach `min_bytes`.
+/// Smaller bodies are sent as is, since compressing them saves little.
+///
+/// ```
+/// use a2a::{A2AAgent, Compression, Encoding};
+///
+/// let agent = A2AAgent::new("my-agent", "My Agent", vec![]).with_compression(Compression {
+///     encoding: Encoding::Deflate,
+///     min_bytes: 64 * 1024,
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub encoding: Encoding,
+    pub min_bytes: usize,
+}
+
+impl Default for Compression {
+    /// Gzip for bodies of 1 KiB or more.
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::Gzip,
+            min_bytes: 1024,
+        }
+    }
+}
+
+/// What a client with compression on sends as `Accept-Encoding`.
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Compress `data` into `encoding`.
+pub(crate) fn compress(encoding: Encoding, data: &[u8]) -> Result<Vec<u8>, A2AError> {
+    let level = flate2::Compression::default();
+    let compressed = match encoding {
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|()| encoder.finish())
+        }
+        Encoding::Deflate => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|()| encoder.finish())
+        }
+    };
+    compressed.map_err(|e| A2AError::Compression(e.to_string()))
+}
+
+/// Decompress `data` read as `encoding`, failing with
+/// [`A2AError::ResponseTooLarge`] once the output passes `limit`.
+pub(crate) fn decompress(encoding: Encoding, data: &[u8], limit: usize) -> Result<Vec<u8>, A2AError> {
+    // Held to its declared format, so mislabelled bodies fail.
+    let decoder: Box<dyn Read + '_> = match encoding {
+        Encoding::Gzip => Box::new(GzDecoder::new(data)),
+        Encoding::Deflate => Box::new(ZlibDecoder::new(data)),
+    };
+    let mut output = Vec::new();
+    decoder
+        .take(limit.saturating_add(1) as u64)
+        .read_to_end(&mut output)
+        .map_err(|e| A2AError::Compression(e.to_string()))?;
+    if output.len() > limit {
+        return Err(A2AError::ResponseTooLarge { limit });
+    }
+    Ok(output)
+}
+
+/// The encoding a body with these headers was sent in. `Ok(None)` means
+/// none, or `identity`.
+pub(crate) fn content_encoding(headers: &HeaderMap) -> Result<Option<Encoding>, A2AError> {
+    let Some(value) = headers.get(header::CONTENT_ENCODING) else {
+        return Ok(None);
+    };
+    let value = value.to_str().unwrap_or_default().trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("identity") {
+        return Ok(None);
+    }
+    Encoding::parse(value)
+        .map(Some)
+        .ok_or_else(|| A2AError::Compression(format!("unsupported Content-Encoding: {}", value)))
+}
+
+/// The encoding to answer a request with `Accept-Encoding: accept` in,
+/// preferring `preferred`.
+fn negotiate(accept: &str, preferred: Encoding) -> Option<Encoding> {
+    let accepted: Vec<Encoding> = accept
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let token = parts.next()?.trim();
+            // `q=0` means "not acceptable".
+            let refused = parts.any(|param| {
+                let param = param.trim();
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            if refused {
+                return None;
+            }
+            if token == "*" {
+                return Some(preferred);
+            }
+            Encoding::parse(token)
+        })
+        .collect();
+
+    if accepted.contains(&preferred) {
+        return Some(preferred);
+    }
+    accepted.first().copied()
+}
+
+/// Server middleware: inflate compressed requests, and compress large
+/// JSON responses for callers that accept it.
+pub(crate) async fn layer(State(compression): State<Compression>, request: Request, next: Next) -> Response {
+    let request = match inflate_request(request).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+
+    let accept = request
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json || response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+    let Some(encoding) = negotiate(&accept, compression.encoding) else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    parts.headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if body.len() < compression.min_bytes {
+        return Response::from_parts(parts, Body::from(body));
+    }
+
+    match compress(encoding, &body) {
+        Ok(compressed) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts
+                .headers
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        Err(e) => {
+            warn!(error = %e, "compressing response failed");
+            Response::from_parts(parts, Body::from(body))
+        }
+    }
+}
+
+/// `request` with its body inflated, or the response refusing it.
+async fn inflate_request(request: Request) -> Result<Request, Response> {
+    let encoding = match content_encoding(request.headers()) {
+        Ok(Some(encoding)) => encoding,
+        Ok(None) => return Ok(request),
+        Err(e) => return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string()).into_response()),
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_INFLATED_REQUEST_BYTES)
+        .await
+        .map_err(|e| (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response())?;
+    let body = decompress(encoding, &body, MAX_INFLATED_REQUEST_BYTES).map_err(|e| {
+        let message = format!("Parse error: {}", e);
+        Json(error_response(Value::Null, PARSE_ERROR, message)).into_response()
+    })?;
+
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Ok(Request::from_parts(parts, Body::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_encodings_round_trip() {
+        let data = "abc".repeat(10_000).into_bytes();
+        for encoding in [Encoding::Gzip, Encoding::Deflate] {
+            let compressed = compress(encoding, &data).unwrap();
+            assert!(compressed.len() < data.len() / 10);
+            assert_eq!(decompress(encoding, &compressed, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn bad_bodies_are_refused() {
+        let compressed = compress(Encoding::Gzip, &[0; 4096]).unwrap();
+        let refused = decompress(Encoding::Gzip, &compressed, 4095);
+        assert!(matches!(refused, Err(A2AError::ResponseTooLarge { limit: 4095 })), "{:?}", refused);
+
+        let truncated = &compressed[..compressed.len() - 4];
+        assert!(matches!(decompress(Encoding::Gzip, truncated, 4096), Err(A2AError::Compression(_))));
+        assert!(matches!(decompress(Encoding::Deflate, &compressed, 4096), Err(A2AError::Compression(_))));
+    }
+
+    #[test]
+    fn negotiation_prefers_our_encoding() {
+        assert_eq!(negotiate("deflate, gzip", Encoding::Gzip), Some(Encoding::Gzip));
+        assert_eq!(negotiate("deflate", Encoding::Gzip), Some(Encoding::Deflate));
+        assert_eq!(negotiate("gzip;q=0, deflate", Encoding::Gzip), Some(Encoding::Deflate));
+        assert_eq!(negotiate("*", Encoding::Deflate), Some(Encoding::Deflate));
+        assert_eq!(negotiate("br, identity", Encoding::Gzip), None);
+    }
+}
@@ -0,0 +1,201 @@
+//! Bounding how many task handlers an [`A2AServer`](crate::A2AServer)
+//! runs at once.
+
+use std::cmp::{Ordering as Precedence, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+
+/// What the server does with a task that arrives while
+/// `max_concurrent_tasks` handlers are already running.
+///
+/// Queueing absorbs short bursts without failing anyone, but under
+/// sustained overload the queue and every caller's latency grow without
+/// bound, and callers may time out and retry, adding more load.
+/// Rejecting sheds that load immediately with JSON-RPC error `-32000`, so
+/// the server stays responsive and callers can back off or fail over,
+/// at the cost of failing requests a queue would eventually have served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverloadPolicy {
+    /// Wait for a running handler to finish. Waiting tasks get the next
+    /// free slot in order of [priority](crate::with_priority), oldest first
+    /// among equals.
+    #[default]
+    Queue,
+    /// Fail the task straight away.
+    Reject,
+}
+
+pub(crate) struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    policy: OverloadPolicy,
+    waiting: Arc<WaitQueue>,
+}
+
+impl ConcurrencyLimit {
+    pub(crate) fn new(max_concurrent_tasks: usize, policy: OverloadPolicy) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent_tasks)),
+            policy,
+            waiting: Arc::default(),
+        }
+    }
+}
+
+/// Tasks waiting for a slot, highest priority first. Freed permits are
+/// handed to the first of them directly, so the semaphore only holds
+/// permits while no one waits.
+#[derive(Default)]
+struct WaitQueue {
+    /// Locked both to queue up and to return a permit, so a permit cannot
+    /// be returned to the semaphore just after a waiter found it empty.
+    waiters: Mutex<WaitersState>,
+}
+
+#[derive(Default)]
+struct WaitersState {
+    heap: BinaryHeap<Waiter>,
+    /// Arrival counter, to keep equal priorities first come, first served.
+    arrivals: u64,
+}
+
+struct Waiter {
+    /// Higher first, then earlier arrivals.
+    key: (u8, Reverse<u64>),
+    wake: oneshot::Sender<Handoff>,
+}
+
+/// A permit on its way to a waiter. Should the waiter give up before
+/// taking it, it goes on to the next one rather than back to the
+/// semaphore, where the waiters behind would never see it.
+struct Handoff {
+    permit: Option<OwnedSemaphorePermit>,
+    queue: Arc<WaitQueue>,
+}
+
+impl Drop for Handoff {
+    fn drop(&mut self) {
+        if let Some(permit) = self.permit.take() {
+            self.queue.release(permit);
+        }
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Precedence> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Precedence {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl WaitQueue {
+    /// A permit from `semaphore`, waiting behind tasks of at least
+    /// `priority` if none is free.
+    async fn acquire(&self, semaphore: &Arc<Semaphore>, priority: u8) -> OwnedSemaphorePermit {
+        let woken = {
+            let mut waiters = self.waiters.lock().unwrap();
+            if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+                return permit;
+            }
+            let (wake, woken) = oneshot::channel();
+            waiters.arrivals += 1;
+            let key = (priority, Reverse(waiters.arrivals));
+            waiters.heap.push(Waiter { key, wake });
+            woken
+        };
+        let mut handoff = woken.await.expect("waiting tasks are woken before the queue is dropped");
+        handoff.permit.take().expect("a handoff carries its permit until taken")
+    }
+
+    /// Give `permit` to the first waiter still waiting, or back to the
+    /// semaphore.
+    fn release(self: &Arc<Self>, permit: OwnedSemaphorePermit) {
+        let mut waiters = self.waiters.lock().unwrap();
+        let mut handoff = Handoff {
+            permit: Some(permit),
+            queue: self.clone(),
+        };
+        while let Some(waiter) = waiters.heap.pop() {
+            // A waiter whose caller gave up refuses it.
+            match waiter.wake.send(handoff) {
+                Ok(()) => return,
+                Err(refused) => handoff = refused,
+            }
+        }
+        // Returned to the semaphore while still holding the lock.
+        drop(handoff.permit.take());
+    }
+}
+
+/// Tracks running handlers; a [`Slot`] is held for as long as one runs.
+#[derive(Clone, Default)]
+pub(crate) struct InFlight {
+    count: Arc<AtomicUsize>,
+}
+
+impl InFlight {
+    pub(crate) fn get(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn slot(&self, permit: Option<(OwnedSemaphorePermit, Arc<WaitQueue>)>) -> Slot {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        Slot {
+            permit,
+            count: self.count.clone(),
+        }
+    }
+
+    /// Take a slot if one is free. `Ok(None)` means the task has to queue
+    /// for one with [`wait`](Self::wait); `Err(())` means it is rejected.
+    pub(crate) fn try_admit(&self, limit: Option<&ConcurrencyLimit>) -> Result<Option<Slot>, ()> {
+        let Some(limit) = limit else {
+            return Ok(Some(self.slot(None)));
+        };
+
+        match limit.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => Ok(Some(self.slot(Some((permit, limit.waiting.clone()))))),
+            Err(_) if limit.policy == OverloadPolicy::Queue => Ok(None),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// Wait until a slot is free and no task of higher `priority` waits
+    /// for it.
+    pub(crate) async fn wait(&self, limit: Option<&ConcurrencyLimit>, priority: u8) -> Slot {
+        let permit = match limit {
+            Some(limit) => Some((limit.waiting.acquire(&limit.semaphore, priority).await, limit.waiting.clone())),
+            None => None,
+        };
+        self.slot(permit)
+    }
+}
+
+/// Permission for one handler to run; released on drop.
+pub(crate) struct Slot {
+    permit: Option<(OwnedSemaphorePermit, Arc<WaitQueue>)>,
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::Relaxed);
+        if let Some((permit, waiting)) = self.permit.take() {
+            waiting.release(permit);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Correlation IDs that tie together the tasks of one logical request
+//! as it hops from agent to agent.
+//!
+//! Every task carries a `correlationId` param. While an [`A2AServer`]
+//! handler runs, the ID of the task being handled is in scope, so tasks
+//! the handler sends onward reuse it automatically. Outside a handler a
+//! fresh ID is generated per task unless one is set with
+//! [`with_correlation_id`].
+//!
+//! The scope is task-local: work moved onto a separate `tokio::spawn`
+//! must be wrapped in [`with_correlation_id`] again to keep the ID.
+//!
+//! [`A2AServer`]: crate::A2AServer
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// The correlation ID in scope, if any.
+pub fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+/// Run `future` with `correlation_id` in scope, so every task it sends
+/// carries that ID.
+pub async fn with_correlation_id<F: Future>(correlation_id: impl Into<String>, future: F) -> F::Output {
+    CORRELATION_ID.scope(correlation_id.into(), future).await
+}
+
+/// Run `f` synchronously with `correlation_id` in scope.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn sync_scope<R>(correlation_id: String, f: impl FnOnce() -> R) -> R {
+    CORRELATION_ID.sync_scope(correlation_id, f)
+}
+
+/// The ID in scope, or a newly generated one.
+pub(crate) fn current_or_new() -> String {
+    current_correlation_id().unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
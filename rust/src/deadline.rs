@@ -0,0 +1,102 @@
+//! Deadlines that bound how long a chain of agent calls may take as a
+//! whole, not just each hop.
+//!
+//! Every task carries the time its sender has left as a `deadlineMs`
+//! param, measured as it is sent. An [`A2AServer`] refuses a task that
+//! arrives with no time left, answering with JSON-RPC error -32002, which
+//! the sender sees as [`A2AError::DeadlineExceeded`]. Otherwise the
+//! deadline is in scope while the handler runs, so tasks the handler
+//! sends onward carry only what remains of it. Outside a handler there is
+//! no deadline unless one is set with [`with_deadline`].
+//!
+//! Like correlation IDs, the scope is task-local: work moved onto a
+//! separate `tokio::spawn` must be wrapped in [`with_deadline`] again.
+//!
+//! [`A2AServer`]: crate::A2AServer
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::time::{self, Instant};
+use crate::A2AError;
+
+/// Far enough out to mean "no deadline", near enough that adding it to
+/// the clock cannot overflow.
+const MAX_BUDGET: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Time left before the deadline in scope, if any; zero once it passed.
+pub fn remaining_budget() -> Option<Duration> {
+    DEADLINE.try_with(|deadline| deadline.duration_since(Instant::now())).ok()
+}
+
+/// Run `future` with a deadline `budget` from now, so every task it sends
+/// must finish by then. A deadline already in scope is only ever
+/// shortened, never extended.
+///
+/// ```rust,no_run
+/// use a2a::{with_deadline, A2AAgent};
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// let agent = A2AAgent::new("client", "Client", vec![]);
+/// let task = agent.send_task("planner", "plan", json!({}), "http://localhost:8080");
+/// let result = with_deadline(Duration::from_secs(5), task).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn with_deadline<F: Future>(budget: Duration, future: F) -> F::Output {
+    let deadline = after(budget);
+    let deadline = match DEADLINE.try_with(|outer| *outer) {
+        Ok(outer) if outer < deadline => outer,
+        _ => deadline,
+    };
+    DEADLINE.scope(deadline, future).await
+}
+
+/// The instant `budget` from now.
+pub(crate) fn after(budget: Duration) -> Instant {
+    Instant::now() + budget.min(MAX_BUDGET)
+}
+
+/// The `deadlineMs` param for a task sent now.
+pub(crate) fn budget_ms() -> Option<u64> {
+    remaining_budget().map(|budget| budget.as_millis() as u64)
+}
+
+/// Run `future` with `deadline`, if any, in scope.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn scope<F: Future>(deadline: Option<Instant>, future: F) -> F::Output {
+    match deadline {
+        Some(deadline) => DEADLINE.scope(deadline, future).await,
+        None => future.await,
+    }
+}
+
+/// Run `f` synchronously with `deadline`, if any, in scope.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn sync_scope<R>(deadline: Option<Instant>, f: impl FnOnce() -> R) -> R {
+    match deadline {
+        Some(deadline) => DEADLINE.sync_scope(deadline, f),
+        None => f(),
+    }
+}
+
+/// Run the call `future` unless the deadline in scope has passed, giving
+/// up on it once it does.
+pub(crate) async fn bounded<T, F>(future: F) -> Result<T, A2AError>
+where
+    F: Future<Output = Result<T, A2AError>>,
+{
+    match remaining_budget() {
+        None => future.await,
+        Some(budget) if budget.is_zero() => Err(A2AError::DeadlineExceeded("deadline passed before the task was sent".to_string())),
+        Some(budget) => time::timeout(budget, future)
+            .await
+            .unwrap_or_else(|| Err(A2AError::DeadlineExceeded("deadline passed awaiting the response".to_string()))),
+    }
+}
@@ -0,0 +1,641 @@
+//! A directory for agents to register with and discover each other
+//! through, for local development and tests.
+//!
+//! [`A2ADirectory`] serves the endpoints [`A2AAgent`](crate::A2AAgent)
+//! calls:
+//!
+//! - `POST /a2a/register`, `/a2a/update`, `/a2a/deregister` and
+//!   `/a2a/discover`, each taking the JSON-RPC call of the same name
+//! - `GET /a2a/agents` listing every agent, and `GET /a2a/agents/{id}`
+//!   returning one or `404`
+//! - `GET /a2a/watch`, streaming each registration, update and
+//!   deregistration as a [`DirectoryEvent`](crate::DirectoryEvent)
+//!
+//! The `/a2a` prefix and the `a2a/` method namespace can be changed to
+//! match agents that use others.
+
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::codec::{self, Codecs};
+use crate::server::{
+    error_response, parse_request, success_response, INTERNAL_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, UNAUTHORIZED,
+};
+use crate::timestamp;
+use crate::{
+    A2AError, AgentIdPolicy, AgentInfo, AllowAllRegistrations, CapabilityQuery, DeregisterParams, DirectoryEvent, DirectoryEventKind, DiscoverParams,
+    JSONRPCResponse, MemoryRegistryStore, RegisterParams, RegistrationPolicy, RegistryStore, RejectionReason, StoredAgent, UpdateParams,
+    normalize_path_prefix, DEFAULT_METHOD_NAMESPACE, DEFAULT_PATH_PREFIX,
+};
+
+/// An agent directory served over HTTP, keeping agents in a
+/// [`RegistryStore`] (in memory unless [`with_store`](Self::with_store)
+/// says otherwise).
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// use std::time::Duration;
+///
+/// // Forget agents that have not re-registered for two minutes, e.g.
+/// // because their heartbeat stopped.
+/// a2a::A2ADirectory::new(8080).with_ttl(Duration::from_secs(120)).run().await
+/// # }
+/// ```
+pub struct A2ADirectory {
+    port: u16,
+    path_prefix: String,
+    method_namespace: String,
+    ttl: Option<Duration>,
+    store: Arc<dyn RegistryStore>,
+    agent_id_policy: AgentIdPolicy,
+    registration_policy: Arc<dyn RegistrationPolicy>,
+    unique_agent_ids: bool,
+    probe_timeout: Option<Duration>,
+    codecs: Codecs,
+}
+
+impl A2ADirectory {
+    /// A directory on `port` that keeps registrations until deregistered.
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            path_prefix: DEFAULT_PATH_PREFIX.to_string(),
+            method_namespace: DEFAULT_METHOD_NAMESPACE.to_string(),
+            ttl: None,
+            store: Arc::new(MemoryRegistryStore::new()),
+            agent_id_policy: AgentIdPolicy::new(),
+            registration_policy: Arc::new(AllowAllRegistrations),
+            unique_agent_ids: false,
+            probe_timeout: None,
+            codecs: Codecs::default(),
+        }
+    }
+
+    /// Serve the endpoints under `prefix` instead of `/a2a`, as in
+    /// `POST /api/v2/a2a/register`, for agents configured with the same
+    /// [`A2AAgent::with_path_prefix`](crate::A2AAgent::with_path_prefix).
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefix = normalize_path_prefix(prefix);
+        self
+    }
+
+    /// Answer `{namespace}/register`, `{namespace}/discover` and so on
+    /// instead of `a2a/...`, for agents configured with the same
+    /// [`A2AAgent::with_method_namespace`](crate::A2AAgent::with_method_namespace).
+    pub fn with_method_namespace(mut self, namespace: &str) -> Self {
+        self.method_namespace = namespace.trim_matches('/').to_string();
+        self
+    }
+
+    /// Keep registrations in `store`, e.g. one that persists them across
+    /// restarts.
+    pub fn with_store(mut self, store: Arc<dyn RegistryStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Drop agents that have not registered again within `ttl`. Pair it
+    /// with [`A2AAgent::start_heartbeat`](crate::A2AAgent::start_heartbeat)
+    /// at a shorter interval. Expired agents vanish from lookups at once
+    /// and are purged from the store every `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Refuse registrations whose agent ID or name breaks `policy`
+    /// instead of the default [`AgentIdPolicy`], with JSON-RPC error
+    /// `-32602` and reason
+    /// [`InvalidAgentId`](crate::RejectionReason::InvalidAgentId).
+    pub fn with_agent_id_policy(mut self, policy: AgentIdPolicy) -> Self {
+        self.agent_id_policy = policy;
+        self
+    }
+
+    /// Store only registrations and capability updates `policy` allows,
+    /// refusing the rest with `-32003` "Unauthorized" and reason
+    /// [`ForbiddenCapability`](crate::RejectionReason::ForbiddenCapability);
+    /// see [`RegistrationPolicy`].
+    pub fn with_registration_policy(mut self, policy: impl RegistrationPolicy + 'static) -> Self {
+        self.registration_policy = Arc::new(policy);
+        self
+    }
+
+    /// Refuse to register an agent ID that a live agent already holds at
+    /// a different endpoint, with `-32602` and reason
+    /// [`DuplicateId`](crate::RejectionReason::DuplicateId), instead of
+    /// letting the newcomer replace it. Registering again at the same
+    /// endpoint, as heartbeats do, is still allowed.
+    pub fn with_unique_agent_ids(mut self, unique: bool) -> Self {
+        self.unique_agent_ids = unique;
+        self
+    }
+
+    /// Before storing a registration, `GET {endpoint}/health` and refuse
+    /// it with `-32602` and reason
+    /// [`UnreachableEndpoint`](crate::RejectionReason::UnreachableEndpoint)
+    /// unless the agent answers within `timeout` with anything but a
+    /// server error, so discovery does not hand out addresses the
+    /// directory cannot reach. Leave it off where the directory has no
+    /// route to its agents. Every registration is probed, heartbeats
+    /// included.
+    pub fn with_reachability_probe(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = Some(timeout);
+        self
+    }
+
+    /// Also read and answer calls in `codec`, besides JSON and the codecs
+    /// built in with the enabled features; see [`Codec`](crate::Codec).
+    pub fn with_codec(mut self, codec: Arc<dyn crate::Codec>) -> Self {
+        self.codecs = self.codecs.with(codec);
+        self
+    }
+
+    /// Start serving. Runs until the process exits or the listener fails.
+    pub async fn run(&self) -> Result<(), A2AError> {
+        self.run_with_shutdown(std::future::pending()).await
+    }
+
+    /// Like [`run`](Self::run), but stop gracefully once `shutdown`
+    /// completes.
+    pub async fn run_with_shutdown<F>(&self, shutdown: F) -> Result<(), A2AError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (_addr, serve) = self.bind_with_shutdown(shutdown).await?;
+        serve.await
+    }
+
+    /// Listen on the directory's port and return the bound address with a
+    /// future that serves, as [`A2AServer::bind`](crate::A2AServer::bind)
+    /// does; port `0` picks a free port.
+    pub async fn bind(&self) -> Result<(SocketAddr, impl Future<Output = Result<(), A2AError>> + Send + 'static), A2AError> {
+        self.bind_with_shutdown(std::future::pending()).await
+    }
+
+    /// Like [`bind`](Self::bind), but the future stops gracefully once
+    /// `shutdown` completes.
+    pub async fn bind_with_shutdown<F>(
+        &self,
+        shutdown: F,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), A2AError>> + Send + 'static), A2AError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", self.port)).await?;
+        let addr = listener.local_addr()?;
+
+        info!(%addr, "A2A directory listening");
+
+        let mut registry = Registry::new(self.store.clone(), self.ttl);
+        registry.agent_id_policy = self.agent_id_policy;
+        registry.registration_policy = self.registration_policy.clone();
+        registry.unique_agent_ids = self.unique_agent_ids;
+        registry.probe = match self.probe_timeout {
+            Some(timeout) => Some((probe_client()?, timeout)),
+            None => None,
+        };
+        registry.codecs = self.codecs.clone();
+        registry.method_namespace = self.method_namespace.clone();
+        let ttl = self.ttl;
+        let closing = registry.closing.clone();
+        let shutdown = async move {
+            shutdown.await;
+            closing.cancel();
+        };
+        let prefix = &self.path_prefix;
+        let router = Router::new()
+            .route(&format!("{}/register", prefix), post(handle_rpc))
+            .route(&format!("{}/update", prefix), post(handle_rpc))
+            .route(&format!("{}/deregister", prefix), post(handle_rpc))
+            .route(&format!("{}/discover", prefix), post(handle_rpc))
+            .route(&format!("{}/agents", prefix), get(handle_list))
+            .route(&format!("{}/agents/{{agent_id}}", prefix), get(handle_get))
+            .route(&format!("{}/watch", prefix), get(handle_watch))
+            .with_state(registry.clone());
+
+        let serve = async move {
+            let purger = ttl.map(|ttl| tokio::spawn(purge_periodically(registry, ttl)));
+            let served = axum::serve(listener, router).with_graceful_shutdown(shutdown).await;
+            if let Some(purger) = purger {
+                purger.abort();
+            }
+            Ok(served?)
+        };
+        Ok((addr, serve))
+    }
+}
+
+async fn handle_rpc(State(registry): State<Registry>, headers: HeaderMap, body: Bytes) -> Response {
+    let codec = registry.codecs.for_request(&headers);
+    let reply = registry.codecs.for_response(&headers, &codec);
+    let raw = match codec::decode(&codec, &body) {
+        Ok(raw) => raw,
+        Err(e) => return codec::respond(&reply, &error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+    };
+    let request = match parse_request(raw) {
+        Ok(request) => request,
+        Err(response) => return codec::respond(&reply, &response),
+    };
+
+    let response = match registry.handle(&request.method, request.params.unwrap_or(json!({})), &headers).await {
+        Ok(result) => success_response(request.id, result),
+        Err(A2AError::Rpc { code, message }) => error_response(request.id, code, message),
+        Err(A2AError::RegistrationRejected { reason, message }) => rejection_response(request.id, reason, message),
+        Err(e) => error_response(request.id, INTERNAL_ERROR, e.to_string()),
+    };
+    codec::respond(&reply, &response)
+}
+
+/// A JSON-RPC error naming `reason` in its `data`, so clients can tell
+/// the rejection apart as [`A2AError::RegistrationRejected`].
+fn rejection_response(id: Value, reason: RejectionReason, message: String) -> JSONRPCResponse {
+    let mut response = error_response(id, rejection_code(reason), message);
+    if let Some(error) = &mut response.error {
+        error.data = Some(json!({"reason": reason.as_str()}));
+    }
+    response
+}
+
+fn rejection_code(reason: RejectionReason) -> i32 {
+    match reason {
+        RejectionReason::ForbiddenCapability => UNAUTHORIZED,
+        RejectionReason::DuplicateId
+        | RejectionReason::InvalidEndpoint
+        | RejectionReason::UnreachableEndpoint
+        | RejectionReason::InvalidAgentId => INVALID_PARAMS,
+    }
+}
+
+async fn handle_list(State(registry): State<Registry>) -> Response {
+    match registry.agents().await {
+        Ok(agents) => Json(json!({"agents": agents})).into_response(),
+        Err(e) => store_failure(e),
+    }
+}
+
+async fn handle_get(State(registry): State<Registry>, Path(agent_id): Path<String>) -> Response {
+    match registry.get(&agent_id).await {
+        Ok(Some(agent)) => Json(agent).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({"error": "Agent not found"}))).into_response(),
+        Err(e) => store_failure(e),
+    }
+}
+
+/// Stream the registry's changes from now on as SSE events named after
+/// their kind.
+async fn handle_watch(State(registry): State<Registry>) -> impl IntoResponse {
+    let watch = (registry.events.subscribe(), registry.closing.clone());
+    let events = futures::stream::unfold(watch, |(mut changes, closing)| async move {
+        loop {
+            let received = tokio::select! {
+                received = changes.recv() => received,
+                // Otherwise open watches would hold up a graceful shutdown
+                // forever.
+                _ = closing.cancelled() => return None,
+            };
+            match received {
+                Ok(change) => {
+                    let event = Event::default().event(change.kind.as_str()).json_data(change);
+                    return Some((event, (changes, closing)));
+                }
+                // A watcher too slow to keep up misses changes rather than
+                // holding up the directory.
+                Err(broadcast::error::RecvError::Lagged(missed)) => warn!(missed, "directory watcher lagging, dropped events"),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// A `500`, so clients fail over to another directory.
+fn store_failure(e: A2AError) -> Response {
+    warn!(error = %e, "registry store failed");
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()}))).into_response()
+}
+
+/// Directory logic over a [`RegistryStore`], shared by clones.
+#[derive(Clone)]
+pub(crate) struct Registry {
+    store: Arc<dyn RegistryStore>,
+    ttl: Option<Duration>,
+    agent_id_policy: AgentIdPolicy,
+    registration_policy: Arc<dyn RegistrationPolicy>,
+    unique_agent_ids: bool,
+    /// The client and timeout to probe endpoints with, if at all.
+    probe: Option<(reqwest::Client, Duration)>,
+    codecs: Codecs,
+    method_namespace: String,
+    /// Changes to the listing, for `GET /a2a/watch`.
+    events: broadcast::Sender<DirectoryEvent>,
+    /// Cancelled when the directory starts shutting down, ending watches.
+    closing: CancellationToken,
+    /// Held across each read-modify-write of the store, so an update
+    /// cannot undo a registration that landed while it ran.
+    writes: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryRegistryStore::new()), None)
+    }
+}
+
+impl Registry {
+    pub(crate) fn new(store: Arc<dyn RegistryStore>, ttl: Option<Duration>) -> Self {
+        Self {
+            store,
+            ttl,
+            agent_id_policy: AgentIdPolicy::new(),
+            registration_policy: Arc::new(AllowAllRegistrations),
+            unique_agent_ids: false,
+            probe: None,
+            codecs: Codecs::default(),
+            method_namespace: DEFAULT_METHOD_NAMESPACE.to_string(),
+            events: broadcast::channel(WATCH_BUFFER).0,
+            closing: CancellationToken::new(),
+            writes: Arc::default(),
+        }
+    }
+
+    /// Answer the directory call `method`, made with `headers`.
+    pub(crate) async fn handle(&self, method: &str, params: Value, headers: &HeaderMap) -> Result<Value, A2AError> {
+        let call = method
+            .strip_prefix(self.method_namespace.as_str())
+            .and_then(|call| call.strip_prefix('/'))
+            .unwrap_or_default();
+        self.handle_call(call, method, params, headers).await
+    }
+
+    /// Answer `call`, such as `register`, which came in as the JSON-RPC
+    /// method `method`.
+    pub(crate) async fn handle_call(&self, call: &str, method: &str, params: Value, headers: &HeaderMap) -> Result<Value, A2AError> {
+        match call {
+            "register" => self.register(parse_params(params)?, headers).await,
+            "update" => self.update(parse_params(params)?, headers).await,
+            "deregister" => self.deregister(parse_params(params)?).await,
+            "discover" => self.discover(parse_params(params)?).await,
+            _ => Err(A2AError::Rpc {
+                code: METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+            }),
+        }
+    }
+
+    pub(crate) async fn get(&self, agent_id: &str) -> Result<Option<AgentInfo>, A2AError> {
+        let stored = self.store.get(agent_id).await?;
+        Ok(stored.filter(|stored| self.is_live(stored)).map(|stored| stored.agent))
+    }
+
+    /// Every live agent, ordered by `agent_id`.
+    pub(crate) async fn agents(&self) -> Result<Vec<AgentInfo>, A2AError> {
+        let stored = self.store.list().await?;
+        Ok(stored
+            .into_iter()
+            .filter(|stored| self.is_live(stored))
+            .map(|stored| stored.agent)
+            .collect())
+    }
+
+    /// Remove registrations older than the TTL from the store.
+    async fn purge_expired(&self) -> Result<usize, A2AError> {
+        match self.ttl {
+            Some(ttl) => self.store.purge(SystemTime::now() - ttl).await,
+            None => Ok(0),
+        }
+    }
+
+    async fn register(&self, params: RegisterParams, headers: &HeaderMap) -> Result<Value, A2AError> {
+        if let Err(e) = self.agent_id_policy.check(&params.agent_id, &params.name) {
+            warn!(agent_id = %params.agent_id, error = %e, "refusing registration");
+            return Err(A2AError::RegistrationRejected {
+                reason: RejectionReason::InvalidAgentId,
+                message: e.to_string(),
+            });
+        }
+        if !is_valid_endpoint(&params.endpoint) {
+            warn!(agent_id = %params.agent_id, endpoint = %params.endpoint, "refusing registration: invalid endpoint");
+            return Err(A2AError::RegistrationRejected {
+                reason: RejectionReason::InvalidEndpoint,
+                message: format!("Invalid endpoint: {:?} is not an absolute URL with a host", params.endpoint),
+            });
+        }
+        let registered = SystemTime::now();
+        let agent = AgentInfo {
+            agent_id: params.agent_id.clone(),
+            name: params.name,
+            capabilities: params.capabilities,
+            endpoint: params.endpoint,
+            registered_at: Some(timestamp::format(registered)),
+            version: params.version,
+            description: params.description,
+            tags: params.tags,
+        };
+        self.check_policy(&agent, headers)?;
+        // Probed before taking `writes`, so a slow agent holds up no one
+        // else.
+        self.check_reachable(&agent).await?;
+        let _writing = self.writes.lock().await;
+        if self.unique_agent_ids {
+            self.check_unique(&agent).await?;
+        }
+        let kind = self.registration_change(&agent).await?;
+        info!(agent_id = %agent.agent_id, "agent registered");
+        self.store
+            .put(StoredAgent { agent: agent.clone(), registered })
+            .await?;
+        if let Some(kind) = kind {
+            self.publish(kind, agent);
+        }
+        Ok(json!({"status": "registered", "agentId": params.agent_id}))
+    }
+
+    /// Patch the capability set of a live agent, keeping everything else,
+    /// including when it registered.
+    async fn update(&self, params: UpdateParams, headers: &HeaderMap) -> Result<Value, A2AError> {
+        let _writing = self.writes.lock().await;
+        let Some(mut stored) = self.store.get(&params.agent_id).await?.filter(|stored| self.is_live(stored)) else {
+            return Err(A2AError::Rpc {
+                code: INVALID_PARAMS,
+                message: format!("Agent not found: {}", params.agent_id),
+            });
+        };
+
+        let capabilities = &mut stored.agent.capabilities;
+        capabilities.retain(|existing| {
+            !params.remove.contains(&existing.name) && !params.add.iter().any(|added| added.replaces(existing))
+        });
+        capabilities.extend(params.add);
+        self.check_policy(&stored.agent, headers)?;
+        info!(agent_id = %params.agent_id, capabilities = stored.agent.capabilities.len(), "agent capabilities updated");
+        self.store.put(stored.clone()).await?;
+        self.publish(DirectoryEventKind::Updated, stored.agent);
+        Ok(json!({"status": "updated", "agentId": params.agent_id}))
+    }
+
+    /// How registering `agent` changes the listing, if at all. Only asks the
+    /// store while someone is watching.
+    async fn registration_change(&self, agent: &AgentInfo) -> Result<Option<DirectoryEventKind>, A2AError> {
+        if self.events.receiver_count() == 0 {
+            return Ok(None);
+        }
+        let Some(existing) = self.store.get(&agent.agent_id).await?.filter(|stored| self.is_live(stored)) else {
+            return Ok(Some(DirectoryEventKind::Registered));
+        };
+        // Every registration is stamped anew; a heartbeat changes nothing else.
+        let details = |agent: &AgentInfo| {
+            let agent = AgentInfo { registered_at: None, ..agent.clone() };
+            serde_json::to_value(agent).unwrap_or_default()
+        };
+        Ok((details(&existing.agent) != details(agent)).then_some(DirectoryEventKind::Updated))
+    }
+
+    /// Tell watchers, if any, about a change.
+    fn publish(&self, kind: DirectoryEventKind, agent: AgentInfo) {
+        debug!(agent_id = %agent.agent_id, kind = kind.as_str(), watchers = self.events.receiver_count(), "publishing directory event");
+        let _ = self.events.send(DirectoryEvent { kind, agent });
+    }
+
+    /// Refuse to store `agent` if the registration policy forbids it.
+    fn check_policy(&self, agent: &AgentInfo, headers: &HeaderMap) -> Result<(), A2AError> {
+        if self.registration_policy.allow(agent, headers) {
+            return Ok(());
+        }
+        warn!(agent_id = %agent.agent_id, "refusing registration: forbidden by policy");
+        Err(A2AError::RegistrationRejected {
+            reason: RejectionReason::ForbiddenCapability,
+            message: format!("Unauthorized: registration of {} refused by policy", agent.agent_id),
+        })
+    }
+
+    /// Refuse `agent` if probing is on and its health check fails.
+    async fn check_reachable(&self, agent: &AgentInfo) -> Result<(), A2AError> {
+        let Some((client, timeout)) = &self.probe else {
+            return Ok(());
+        };
+        let url = format!("{}/health", agent.endpoint.trim_end_matches('/'));
+        let failure = match client.get(&url).timeout(*timeout).send().await {
+            Ok(response) if !response.status().is_server_error() => return Ok(()),
+            Ok(response) => format!("GET {} answered {}", url, response.status()),
+            Err(e) if e.is_timeout() => format!("GET {} timed out after {:?}", url, timeout),
+            Err(e) => format!("GET {} failed: {}", url, e),
+        };
+        warn!(agent_id = %agent.agent_id, endpoint = %agent.endpoint, "refusing registration: unreachable endpoint");
+        Err(A2AError::RegistrationRejected {
+            reason: RejectionReason::UnreachableEndpoint,
+            message: format!("Unreachable endpoint: {}", failure),
+        })
+    }
+
+    /// Refuse `agent` if a live agent holds its ID at another endpoint.
+    /// Called with `writes` held, so two newcomers cannot both pass.
+    async fn check_unique(&self, agent: &AgentInfo) -> Result<(), A2AError> {
+        let Some(existing) = self.store.get(&agent.agent_id).await?.filter(|stored| self.is_live(stored)) else {
+            return Ok(());
+        };
+        if existing.agent.endpoint.trim_end_matches('/') == agent.endpoint.trim_end_matches('/') {
+            return Ok(());
+        }
+        warn!(agent_id = %agent.agent_id, endpoint = %agent.endpoint, "refusing registration: duplicate agent ID");
+        Err(A2AError::RegistrationRejected {
+            reason: RejectionReason::DuplicateId,
+            message: format!("Duplicate agent ID: {} is already registered at {}", agent.agent_id, existing.agent.endpoint),
+        })
+    }
+
+    async fn deregister(&self, params: DeregisterParams) -> Result<Value, A2AError> {
+        let _writing = self.writes.lock().await;
+        let removed = match self.events.receiver_count() {
+            0 => None,
+            _ => self.store.get(&params.agent_id).await?.filter(|stored| self.is_live(stored)),
+        };
+        self.store.remove(&params.agent_id).await?;
+        if let Some(removed) = removed {
+            self.publish(DirectoryEventKind::Deregistered, removed.agent);
+        }
+        Ok(json!({"status": "deregistered", "agentId": params.agent_id}))
+    }
+
+    async fn discover(&self, params: DiscoverParams) -> Result<Value, A2AError> {
+        let query = match (params.query, params.capabilities) {
+            (Some(query), _) => query,
+            (None, capabilities) => CapabilityQuery::All(capabilities.unwrap_or_default()),
+        };
+
+        let mut agents = self.agents().await?;
+        agents.retain(|agent| agent.matches(&query) && agent.matches_tags(&params.tags));
+        let Some(limit) = params.limit else {
+            return Ok(json!({"agents": agents}));
+        };
+
+        // Agents are ordered by `agent_id`, so the last one listed marks
+        // where the next page starts, even if agents come and go between
+        // pages.
+        if let Some(cursor) = &params.cursor {
+            agents.retain(|agent| agent.agent_id > *cursor);
+        }
+        let more = agents.len() > limit.max(1);
+        agents.truncate(limit.max(1));
+        let next_cursor = more.then(|| agents.last().map(|agent| agent.agent_id.clone())).flatten();
+        Ok(json!({"agents": agents, "nextCursor": next_cursor}))
+    }
+
+    /// Expired registrations are hidden even before a purge removes them.
+    fn is_live(&self, stored: &StoredAgent) -> bool {
+        match self.ttl {
+            // A clock that went backwards makes the entry look fresh.
+            Some(ttl) => stored.registered.elapsed().map_or(true, |age| age < ttl),
+            None => true,
+        }
+    }
+}
+
+/// Changes kept for each watcher that has not read them yet.
+const WATCH_BUFFER: usize = 256;
+
+/// Purge expired registrations every `ttl` until aborted.
+async fn purge_periodically(registry: Registry, ttl: Duration) {
+    let mut interval = tokio::time::interval(ttl);
+    loop {
+        interval.tick().await;
+        match registry.purge_expired().await {
+            Ok(0) => {}
+            Ok(purged) => debug!(purged, "purged expired registrations"),
+            Err(e) => warn!(error = %e, "purging expired registrations failed"),
+        }
+    }
+}
+
+fn probe_client() -> Result<reqwest::Client, A2AError> {
+    reqwest::Client::builder()
+        .user_agent(crate::transport::DEFAULT_USER_AGENT)
+        .build()
+        .map_err(|e| A2AError::Config(format!("cannot build the reachability probe's HTTP client: {}", e)))
+}
+
+/// An absolute URL with a host, such as `http://10.0.0.5:8080`. Any
+/// scheme passes, so in-process transports keep their own.
+fn is_valid_endpoint(endpoint: &str) -> bool {
+    reqwest::Url::parse(endpoint).is_ok_and(|url| url.has_host() && !url.cannot_be_a_base())
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, A2AError> {
+    serde_json::from_value(params).map_err(|e| A2AError::Rpc {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {}", e),
+    })
+}
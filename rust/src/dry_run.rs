@@ -0,0 +1,66 @@
+//! Building task calls without sending them, for testing the code that
+//! decides what to send.
+
+use serde_json::Value;
+
+use crate::{A2AAgent, A2AError, JSONRPCRequest, TaskParams, TaskResult, TaskStatus, JSONRPC_VERSION};
+
+/// A task call as [`A2AAgent::build_task_request`] assembled it.
+#[derive(Debug, Clone)]
+pub struct TaskRequest {
+    /// The target's endpoint, as resolved through the directory.
+    pub endpoint: String,
+    pub request: JSONRPCRequest,
+}
+
+impl A2AAgent {
+    /// Resolve `target_agent_id` and build the `a2a/task` call
+    /// [`send_task`](Self::send_task) would make, without sending it.
+    ///
+    /// The directory is still asked for the target, and the opt-in action
+    /// and input checks still run; only the peer is left alone. Task and
+    /// request IDs are fresh on every call.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgent;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let task = agent
+    ///     .build_task_request("calculator", "add", json!({"a": 1, "b": 2}), "http://localhost:8080")
+    ///     .await?;
+    /// assert_eq!(task.request.method, "a2a/task");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build_task_request(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskRequest, A2AError> {
+        let agent_info = self.resolve_agent(target_agent_id, directory_url).await?;
+        self.check_task(&agent_info, action, None, &input)?;
+
+        let params = self.task_params(action, input);
+        Ok(TaskRequest {
+            endpoint: agent_info.endpoint,
+            request: JSONRPCRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: Value::String(uuid::Uuid::new_v4().to_string()),
+                method: self.method("task"),
+                params: Some(serde_json::to_value(params)?),
+            },
+        })
+    }
+}
+
+/// What a dry-run agent answers in place of sending `params`: a
+/// [`TaskStatus::DryRun`] result echoing the input.
+pub(crate) fn result(params: TaskParams) -> TaskResult {
+    let mut result = TaskResult::new(&params.task_id, TaskStatus::DryRun);
+    result.output = Some(params.input);
+    result
+}
@@ -0,0 +1,97 @@
+//! Configuring an agent from environment variables, for deployments that
+//! keep their settings out of the code.
+
+use std::env::{self, VarError};
+use std::time::Duration;
+
+use crate::{A2AAgent, A2AAgentBuilder, A2AError, AuthConfig};
+
+impl A2AAgentBuilder {
+    /// Start configuring an agent from the environment, so that settings
+    /// made on the returned builder override what the environment said.
+    ///
+    /// | Variable | |
+    /// |---|---|
+    /// | `A2A_AGENT_ID` | Required. The agent's ID. |
+    /// | `A2A_DIRECTORY_URL` | Required. The directory to use, kept in [`A2AAgent::directory_url`]. |
+    /// | `A2A_AGENT_NAME` | The agent's name; defaults to its ID. |
+    /// | `A2A_CAPABILITIES` | Comma-separated capability names. |
+    /// | `A2A_TIMEOUT_SECS` | Request timeout in seconds, fractions allowed. |
+    /// | `A2A_AUTH_TOKEN` | Sent as `Authorization: Bearer <token>`. |
+    /// | `A2A_FALLBACK_DIRECTORIES` | Comma-separated directories to fail over to. |
+    ///
+    /// Variables that are set but empty count as unset. Fails with
+    /// [`A2AError::Config`] naming the variable if a required one is
+    /// missing or a value cannot be used.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgentBuilder;
+    /// use std::time::Duration;
+    ///
+    /// # fn example() -> Result<(), a2a::A2AError> {
+    /// // Whatever A2A_TIMEOUT_SECS says, this agent waits 5 seconds.
+    /// let agent = A2AAgentBuilder::from_env()?.timeout(Duration::from_secs(5)).build()?;
+    /// println!("using {:?}", agent.directory_url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_env() -> Result<Self, A2AError> {
+        let agent_id = required("A2A_AGENT_ID")?;
+        let name = var("A2A_AGENT_NAME")?.unwrap_or_else(|| agent_id.clone());
+        let mut builder = A2AAgent::builder(&agent_id, &name).directory_url(required("A2A_DIRECTORY_URL")?);
+        if let Some(capabilities) = var("A2A_CAPABILITIES")? {
+            builder = builder.capabilities(list(&capabilities));
+        }
+        if let Some(timeout) = var("A2A_TIMEOUT_SECS")? {
+            builder = builder.timeout(seconds("A2A_TIMEOUT_SECS", &timeout)?);
+        }
+        if let Some(token) = var("A2A_AUTH_TOKEN")? {
+            builder = builder.auth(AuthConfig::Bearer(token));
+        }
+        if let Some(directories) = var("A2A_FALLBACK_DIRECTORIES")? {
+            builder = builder.fallback_directories(list(&directories));
+        }
+        Ok(builder)
+    }
+}
+
+impl A2AAgent {
+    /// Build an agent configured entirely from the environment; see
+    /// [`A2AAgentBuilder::from_env`] for the variables read, and to
+    /// override some of them in code.
+    pub fn from_env() -> Result<Self, A2AError> {
+        A2AAgentBuilder::from_env()?.build()
+    }
+}
+
+/// The trimmed value of `name`, or `None` if it is unset or blank.
+fn var(name: &str) -> Result<Option<String>, A2AError> {
+    match env::var(name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value.trim().to_string())),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(A2AError::Config(format!("{} is not valid UTF-8", name))),
+    }
+}
+
+fn required(name: &str) -> Result<String, A2AError> {
+    var(name)?.ok_or_else(|| A2AError::Config(format!("{} is not set", name)))
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn seconds(name: &str, value: &str) -> Result<Duration, A2AError> {
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| A2AError::Config(format!("{} must be a positive number of seconds, got {:?}", name, value)))
+}
@@ -0,0 +1,282 @@
+//! Error type shared by the agent client and the server.
+
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum A2AError {
+    /// Transport-level failure talking to the directory or a peer.
+    Http(reqwest::Error),
+    /// The directory or peer answered with a non-success HTTP status;
+    /// `body` holds the start of the response, e.g. a gateway's error page.
+    HttpStatus { status: reqwest::StatusCode, body: String },
+    /// A successful response was declared as something other than JSON.
+    UnexpectedContentType { content_type: String, body: String },
+    /// A TLS handshake failed, or a certificate or key could not be parsed.
+    #[cfg(feature = "tls")]
+    Tls(reqwest::Error),
+    /// A server's TLS certificate or private key could not be loaded.
+    #[cfg(feature = "tls")]
+    TlsSetup(String),
+    /// The directory or peer did not answer within the configured timeout.
+    Timeout(reqwest::Error),
+    /// The remote end answered with a JSON-RPC error object.
+    Rpc { code: i32, message: String },
+    /// A payload could not be serialized or deserialized.
+    Serde(serde_json::Error),
+    /// The directory does not know the requested agent.
+    AgentNotFound(String),
+    /// A JSON-RPC response carried neither `result` nor `error`, which the
+    /// spec forbids. A `"result": null` is a result.
+    EmptyResponse,
+    /// A response body exceeded the agent's `max_response_bytes`.
+    ResponseTooLarge { limit: usize },
+    /// A peer broke the JSON-RPC protocol, e.g. by answering with a
+    /// `jsonrpc` version other than `"2.0"`.
+    Protocol(String),
+    /// A JSON-RPC response answered a different request than the one sent.
+    IdMismatch { expected: serde_json::Value, got: serde_json::Value },
+    /// Every configured directory was unreachable or failing; carries each
+    /// directory URL with the error it produced, in the order tried.
+    AllDirectoriesFailed(Vec<(String, A2AError)>),
+    /// Every agent matching a query failed the task; carries each
+    /// candidate's `agent_id` with the error it produced, in the order
+    /// tried. Empty if no agent matched.
+    AllCandidatesFailed(Vec<(String, A2AError)>),
+    /// A rate limit on calls to `target` is exhausted: either the
+    /// client-side limit for a target agent, named by its `agent_id`, or a
+    /// directory or peer that answered HTTP 429, named by its URL.
+    /// `retry_after` is when another call may succeed, if known: the next
+    /// free token, or the server's `Retry-After`.
+    RateLimited { target: String, retry_after: Option<Duration> },
+    /// Recent calls to this target agent kept failing, so its circuit
+    /// breaker is failing calls fast until the cooldown passes.
+    CircuitOpen(String),
+    /// The deadline set with [`with_deadline`](crate::with_deadline), or
+    /// inherited from the task being handled, passed before the task
+    /// finished.
+    DeadlineExceeded(String),
+    /// A batch request failed as a whole; carries the shared cause.
+    BatchFailed(String),
+    /// A task stream ended without the final `done` event.
+    StreamClosed,
+    /// A submitted task did not finish before the caller's deadline.
+    TaskWaitTimeout(String),
+    /// Task input does not satisfy the target's declared input schema.
+    InvalidInput { errors: Vec<String> },
+    /// The target agent does not declare the requested action.
+    UnsupportedAction { agent_id: String, action: String },
+    /// An attachment's name or content type cannot go in a MIME header,
+    /// or the transport cannot carry attachments.
+    InvalidAttachment(String),
+    /// A version or version requirement could not be parsed.
+    InvalidVersion(String),
+    /// An agent ID or name breaks the [`AgentIdPolicy`](crate::AgentIdPolicy)
+    /// in force.
+    InvalidAgentId(String),
+    /// An endpoint given to [`AgentInfo::new`](crate::AgentInfo::new) is
+    /// not an absolute `http` or `https` URL.
+    InvalidEndpoint(String),
+    /// A directory refused to register the agent, or to apply a capability
+    /// update, for the machine-readable `reason` it gave.
+    RegistrationRejected { reason: RejectionReason, message: String },
+    /// A required environment variable is missing, one holds a value that
+    /// cannot be used, or an HTTP client cannot be built from its settings.
+    Config(String),
+    /// The target declares no version of `action` that satisfies the
+    /// `required` constraint; `available` lists those it does declare, in
+    /// ascending order.
+    VersionMismatch {
+        agent_id: String,
+        action: String,
+        required: String,
+        available: Vec<crate::Version>,
+    },
+    /// Local I/O failure, e.g. the server could not bind its port.
+    Io(std::io::Error),
+    /// A directory's registry store could not read or write its agents.
+    Store(String),
+    /// A WebSocket session could not connect, or its connection dropped.
+    #[cfg(feature = "ws")]
+    WebSocket(String),
+    /// A body could not be compressed or decompressed, or used an
+    /// unsupported `Content-Encoding`.
+    #[cfg(feature = "compression")]
+    Compression(String),
+    /// A task result was unsigned, or its signature did not verify
+    /// against the key configured for `agent_id`.
+    #[cfg(feature = "signing")]
+    SignatureInvalid { agent_id: String, reason: String },
+    /// A signing key could not be generated or parsed.
+    #[cfg(feature = "signing")]
+    InvalidKey(String),
+}
+
+impl fmt::Display for A2AError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            A2AError::Http(e) => write!(f, "HTTP error: {}", e),
+            A2AError::HttpStatus { status, body } if body.is_empty() => write!(f, "HTTP status {}", status),
+            A2AError::HttpStatus { status, body } => write!(f, "HTTP status {}: {}", status, body),
+            A2AError::UnexpectedContentType { content_type, body } => {
+                write!(f, "Expected a JSON response, got {}: {}", content_type, body)
+            }
+            #[cfg(feature = "tls")]
+            A2AError::Tls(e) => write!(f, "TLS error: {}", e),
+            #[cfg(feature = "tls")]
+            A2AError::TlsSetup(message) => write!(f, "TLS setup failed: {}", message),
+            A2AError::Timeout(e) => write!(f, "Request timed out: {}", e),
+            A2AError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            A2AError::Serde(e) => write!(f, "Serialization error: {}", e),
+            A2AError::AgentNotFound(agent_id) => write!(f, "Agent not found: {}", agent_id),
+            A2AError::EmptyResponse => write!(f, "Empty response: neither result nor error"),
+            A2AError::ResponseTooLarge { limit } => write!(f, "Response body exceeds {} bytes", limit),
+            A2AError::Protocol(message) => write!(f, "Protocol error: {}", message),
+            A2AError::IdMismatch { expected, got } => {
+                write!(f, "Response id {} does not match request id {}", got, expected)
+            }
+            A2AError::AllDirectoriesFailed(failures) => {
+                write!(f, "All directories failed")?;
+                for (i, (directory, e)) in failures.iter().enumerate() {
+                    write!(f, "{} {}: {}", if i == 0 { ":" } else { ";" }, directory, e)?;
+                }
+                Ok(())
+            }
+            A2AError::AllCandidatesFailed(failures) if failures.is_empty() => write!(f, "No agent matched the query"),
+            A2AError::AllCandidatesFailed(failures) => {
+                write!(f, "All candidates failed")?;
+                for (i, (agent_id, e)) in failures.iter().enumerate() {
+                    write!(f, "{} {}: {}", if i == 0 { ":" } else { ";" }, agent_id, e)?;
+                }
+                Ok(())
+            }
+            A2AError::RateLimited { target, retry_after: None } => write!(f, "Rate limit exceeded for {}", target),
+            A2AError::RateLimited { target, retry_after: Some(delay) } => {
+                write!(f, "Rate limit exceeded for {}; retry after {:?}", target, delay)
+            }
+            A2AError::CircuitOpen(agent_id) => write!(f, "Circuit open for agent: {}", agent_id),
+            A2AError::DeadlineExceeded(message) => write!(f, "Deadline exceeded: {}", message),
+            A2AError::BatchFailed(message) => write!(f, "Batch failed: {}", message),
+            A2AError::StreamClosed => write!(f, "Task stream closed before completion"),
+            A2AError::TaskWaitTimeout(task_id) => write!(f, "Timed out waiting for task: {}", task_id),
+            A2AError::InvalidInput { errors } => write!(f, "Invalid input: {}", errors.join("; ")),
+            A2AError::UnsupportedAction { agent_id, action } => {
+                write!(f, "Agent {} does not support action: {}", agent_id, action)
+            }
+            A2AError::InvalidAttachment(message) => write!(f, "Invalid attachment: {}", message),
+            A2AError::InvalidVersion(message) => write!(f, "Invalid version: {}", message),
+            A2AError::InvalidAgentId(message) => write!(f, "Invalid agent ID: {}", message),
+            A2AError::InvalidEndpoint(message) => write!(f, "Invalid endpoint: {}", message),
+            A2AError::Config(message) => write!(f, "Configuration error: {}", message),
+            A2AError::RegistrationRejected { reason, message } => {
+                write!(f, "Registration rejected ({}): {}", reason.as_str(), message)
+            }
+            A2AError::VersionMismatch {
+                agent_id,
+                action,
+                required,
+                available,
+            } => {
+                write!(f, "Agent {} offers no version of {} matching {}", agent_id, action, required)?;
+                for (i, version) in available.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { "; it has" } else { "," }, version)?;
+                }
+                Ok(())
+            }
+            A2AError::Io(e) => write!(f, "I/O error: {}", e),
+            A2AError::Store(message) => write!(f, "Registry store error: {}", message),
+            #[cfg(feature = "ws")]
+            A2AError::WebSocket(message) => write!(f, "WebSocket error: {}", message),
+            #[cfg(feature = "compression")]
+            A2AError::Compression(message) => write!(f, "Compression error: {}", message),
+            #[cfg(feature = "signing")]
+            A2AError::SignatureInvalid { agent_id, reason } => {
+                write!(f, "Invalid result signature from {}: {}", agent_id, reason)
+            }
+            #[cfg(feature = "signing")]
+            A2AError::InvalidKey(message) => write!(f, "Invalid signing key: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for A2AError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            A2AError::Http(e) | A2AError::Timeout(e) => Some(e),
+            #[cfg(feature = "tls")]
+            A2AError::Tls(e) => Some(e),
+            A2AError::Serde(e) => Some(e),
+            A2AError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Why a directory refused a registration, sent as the `reason` in the
+/// `data` of its JSON-RPC error, e.g. `{"reason": "duplicate_id"}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectionReason {
+    /// Another live agent holds the `agent_id` at a different endpoint.
+    DuplicateId,
+    /// The `endpoint` is not an absolute URL with a host.
+    InvalidEndpoint,
+    /// The directory probes endpoints and could not reach this one.
+    UnreachableEndpoint,
+    /// The directory's [`RegistrationPolicy`](crate::RegistrationPolicy)
+    /// refused the agent or its capabilities.
+    ForbiddenCapability,
+    /// The `agent_id` or `name` breaks the directory's
+    /// [`AgentIdPolicy`](crate::AgentIdPolicy).
+    InvalidAgentId,
+}
+
+impl RejectionReason {
+    /// The wire name, as in `"duplicate_id"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectionReason::DuplicateId => "duplicate_id",
+            RejectionReason::InvalidEndpoint => "invalid_endpoint",
+            RejectionReason::UnreachableEndpoint => "unreachable_endpoint",
+            RejectionReason::ForbiddenCapability => "forbidden_capability",
+            RejectionReason::InvalidAgentId => "invalid_agent_id",
+        }
+    }
+
+    pub(crate) fn from_wire(reason: &str) -> Option<Self> {
+        match reason {
+            "duplicate_id" => Some(RejectionReason::DuplicateId),
+            "invalid_endpoint" => Some(RejectionReason::InvalidEndpoint),
+            "unreachable_endpoint" => Some(RejectionReason::UnreachableEndpoint),
+            "forbidden_capability" => Some(RejectionReason::ForbiddenCapability),
+            "invalid_agent_id" => Some(RejectionReason::InvalidAgentId),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for A2AError {
+    fn from(e: reqwest::Error) -> Self {
+        #[cfg(feature = "tls")]
+        if crate::tls::is_handshake_error(&e) {
+            return A2AError::Tls(e);
+        }
+
+        if e.is_timeout() {
+            A2AError::Timeout(e)
+        } else {
+            A2AError::Http(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for A2AError {
+    fn from(e: serde_json::Error) -> Self {
+        A2AError::Serde(e)
+    }
+}
+
+impl From<std::io::Error> for A2AError {
+    fn from(e: std::io::Error) -> Self {
+        A2AError::Io(e)
+    }
+}
@@ -0,0 +1,90 @@
+//! Checks an [`A2AServer`](crate::A2AServer) runs on a task's input
+//! before any handler sees it.
+
+use std::io;
+
+use serde_json::Value;
+
+/// Limits on the `input` of incoming tasks, checked for `a2a/task`,
+/// `a2a/task/submit` and `a2a/task/stream` before the handler runs.
+///
+/// A task that breaks one is refused with JSON-RPC error `-32602` and a
+/// message naming the limit, so handlers can assume well-formed input
+/// instead of each repeating the same checks. Nothing is checked by
+/// default.
+///
+/// ```rust
+/// use a2a::InputGuard;
+///
+/// let guard = InputGuard::new().require_object().max_bytes(64 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InputGuard {
+    pub require_object: bool,
+    /// The most bytes `input` may take as compact JSON.
+    pub max_bytes: Option<usize>,
+}
+
+impl InputGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse input that isn't a JSON object.
+    pub fn require_object(mut self) -> Self {
+        self.require_object = true;
+        self
+    }
+
+    /// Refuse input larger than `max_bytes` as compact JSON.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Why `input` is refused, if it is.
+    pub(crate) fn check(&self, input: &Value) -> Result<(), String> {
+        if self.require_object && !input.is_object() {
+            return Err(format!("Invalid input: expected a JSON object, got {}", kind(input)));
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            let mut size = Size { bytes: 0, max_bytes };
+            if serde_json::to_writer(&mut size, input).is_err() {
+                return Err(format!("Invalid input: larger than {} bytes", max_bytes));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Counts the bytes written to it, failing once they pass `max_bytes` so
+/// an oversized input is not serialized in full just to be refused.
+struct Size {
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl io::Write for Size {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        if self.bytes > self.max_bytes {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
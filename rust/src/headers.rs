@@ -0,0 +1,45 @@
+//! Extra HTTP headers for the requests of one call.
+//!
+//! Headers set with [`with_headers`] go on every HTTP request the wrapped
+//! future makes, including the directory lookup behind `send_task`, and
+//! take precedence over the agent's default and auth headers. Like the
+//! correlation ID, the scope is task-local.
+
+use reqwest::header::HeaderMap;
+use reqwest::RequestBuilder;
+use std::future::Future;
+
+tokio::task_local! {
+    static HEADERS: HeaderMap;
+}
+
+/// Run `future` with `headers` added to every request it sends. Nested
+/// scopes add to the outer one, replacing headers of the same name.
+///
+/// ```rust,no_run
+/// # async fn example(agent: a2a::A2AAgent) -> Result<(), a2a::A2AError> {
+/// use reqwest::header::{HeaderMap, HeaderValue};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-tenant-id", HeaderValue::from_static("acme"));
+/// let result = a2a::with_headers(
+///     headers,
+///     agent.send_task("calculator", "add", serde_json::json!({"a": 1, "b": 2}), "http://localhost:8080"),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn with_headers<F: Future>(headers: HeaderMap, future: F) -> F::Output {
+    let mut scoped = HEADERS.try_with(Clone::clone).unwrap_or_default();
+    scoped.extend(headers);
+    HEADERS.scope(scoped, future).await
+}
+
+/// Add the headers in scope to `request`, replacing any of the same name.
+pub(crate) fn apply(request: RequestBuilder) -> RequestBuilder {
+    match HEADERS.try_with(Clone::clone) {
+        Ok(headers) => request.headers(headers),
+        Err(_) => request,
+    }
+}
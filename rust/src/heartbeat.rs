@@ -0,0 +1,62 @@
+//! Periodic re-registration so directories that expire stale entries keep
+//! this agent listed.
+
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::A2AAgent;
+
+/// Controls a heartbeat started by [`A2AAgent::start_heartbeat`]. The
+/// heartbeat stops when this handle is stopped or dropped.
+#[derive(Debug)]
+#[must_use = "the heartbeat stops as soon as its handle is dropped"]
+pub struct HeartbeatHandle {
+    task: JoinHandle<()>,
+}
+
+impl HeartbeatHandle {
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for HeartbeatHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl A2AAgent {
+    /// Re-send this agent's registration to `directory_url` every
+    /// `interval`, starting one interval from now.
+    ///
+    /// The agent should already be registered; it re-registers with its
+    /// current name, capabilities and endpoint. A failed attempt is logged
+    /// and retried on the next tick.
+    pub fn start_heartbeat(&self, directory_url: &str, interval: Duration) -> HeartbeatHandle {
+        let agent = self.clone();
+        let directory_url = directory_url.to_string();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(endpoint) = &agent.endpoint else {
+                    warn!(agent_id = %agent.agent_id, "heartbeat skipped: agent has no endpoint");
+                    continue;
+                };
+
+                match agent.send_registration(endpoint, &directory_url).await {
+                    Ok(()) => debug!(agent_id = %agent.agent_id, "heartbeat re-registered"),
+                    Err(e) => warn!(agent_id = %agent.agent_id, error = %e, "heartbeat re-registration failed"),
+                }
+            }
+        });
+
+        HeartbeatHandle { task }
+    }
+}
@@ -0,0 +1,181 @@
+//! Server-side de-duplication of tasks by `task_id`.
+
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+use crate::JSONRPCError;
+
+type Outcome = Result<Value, JSONRPCError>;
+
+/// The outcome of a task that reached its handler, or the error it was
+/// refused with before that, e.g. for want of a slot or of time. Only
+/// outcomes are remembered, since a retry may get past a refusal.
+pub(crate) type Attempt = Result<Outcome, JSONRPCError>;
+
+/// Filled in once by whichever copy of a task runs first.
+type SharedOutcome = Arc<OnceCell<Outcome>>;
+
+/// The sender plus the task ID it chose, so two callers cannot collide.
+type Key = (String, String);
+
+/// Recent task outcomes, keyed by sender and `task_id`. Entries expire
+/// after `ttl`; past `max_entries` the oldest entry is evicted.
+pub(crate) struct IdempotencyCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<Entries>,
+}
+
+/// Entries are never refreshed, so insertion order is age order: expiry
+/// and eviction both pop from the front of `order`.
+#[derive(Default)]
+struct Entries {
+    outcomes: HashMap<Key, SharedOutcome>,
+    order: VecDeque<(Instant, Key)>,
+}
+
+impl IdempotencyCache {
+    pub(crate) fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries: max_entries.max(1),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Run `task` unless the same task was seen recently, in which case
+    /// return its outcome; a duplicate arriving while the first copy is
+    /// still running waits for it. A refused copy leaves nothing behind,
+    /// so the next one runs.
+    pub(crate) async fn run<F, Fut>(&self, sender: &str, task_id: &str, task: F) -> Outcome
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Attempt>,
+    {
+        let cell = self.entry((sender.to_string(), task_id.to_string()));
+        cell.get_or_try_init(task).await?.clone()
+    }
+
+    fn entry(&self, key: Key) -> SharedOutcome {
+        let mut entries = self.entries.lock().unwrap();
+        while entries.order.front().is_some_and(|(stored, _)| stored.elapsed() >= self.ttl) {
+            entries.pop_oldest();
+        }
+
+        if let Some(cell) = entries.outcomes.get(&key) {
+            debug!(task_id = %key.1, "duplicate task");
+            return cell.clone();
+        }
+
+        if entries.outcomes.len() >= self.max_entries {
+            entries.pop_oldest();
+        }
+
+        let cell = Arc::new(OnceCell::new());
+        entries.outcomes.insert(key.clone(), cell.clone());
+        entries.order.push_back((Instant::now(), key));
+        cell
+    }
+}
+
+impl Entries {
+    fn pop_oldest(&mut self) {
+        if let Some((_, key)) = self.order.pop_front() {
+            self.outcomes.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn count_runs(cache: &IdempotencyCache, runs: &AtomicUsize, task_id: &str) -> Outcome {
+        cache
+            .run("client", task_id, || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(Ok(json!(task_id)))
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn a_repeated_task_runs_once() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+        let runs = AtomicUsize::new(0);
+
+        assert_eq!(count_runs(&cache, &runs, "t1").await.unwrap(), json!("t1"));
+        assert_eq!(count_runs(&cache, &runs, "t1").await.unwrap(), json!("t1"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // The same ID from another sender is another task.
+        cache.run("other", "t1", || async { Ok(Ok(json!("other"))) }).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.entries.lock().unwrap().outcomes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn refused_tasks_are_not_remembered() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+        let runs = AtomicUsize::new(0);
+
+        let busy = JSONRPCError {
+            code: -32000,
+            message: "Server busy".to_string(),
+            data: None,
+        };
+        let refused = cache.run("client", "t1", || async { Err(busy) }).await;
+        assert_eq!(refused.unwrap_err().code, -32000);
+        assert_eq!(count_runs(&cache, &runs, "t1").await.unwrap(), json!("t1"));
+        assert_eq!(count_runs(&cache, &runs, "t1").await.unwrap(), json!("t1"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Failures of the handler itself are outcomes like any other.
+        let failed = JSONRPCError {
+            code: -32050,
+            message: "declined".to_string(),
+            data: None,
+        };
+        cache.run("client", "t2", || async { Ok(Err(failed)) }).await.unwrap_err();
+        assert_eq!(count_runs(&cache, &runs, "t2").await.unwrap_err().code, -32050);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_run_again() {
+        let cache = IdempotencyCache::new(Duration::from_millis(50), 10);
+        let runs = AtomicUsize::new(0);
+
+        count_runs(&cache, &runs, "t1").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        count_runs(&cache, &runs, "t1").await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.entries.lock().unwrap().order.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn the_oldest_entry_is_evicted_when_full() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 2);
+        let runs = AtomicUsize::new(0);
+
+        for task_id in ["t1", "t2", "t3"] {
+            count_runs(&cache, &runs, task_id).await.unwrap();
+        }
+        // t2 and t3 are still remembered; t1 was evicted.
+        count_runs(&cache, &runs, "t3").await.unwrap();
+        count_runs(&cache, &runs, "t2").await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        count_runs(&cache, &runs, "t1").await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.outcomes.len(), 2);
+        assert_eq!(entries.order.len(), 2);
+    }
+}
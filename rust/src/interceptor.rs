@@ -0,0 +1,45 @@
+//! Hooks run around every outbound HTTP request.
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// An outbound request as seen by [`Interceptor::on_request`].
+///
+/// Only changes to `headers` are sent; `method` and `url` are informational.
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    /// The JSON-RPC method, or `a2a/agents` and `health` for the plain
+    /// `GET` lookups and health checks.
+    pub method: String,
+    pub url: String,
+    pub headers: HeaderMap,
+}
+
+/// The outcome of a request, passed to [`Interceptor::on_response`].
+#[derive(Debug, Clone)]
+pub struct ResponseParts {
+    pub method: String,
+    pub url: String,
+    /// `None` when no response arrived, e.g. the connection failed.
+    pub status: Option<StatusCode>,
+    pub elapsed: Duration,
+}
+
+/// Middleware for an agent's directory and peer calls, registered with
+/// [`A2AAgent::with_interceptor`](crate::A2AAgent::with_interceptor).
+///
+/// `on_request` runs in registration order just before each HTTP attempt
+/// (so once per retry), after credentials were added; `on_response` runs
+/// in reverse order once the attempt finishes. Both default to doing
+/// nothing.
+///
+/// See `examples/latency_interceptor.rs` for an interceptor that logs
+/// how long every call takes.
+#[async_trait]
+pub trait Interceptor: Send + Sync {
+    async fn on_request(&self, _request: &mut RequestParts) {}
+
+    async fn on_response(&self, _response: &ResponseParts) {}
+}
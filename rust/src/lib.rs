@@ -2,11 +2,12 @@
 //!
 //! # Usage
 //!
-//! ```rust
-//! use a2a::{A2AAgent, A2AServer};
+//! ```rust,no_run
+//! use a2a::A2AAgent;
 //!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! // Create an agent
-//! let agent = A2AAgent::new(
+//! let mut agent = A2AAgent::new(
 //!     "my-agent",
 //!     "My Agent",
 //!     vec!["search".to_string(), "summarize".to_string()],
@@ -16,7 +17,10 @@
 //! agent.register("http://localhost:9001", "http://localhost:8080").await?;
 //!
 //! // Discover agents
-//! let other = agent.discover(vec!["calculator".to_string()], "http://localhost:8080").await?;
+//! let other = agent
+//!     .discover(vec!["calculator".to_string()], "http://localhost:8080")
+//!     .await?
+//!     .ok_or("no calculator agent registered")?;
 //!
 //! // Send a task
 //! let result = agent.send_task(
@@ -25,138 +29,1284 @@
 //!     serde_json::json!({"a": 10, "b": 20}),
 //!     "http://localhost:8080"
 //! ).await?;
+//! # Ok(())
+//! # }
 //! ```
 
+// Without `tracing`, values that are only logged go unused.
+#![cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+compile_error!("building for wasm32 requires the `wasm` feature");
+#[cfg(all(
+    target_arch = "wasm32",
+    any(feature = "blocking", feature = "compression", feature = "sqlite", feature = "tls", feature = "ws")
+))]
+compile_error!("the `blocking`, `compression`, `sqlite`, `tls` and `ws` features need a native target");
+
+use chrono::{DateTime, Utc};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[macro_use]
+mod logging;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod abandon;
+mod agent_id;
+mod attachment;
+mod auth;
+#[cfg(not(target_arch = "wasm32"))]
+mod authorizer;
+mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+mod batcher;
+mod body_log;
+mod breaker;
+mod broadcast;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+mod cancellation;
+mod capability;
+mod codec;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(not(target_arch = "wasm32"))]
+mod concurrency;
+mod correlation;
+mod deadline;
+#[cfg(not(target_arch = "wasm32"))]
+mod directory;
+mod dry_run;
+mod env;
+mod error;
+#[cfg(not(target_arch = "wasm32"))]
+mod guard;
+mod headers;
+#[cfg(not(target_arch = "wasm32"))]
+mod heartbeat;
+#[cfg(not(target_arch = "wasm32"))]
+mod idempotency;
+mod interceptor;
+mod managed;
+#[cfg(not(target_arch = "wasm32"))]
+mod memory;
+mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+mod observer;
+mod outbox;
+mod page;
+mod priority;
+mod query;
+mod ratelimit;
+#[cfg(not(target_arch = "wasm32"))]
+mod registration;
+mod raw;
+mod retry;
+mod selection;
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(not(target_arch = "wasm32"))]
+mod store;
+mod stream;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
+mod time;
+mod timestamp;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "otel")]
+mod trace_context;
+mod transport;
+mod validation;
+mod version;
+mod watch;
+#[cfg(feature = "ws")]
+mod ws;
+
+pub use agent_id::AgentIdPolicy;
+pub use attachment::Attachment;
+pub use auth::{AuthConfig, TokenProvider};
+#[cfg(not(target_arch = "wasm32"))]
+pub use authorizer::{AuthError, Authorizer, BearerAuthorizer};
+#[cfg(not(target_arch = "wasm32"))]
+pub use batcher::TaskBatcher;
+pub use breaker::CircuitBreaker;
+pub use builder::A2AAgentBuilder;
+pub use capability::Capability;
+pub use codec::{Codec, JsonCodec};
+#[cfg(feature = "msgpack")]
+pub use codec::MsgPackCodec;
+#[cfg(feature = "compression")]
+pub use compression::{Compression, Encoding};
+#[cfg(not(target_arch = "wasm32"))]
+pub use concurrency::OverloadPolicy;
+pub use correlation::{current_correlation_id, with_correlation_id};
+pub use deadline::{remaining_budget, with_deadline};
+#[cfg(not(target_arch = "wasm32"))]
+pub use directory::A2ADirectory;
+pub use dry_run::TaskRequest;
+pub use error::{A2AError, RejectionReason};
+#[cfg(not(target_arch = "wasm32"))]
+pub use guard::InputGuard;
+pub use headers::with_headers;
+#[cfg(not(target_arch = "wasm32"))]
+pub use heartbeat::HeartbeatHandle;
+pub use interceptor::{Interceptor, RequestParts, ResponseParts};
+pub use managed::ManagedAgent;
+#[cfg(not(target_arch = "wasm32"))]
+pub use memory::InMemoryTransport;
+#[cfg(feature = "metrics")]
+pub use metrics::render_metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub use observer::TaskObserver;
+#[cfg(not(target_arch = "wasm32"))]
+pub use outbox::FileOutbox;
+pub use outbox::{Outbox, PendingTask};
+pub use page::{Page, PageOptions};
+pub use priority::{with_priority, DEFAULT_PRIORITY};
+pub use query::CapabilityQuery;
+pub use ratelimit::RateLimit;
+#[cfg(not(target_arch = "wasm32"))]
+pub use registration::{AllowAllRegistrations, RegistrationPolicy};
+pub use retry::RetryPolicy;
+pub use selection::SelectionStrategy;
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{
+    run_server, A2AServer, AsyncTaskHandler, IntoTaskOutput, MethodContext, MethodHandler, StreamTaskHandler, TaskContext,
+};
+#[cfg(feature = "signing")]
+pub use signing::{ResultSignature, SigningKey, VerifyingKey};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteRegistryStore;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::{MemoryRegistryStore, RegistryStore, StoredAgent};
+pub use stream::{TaskChunk, TaskStream};
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokio_util::sync::CancellationToken;
+#[cfg(feature = "tls")]
+pub use tls::{ServerTlsConfig, TlsConfig};
+#[cfg(feature = "otel")]
+pub use trace_context::TRACEPARENT;
+pub use transport::{HttpTransport, Transport};
+pub use version::{Version, VersionReq};
+pub use watch::{DirectoryEvent, DirectoryEventKind, DirectoryEventStream};
+#[cfg(feature = "ws")]
+pub use ws::{WsNotification, WsSession};
+
+use body_log::BodyLog;
+use breaker::Breakers;
+use cache::DiscoveryCache;
+use ratelimit::RateLimiter;
+use selection::Selector;
 
 // ============ Types ============
 
+/// A directory's record of one agent.
+///
+/// The metadata fields are optional on the wire, so records from
+/// directories that predate them still parse:
+///
+/// ```
+/// let agent: a2a::AgentInfo = serde_json::from_value(serde_json::json!({
+///     "agentId": "calc",
+///     "name": "Calculator",
+///     "capabilities": ["add"],
+///     "endpoint": "http://localhost:9001",
+///     "registeredAt": null,
+/// }))?;
+/// assert!(agent.version.is_none() && agent.tags.is_empty());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// It is written with the camelCase names `agentId` and `registeredAt`,
+/// but older directories wrote `agent_id` and `registered_at`, so both
+/// spellings are read. The same goes for the camelCase fields of the
+/// protocol's params and results: `taskId`, `correlationId`,
+/// `deadlineMs`, `inputSchema`, `outputSchema`, `keyId`, `nextCursor`
+/// and the `allOf`/`anyOf` queries also accept their snake_case forms.
+///
+/// ```
+/// let json = r#"{"agent_id": "calc", "name": "Calc", "capabilities": [],
+///     "endpoint": "http://localhost:9001", "registered_at": "2024-05-01T10:00:00Z"}"#;
+/// let agent: a2a::AgentInfo = serde_json::from_str(json)?;
+/// assert_eq!(agent.agent_id, "calc");
+/// assert!(agent.registered_at_time().is_some());
+///
+/// let written = serde_json::to_value(&agent)?;
+/// assert_eq!(written["agentId"], "calc");
+/// assert!(written.get("agent_id").is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AgentInfo {
+    #[serde(rename = "agentId", alias = "agent_id")]
     pub agent_id: String,
     pub name: String,
-    pub capabilities: Vec<String>,
+    pub capabilities: Vec<Capability>,
     pub endpoint: String,
-    #[serde(rename = "registeredAt")]
+    #[serde(rename = "registeredAt", alias = "registered_at")]
     pub registered_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Free-form labels such as `team` or `region`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JSONRPCRequest {
-    jsonrpc: String,
-    id: String,
-    method: String,
+impl AgentInfo {
+    /// A record of an agent reachable at `endpoint`, e.g. for tests or a
+    /// custom directory, with no version, description, tags or
+    /// registration time. Fails with [`A2AError::InvalidEndpoint`] unless
+    /// `endpoint` is an absolute `http` or `https` URL with a host; the
+    /// fields stay public, so records read with serde are not checked.
+    ///
+    /// ```
+    /// use a2a::{A2AError, AgentInfo};
+    ///
+    /// let agent = AgentInfo::new("calc", "Calculator", vec!["add".into()], "http://10.0.0.5:9001")?;
+    /// assert_eq!(agent.endpoint, "http://10.0.0.5:9001");
+    ///
+    /// for endpoint in ["10.0.0.5:9001", "/a2a", "ftp://10.0.0.5", "http://", ""] {
+    ///     let refused = AgentInfo::new("calc", "Calculator", vec![], endpoint);
+    ///     assert!(matches!(refused, Err(A2AError::InvalidEndpoint(_))), "{}", endpoint);
+    /// }
+    /// # Ok::<(), A2AError>(())
+    /// ```
+    pub fn new(agent_id: &str, name: &str, capabilities: Vec<String>, endpoint: &str) -> Result<Self, A2AError> {
+        check_endpoint(endpoint)?;
+        Ok(Self {
+            agent_id: agent_id.to_string(),
+            name: name.to_string(),
+            capabilities: capabilities.into_iter().map(Capability::from).collect(),
+            endpoint: endpoint.to_string(),
+            registered_at: None,
+            version: None,
+            description: None,
+            tags: HashMap::new(),
+        })
+    }
+
+    /// Move the agent to `endpoint`, checked as [`new`](Self::new) does.
+    /// On failure the endpoint is left as it was.
+    pub fn set_endpoint(&mut self, endpoint: &str) -> Result<(), A2AError> {
+        check_endpoint(endpoint)?;
+        self.endpoint = endpoint.to_string();
+        Ok(())
+    }
+
+    /// Take on the details of `other`, a later record of the same agent:
+    /// its name, capabilities, endpoint and tags, and its version,
+    /// description and `registered_at` where it has them. `agent_id` is
+    /// kept.
+    pub fn merge(&mut self, other: AgentInfo) {
+        self.name = other.name;
+        self.capabilities = other.capabilities;
+        self.endpoint = other.endpoint;
+        self.tags = other.tags;
+        if other.registered_at.is_some() {
+            self.registered_at = other.registered_at;
+        }
+        if other.version.is_some() {
+            self.version = other.version;
+        }
+        if other.description.is_some() {
+            self.description = other.description;
+        }
+    }
+
+    /// `registered_at` parsed as an RFC 3339 timestamp, or `None` if it
+    /// is missing or malformed.
+    ///
+    /// ```
+    /// # use a2a::AgentInfo;
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let mut agent: AgentInfo = serde_json::from_str(r#"{"agentId": "calc", "name": "Calc",
+    ///     "capabilities": [], "endpoint": "http://localhost:9001", "registeredAt": null}"#)?;
+    /// assert_eq!(agent.registered_at_time(), None);
+    ///
+    /// agent.registered_at = Some("2024-05-01T12:00:00+02:00".to_string());
+    /// assert_eq!(agent.registered_at_time(), Some(Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap()));
+    ///
+    /// agent.registered_at = Some("yesterday".to_string());
+    /// assert_eq!(agent.registered_at_time(), None);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn registered_at_time(&self) -> Option<DateTime<Utc>> {
+        timestamp::parse(self.registered_at.as_deref()?)
+    }
+
+    /// Whether this record is at least as recent as `other`. A record
+    /// with a registration time beats one without.
+    fn is_newer_than(&self, other: &AgentInfo) -> bool {
+        self.registered_at_time() >= other.registered_at_time()
+    }
+}
+
+/// Refuse `endpoint` unless it is an absolute `http` or `https` URL with a
+/// host.
+fn check_endpoint(endpoint: &str) -> Result<(), A2AError> {
+    let url = reqwest::Url::parse(endpoint).map_err(|e| A2AError::InvalidEndpoint(format!("{:?}: {}", endpoint, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(A2AError::InvalidEndpoint(format!("{:?}: scheme must be http or https", endpoint)));
+    }
+    if !url.has_host() {
+        return Err(A2AError::InvalidEndpoint(format!("{:?}: no host", endpoint)));
+    }
+    Ok(())
+}
+
+/// Two records are of the same agent when their `agent_id`s match,
+/// however the rest changed between registrations.
+///
+/// ```
+/// # use a2a::AgentInfo;
+/// # use std::collections::HashSet;
+/// let old: AgentInfo = serde_json::from_str(r#"{"agentId": "calc", "name": "Calc", "capabilities": [],
+///     "endpoint": "http://10.0.0.1:9001", "registeredAt": "2024-05-01T10:00:00Z"}"#)?;
+/// let mut new = old.clone();
+/// new.endpoint = "http://10.0.0.2:9001".to_string();
+///
+/// assert_eq!(old, new);
+/// assert_eq!(HashSet::from([old, new]).len(), 1);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl PartialEq for AgentInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.agent_id == other.agent_id
+    }
+}
+
+impl Eq for AgentInfo {}
+
+impl std::hash::Hash for AgentInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.agent_id.hash(state);
+    }
+}
+
+/// Bookkeeping for lists of agents, e.g. merged from several directories.
+pub trait AgentListExt {
+    /// Keep one record per `agent_id`: the most recently registered, or
+    /// the last one when they tie. Agents stay in order of first
+    /// appearance.
+    ///
+    /// ```
+    /// use a2a::{AgentInfo, AgentListExt};
+    /// use serde_json::json;
+    ///
+    /// let record = |endpoint: &str, at: &str| -> AgentInfo {
+    ///     serde_json::from_value(json!({"agentId": "calc", "name": "Calc", "capabilities": [],
+    ///         "endpoint": endpoint, "registeredAt": at})).unwrap()
+    /// };
+    /// let mut agents = vec![
+    ///     record("http://10.0.0.2:9001", "2024-05-02T10:00:00Z"),
+    ///     record("http://10.0.0.1:9001", "2024-05-01T10:00:00Z"),
+    /// ];
+    /// agents.dedup_agents();
+    ///
+    /// assert_eq!(agents.len(), 1);
+    /// assert_eq!(agents[0].endpoint, "http://10.0.0.2:9001");
+    /// ```
+    fn dedup_agents(&mut self);
+}
+
+impl AgentListExt for Vec<AgentInfo> {
+    fn dedup_agents(&mut self) {
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut kept: Vec<AgentInfo> = Vec::with_capacity(self.len());
+        for agent in self.drain(..) {
+            match positions.get(&agent.agent_id) {
+                Some(&i) if agent.is_newer_than(&kept[i]) => kept[i] = agent,
+                Some(_) => {}
+                None => {
+                    positions.insert(agent.agent_id.clone(), kept.len());
+                    kept.push(agent);
+                }
+            }
+        }
+        *self = kept;
+    }
+}
+
+/// The only `jsonrpc` version spoken, required on every request and
+/// response.
+const JSONRPC_VERSION: &str = "2.0";
+
+/// A JSON-RPC 2.0 request, as sent to directories and peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JSONRPCRequest {
+    // Missing versions are read as empty and rejected like wrong ones.
+    #[serde(default)]
+    pub jsonrpc: String,
+    /// `null` for a notification, which is sent without an `id` and gets
+    /// no response.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub id: Value,
+    pub method: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    params: Option<Value>,
+    pub params: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JSONRPCResponse {
+    #[serde(default)]
     jsonrpc: String,
-    id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Value,
+    /// `Some(Value::Null)` for `"result": null`, `None` only when absent.
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
     result: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<JSONRPCError>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Read a field that is there, even as `null`, as `Some`; with
+/// `#[serde(default)]`, a missing one stays `None`.
+fn present<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+impl JSONRPCResponse {
+    /// Fail unless this answers the request with `id`. An error response
+    /// with a `null` id, sent when the server could not read the request,
+    /// passes so that its error surfaces.
+    fn check_id(&self, id: &Value) -> Result<(), A2AError> {
+        if &self.id == id || (self.id.is_null() && self.error.is_some()) {
+            return Ok(());
+        }
+        Err(A2AError::IdMismatch {
+            expected: id.clone(),
+            got: self.id.clone(),
+        })
+    }
+
+    /// Fail unless the response declares `"jsonrpc": "2.0"`.
+    fn check_version(&self) -> Result<(), A2AError> {
+        if self.jsonrpc == JSONRPC_VERSION {
+            return Ok(());
+        }
+        Err(A2AError::Protocol(format!(
+            "expected jsonrpc \"{}\", got {:?}",
+            JSONRPC_VERSION, self.jsonrpc
+        )))
+    }
+
+    fn into_result(self) -> Result<Value, A2AError> {
+        self.check_version()?;
+        if self.result.is_some() && self.error.is_some() {
+            return Err(A2AError::Protocol("response carries both result and error".to_string()));
+        }
+        if let Some(error) = self.error {
+            if error.code == DEADLINE_EXCEEDED {
+                return Err(A2AError::DeadlineExceeded(error.message));
+            }
+            if let Some(reason) = error.rejection_reason() {
+                return Err(A2AError::RegistrationRejected {
+                    reason,
+                    message: error.message,
+                });
+            }
+            return Err(A2AError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+
+        self.result.ok_or(A2AError::EmptyResponse)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct JSONRPCError {
     code: i32,
     message: String,
+    /// Details of the failure; a directory refusing a registration sends
+    /// `{"reason": ...}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl JSONRPCError {
+    /// The registration rejection this error carries in its `data`, if any.
+    fn rejection_reason(&self) -> Option<RejectionReason> {
+        let reason = self.data.as_ref()?.get("reason")?.as_str()?;
+        RejectionReason::from_wire(reason)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct RegisterParams {
-    #[serde(rename = "agentId")]
+    #[serde(rename = "agentId", alias = "agent_id")]
     agent_id: String,
     name: String,
-    capabilities: Vec<String>,
+    capabilities: Vec<Capability>,
     endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tags: HashMap<String, String>,
+}
+
+/// A change to a registered agent's capabilities. Names in `remove` are
+/// dropped first, then `add` replaces or appends capabilities by name.
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateParams {
+    #[serde(rename = "agentId", alias = "agent_id")]
+    agent_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    add: Vec<Capability>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remove: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DeregisterParams {
+    #[serde(rename = "agentId", alias = "agent_id")]
+    agent_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct DiscoverParams {
-    capabilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capabilities: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    query: Option<CapabilityQuery>,
+    /// Tags every returned agent must carry.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tags: HashMap<String, String>,
+    /// Most agents to return, for paged discovery.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    /// Return only agents after this one, as named by a previous page's
+    /// `nextCursor`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct TaskParams {
-    #[serde(rename = "taskId")]
+    #[serde(rename = "taskId", alias = "task_id")]
     task_id: String,
     action: String,
     sender: String,
     input: Value,
+    /// Shared by every task spawned from the same originating request.
+    #[serde(rename = "correlationId", alias = "correlation_id", default, skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<String>,
+    /// Milliseconds the sender had left when it sent the task.
+    #[serde(rename = "deadlineMs", alias = "deadline_ms", default, skip_serializing_if = "Option::is_none")]
+    deadline_ms: Option<u64>,
+    /// The version of `action` the sender chose, if it asked for one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<Version>,
+    /// Higher is more urgent; see [`with_priority`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct TaskResult {
-    #[serde(rename = "taskId")]
+struct TaskStatusParams {
+    #[serde(rename = "taskId", alias = "task_id")]
     task_id: String,
-    status: String,
-    output: Option<Value>,
+    /// The agent that sent the task; a server only answers it about its
+    /// own tasks.
+    sender: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    /// Submitted via `a2a/task/submit`; the peer has taken it on. This is
+    /// the acknowledgement a submit answers with, together with the
+    /// `taskId` it was sent.
+    Accepted,
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Stopped at the caller's request via `a2a/task/cancel`.
+    Cancelled,
+    /// Never sent, by an agent in dry-run mode; see
+    /// [`A2AAgent::with_dry_run`].
+    #[serde(rename = "dry-run")]
+    DryRun,
+}
+
+impl TaskStatus {
+    /// Whether the task has finished and its status will not change again.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled | TaskStatus::DryRun)
+    }
+
+    /// The wire name, as in `"completed"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskStatus::Accepted => "accepted",
+            TaskStatus::Pending => "pending",
+            TaskStatus::Running => "running",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Cancelled => "cancelled",
+            TaskStatus::DryRun => "dry-run",
+        }
+    }
+}
+
+/// JSON-RPC error code of [`TaskError::failed`].
+const TASK_FAILED: i32 = -32001;
+
+/// JSON-RPC error code for a task that arrived after its deadline.
+pub(crate) const DEADLINE_EXCEEDED: i32 = -32002;
+
+/// A task rejected by its handler. Sent back to the caller as the
+/// JSON-RPC `error` object instead of a `result`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl TaskError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// A generic `-32001` "task failed" error.
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(TASK_FAILED, message)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskResult {
+    #[serde(rename = "taskId", alias = "task_id")]
+    pub task_id: String,
+    pub status: TaskStatus,
+    pub output: Option<Value>,
+    /// Why the task failed, when `status` is [`TaskStatus::Failed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<TaskError>,
+    /// Set by servers with a [`SigningKey`].
+    #[cfg(feature = "signing")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ResultSignature>,
+}
+
+impl TaskResult {
+    pub(crate) fn new(task_id: &str, status: TaskStatus) -> Self {
+        Self {
+            task_id: task_id.to_string(),
+            status,
+            output: None,
+            error: None,
+            #[cfg(feature = "signing")]
+            signature: None,
+        }
+    }
 }
 
 // ============ A2AAgent ============
 
+/// Timeout applied to every outbound request unless overridden.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where directory endpoints are mounted unless overridden.
+pub const DEFAULT_PATH_PREFIX: &str = "/a2a";
+
+/// What JSON-RPC method names begin with unless overridden.
+pub const DEFAULT_METHOD_NAMESPACE: &str = "a2a";
+
+/// An agent's client side: registering with directories, discovering
+/// peers and sending them tasks.
+///
+/// Clones are cheap and share the HTTP connection pool, credentials and
+/// transport, as well as the discovery cache, rate limits, circuit
+/// breakers and selection state, so every clone sees the same
+/// round-robin turn. Settings changed on a clone afterwards, such as its
+/// capabilities, stay its own. [`Default`] builds a client-only agent
+/// with a generated `agent_id` as both ID and name and no capabilities.
+#[derive(Clone)]
 pub struct A2AAgent {
     pub agent_id: String,
     pub name: String,
-    pub capabilities: Vec<String>,
+    pub capabilities: Vec<Capability>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub tags: HashMap<String, String>,
     pub endpoint: Option<String>,
-    client: Client,
+    /// The directory this agent was configured to use, e.g. from
+    /// `A2A_DIRECTORY_URL` by [`from_env`](Self::from_env). Calls still
+    /// take their directory as an argument; this is where to find one.
+    pub directory_url: Option<String>,
+    http: HttpTransport,
+    transport: Option<Arc<dyn Transport>>,
+    retry: RetryPolicy,
+    validate_input: bool,
+    check_actions: bool,
+    dry_run: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    cancel_on_drop: bool,
+    broadcast_concurrency: usize,
+    agent_id_policy: AgentIdPolicy,
+    discovery_cache: Option<Arc<DiscoveryCache>>,
+    fallback_directories: Vec<String>,
+    outbox: Option<Arc<dyn Outbox>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    circuit_breakers: Option<Arc<Breakers>>,
+    selector: Arc<Selector>,
+    #[cfg(feature = "signing")]
+    verifying_keys: HashMap<String, VerifyingKey>,
+    path_prefix: String,
+    method_namespace: String,
 }
 
+/// Building the client fails only for proxy or TLS settings, which the
+/// constructors below leave alone.
+const DEFAULT_CLIENT: &str = "an HTTP client with default settings builds";
+
 impl A2AAgent {
     pub fn new(agent_id: &str, name: &str, capabilities: Vec<String>) -> Self {
-        Self {
-            agent_id: agent_id.to_string(),
-            name: name.to_string(),
-            capabilities,
-            endpoint: None,
-            client: Client::new(),
+        Self::builder(agent_id, name).capabilities(capabilities).build().expect(DEFAULT_CLIENT)
+    }
+
+    /// Start configuring an agent fluently; see [`A2AAgentBuilder`].
+    pub fn builder(agent_id: &str, name: &str) -> A2AAgentBuilder {
+        A2AAgentBuilder::new(agent_id, name)
+    }
+
+    /// Create an agent whose directory and peer calls give up after
+    /// `timeout`. The same limit is used for establishing the connection.
+    pub fn with_timeout(agent_id: &str, name: &str, capabilities: Vec<String>, timeout: Duration) -> Self {
+        Self::builder(agent_id, name)
+            .capabilities(capabilities)
+            .timeout(timeout)
+            .build()
+            .expect(DEFAULT_CLIENT)
+    }
+
+    /// Create an agent that sends all requests through `client`.
+    ///
+    /// Cloning a `reqwest::Client` is cheap and the clones share one
+    /// connection pool, so a process hosting many logical agents should
+    /// build a single client and hand each agent a clone. Timeouts and
+    /// other transport settings come from `client` itself.
+    pub fn with_client(agent_id: &str, name: &str, capabilities: Vec<String>, client: Client) -> Self {
+        Self::builder(agent_id, name)
+            .capabilities(capabilities)
+            .client(client)
+            .build()
+            .expect(DEFAULT_CLIENT)
+    }
+
+    /// Replace the plain capability names with fully described
+    /// capabilities, including input/output schemas, for registration.
+    pub fn with_capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Advertise `version` on registration, e.g. `"1.4.0"`.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Advertise a human-readable description on registration.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Advertise the tag `key = value` on registration, replacing any
+    /// earlier value for `key`.
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check `send_task` input against the target's declared input schema
+    /// before sending it, failing with [`A2AError::InvalidInput`] locally.
+    pub fn with_input_validation(mut self, validate_input: bool) -> Self {
+        self.validate_input = validate_input;
+        self
+    }
+
+    /// Refuse to send a task whose `action` the target does not list among
+    /// its capabilities, failing with [`A2AError::UnsupportedAction`]
+    /// locally. The capabilities come from the directory lookup every send
+    /// makes anyway, so the check costs no extra round trip.
+    pub fn with_action_check(mut self, check_actions: bool) -> Self {
+        self.check_actions = check_actions;
+        self
+    }
+
+    /// Resolve targets and check tasks as usual, but never send them:
+    /// `send_task` and `submit_task` return a [`TaskStatus::DryRun`] result
+    /// echoing the input instead. Useful for testing the code that decides
+    /// what to send; see also [`build_task_request`](Self::build_task_request).
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Ask the target to cancel a task with `a2a/task/cancel`, in the
+    /// background, when the future of the call that sent it is dropped
+    /// before the answer came, e.g. because it lost a `select!` or a
+    /// `tokio::time::timeout`.
+    ///
+    /// Dropping such a future always aborts its HTTP request at once,
+    /// closing the connection. An [`A2AServer`] then stops awaiting the
+    /// handler, but a target behind a proxy, or whose handler handed the
+    /// work elsewhere, may run the task to the end. This applies to [`send_task`](Self::send_task) and
+    /// the calls built on it, not to submits, batches or streams. The
+    /// cancel is best effort, cooperative like
+    /// [`cancel_task`](Self::cancel_task), and needs a Tokio runtime still
+    /// running when the future is dropped; its failures are only logged.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_cancel_on_drop(mut self, cancel_on_drop: bool) -> Self {
+        self.cancel_on_drop = cancel_on_drop;
+        self
+    }
+
+    /// Send at most `max` of a [`broadcast_task`](Self::broadcast_task)'s
+    /// tasks at once (default: 16).
+    pub fn with_broadcast_concurrency(mut self, max: usize) -> Self {
+        self.broadcast_concurrency = max;
+        self
+    }
+
+    /// Check the agent's ID and name against `policy` instead of the
+    /// default [`AgentIdPolicy`] before registering.
+    pub fn with_agent_id_policy(mut self, policy: AgentIdPolicy) -> Self {
+        self.agent_id_policy = policy;
+        self
+    }
+
+    /// Send `headers`, e.g. `X-Tenant-Id`, on every directory and peer
+    /// request. Auth headers and those of a [`with_headers`] scope take
+    /// precedence over them.
+    pub fn with_default_headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.http.default_headers = headers;
+        self
+    }
+
+    /// Identify as `user_agent` on every directory and peer request, e.g.
+    /// `billing/3.1 a2a-rust/0.1.0`, instead of the default
+    /// `a2a-rust/<crate version>`. A `User-Agent` among the
+    /// [default headers](Self::with_default_headers) wins over it, and a
+    /// value that is not a valid header fails each request with
+    /// [`A2AError::Http`]. Browsers send their own and ignore it.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = user_agent.into();
+        self
+    }
+
+    /// Encode calls with `codec` instead of JSON, and ask for answers in
+    /// it. JSON answers are still read, but the peer or directory must
+    /// read `codec`, as an [`A2AServer`] or [`A2ADirectory`] does for the
+    /// codecs built into it; see [`Codec`].
+    pub fn with_codec(mut self, codec: Arc<dyn Codec>) -> Self {
+        self.http.codec = Some(codec);
+        self
+    }
+
+    /// Accept compressed responses and compress request bodies of at
+    /// least `compression.min_bytes`. The peer or directory must accept
+    /// `Content-Encoding` on requests, as an [`A2AServer`] with
+    /// compression on does.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.http.compression = Some(compression);
+        self
+    }
+
+    /// Fail with [`A2AError::ResponseTooLarge`] rather than read a
+    /// directory or peer response body beyond `max_bytes` (default
+    /// 16 MiB). Streamed task output is not limited.
+    pub fn with_max_response_bytes(mut self, max_bytes: usize) -> Self {
+        self.http.max_response_bytes = max_bytes;
+        self
+    }
+
+    /// Log the body of every JSON-RPC request this agent sends, and of
+    /// each response, at debug level, with the fields at the dot-separated
+    /// paths in `redact`, e.g. `params.input.password`, shown as `"***"`.
+    /// Paths run through arrays, applying to each element. Headers, and so
+    /// credentials, are never logged. Off by default; streamed task output
+    /// and batches are not logged.
+    pub fn with_body_logging(mut self, redact: Vec<String>) -> Self {
+        self.http.body_log = Some(Arc::new(BodyLog::new(redact)));
+        self
+    }
+
+    /// Attach `auth` to every directory and peer request.
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.http.auth = Some(auth);
+        self
+    }
+
+    /// Fetch a fresh bearer token from `provider` before every request.
+    /// Takes precedence over any static [`AuthConfig`].
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.http.token_provider = Some(provider);
+        self
+    }
+
+    /// Override how failed directory and peer calls are retried.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Remember discovery results for `ttl`, keyed by directory and
+    /// capability set, instead of asking the directory on every call.
+    pub fn with_discovery_cache(mut self, ttl: Duration) -> Self {
+        self.discovery_cache = Some(Arc::new(DiscoveryCache::new(ttl)));
+        self
+    }
+
+    /// Directories to fall back on, in order, when the `directory_url`
+    /// passed to a call refuses connections or answers with a 5xx.
+    pub fn with_fallback_directories(mut self, directory_urls: Vec<String>) -> Self {
+        self.fallback_directories = directory_urls;
+        self
+    }
+
+    /// Keep tasks passed to [`submit_task`](Self::submit_task) in `outbox`
+    /// until the target acknowledges them, so that
+    /// [`resubmit_pending`](Self::resubmit_pending) can send them again
+    /// after a crash; see [`Outbox`].
+    pub fn with_outbox(mut self, outbox: Arc<dyn Outbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Throttle tasks sent to each target agent; see [`RateLimit`].
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Fail calls fast to target agents that keep failing; see
+    /// [`CircuitBreaker`].
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breakers = Some(Arc::new(Breakers::new(breaker)));
+        self
+    }
+
+    /// Append `interceptor` to the chain run around every outbound
+    /// request; see [`Interceptor`] for the invocation order.
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.http.interceptors.push(interceptor);
+        self
+    }
+
+    /// Require task results from `agent_id` to be signed with `key`,
+    /// failing with [`A2AError::SignatureInvalid`] otherwise. Applies to
+    /// `send_task`, `submit_task`, batches and status polls over HTTP.
+    #[cfg(feature = "signing")]
+    pub fn with_verifying_key(mut self, agent_id: impl Into<String>, key: VerifyingKey) -> Self {
+        self.verifying_keys.insert(agent_id.into(), key);
+        self
+    }
+
+    /// Reach the directory's endpoints under `prefix` instead of `/a2a`,
+    /// e.g. `/api/v2/a2a` makes registration post to
+    /// `{directory_url}/api/v2/a2a/register`.
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefix = normalize_path_prefix(prefix);
+        self
+    }
+
+    /// Name JSON-RPC methods `{namespace}/register`, `{namespace}/task` and
+    /// so on instead of `a2a/...`. The directory and peers must answer to
+    /// the same names.
+    pub fn with_method_namespace(mut self, namespace: &str) -> Self {
+        self.method_namespace = namespace.trim_matches('/').to_string();
+        self
+    }
+
+    /// Make registration, discovery and task calls through `transport`
+    /// instead of HTTP. Streaming, batches, `ping` and WebSocket sessions
+    /// still go over HTTP, and credentials and interceptors apply to HTTP
+    /// only.
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Forget all cached discovery results.
+    pub fn clear_discovery_cache(&self) {
+        if let Some(cache) = &self.discovery_cache {
+            cache.clear();
         }
     }
 
-    pub async fn register(&mut self, endpoint: &str, directory_url: &str) -> Result<(), String> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id)))]
+    pub async fn register(&mut self, endpoint: &str, directory_url: &str) -> Result<(), A2AError> {
         self.endpoint = Some(endpoint.to_string());
+        self.send_registration(endpoint, directory_url).await?;
+
+        info!(agent_id = %self.agent_id, endpoint, "registered with directory");
+        Ok(())
+    }
 
+    async fn send_registration(&self, endpoint: &str, directory_url: &str) -> Result<(), A2AError> {
+        self.agent_id_policy.check(&self.agent_id, &self.name)?;
         let params = RegisterParams {
             agent_id: self.agent_id.clone(),
             name: self.name.clone(),
             capabilities: self.capabilities.clone(),
             endpoint: endpoint.to_string(),
+            version: self.version.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
         };
 
-        let result = self
-            .request(&format!("{}/a2a/register", directory_url.trim_end_matches('/')), "a2a/register", Some(params))
-            .await?;
+        let params = serde_json::to_value(params)?;
+        self.on_directories(directory_url, |directory| {
+            let params = params.clone();
+            async move {
+                self.request(&self.directory_path(&directory, "register"), &self.method("register"), Some(params))
+                    .await
+            }
+        })
+        .await?;
+        Ok(())
+    }
 
-        println!("✅ Registered: {}", self.agent_id);
-        Ok(result)
+    /// Offer `capability` from now on, both locally and in the directory,
+    /// replacing any capability of the same name and version. Only the capability set
+    /// is sent, via `a2a/update`, so concurrent changes to other fields
+    /// are not overwritten as a full re-registration would.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id)))]
+    pub async fn add_capability(&mut self, capability: impl Into<Capability>, directory_url: &str) -> Result<(), A2AError> {
+        let capability = capability.into();
+        self.send_update(vec![capability.clone()], vec![], directory_url).await?;
+
+        info!(agent_id = %self.agent_id, capability = %capability.name, "capability added");
+        self.capabilities.retain(|existing| !capability.replaces(existing));
+        self.capabilities.push(capability);
+        Ok(())
+    }
+
+    /// Stop offering the capability `name`, every version of it, like
+    /// [`add_capability`](Self::add_capability). Removing a capability the
+    /// agent does not have is not an error.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id)))]
+    pub async fn remove_capability(&mut self, name: &str, directory_url: &str) -> Result<(), A2AError> {
+        self.send_update(vec![], vec![name.to_string()], directory_url).await?;
+
+        info!(agent_id = %self.agent_id, capability = name, "capability removed");
+        self.capabilities.retain(|existing| existing.name != name);
+        Ok(())
+    }
+
+    async fn send_update(&self, add: Vec<Capability>, remove: Vec<String>, directory_url: &str) -> Result<(), A2AError> {
+        let params = UpdateParams {
+            agent_id: self.agent_id.clone(),
+            add,
+            remove,
+        };
+
+        let params = serde_json::to_value(params)?;
+        self.on_directories(directory_url, |directory| {
+            let params = params.clone();
+            async move {
+                self.request(&self.directory_path(&directory, "update"), &self.method("update"), Some(params))
+                    .await
+            }
+        })
+        .await?;
+        Ok(())
     }
 
+    /// Remove this agent from the directory and clear its endpoint.
+    ///
+    /// Call this from a shutdown hook (e.g. after `tokio::signal::ctrl_c`)
+    /// so discovery stops handing out a dead endpoint. Deregistering an
+    /// agent the directory no longer knows is not an error.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id)))]
+    pub async fn deregister(&mut self, directory_url: &str) -> Result<(), A2AError> {
+        match self.send_deregistration(directory_url).await {
+            Ok(_) => {}
+            Err(A2AError::HttpStatus { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {}
+            Err(e) => return Err(e),
+        }
+
+        self.endpoint = None;
+        info!(agent_id = %self.agent_id, "deregistered from directory");
+        Ok(())
+    }
+
+    async fn send_deregistration(&self, directory_url: &str) -> Result<Value, A2AError> {
+        let params = DeregisterParams {
+            agent_id: self.agent_id.clone(),
+        };
+        let params = serde_json::to_value(params)?;
+
+        self.on_directories(directory_url, |directory| {
+            let params = params.clone();
+            async move {
+                self.request(&self.directory_path(&directory, "deregister"), &self.method("deregister"), Some(params))
+                    .await
+            }
+        })
+        .await
+    }
+
+    /// Check that the agent at `endpoint` answers `GET /health`, returning
+    /// the round-trip time.
+    pub async fn ping(&self, endpoint: &str) -> Result<Duration, A2AError> {
+        let url = format!("{}/health", endpoint.trim_end_matches('/'));
+        let started = time::Instant::now();
+
+        transport::check_status(self.http.send(self.http.client.get(&url), "health").await?).await?;
+
+        Ok(started.elapsed())
+    }
+
+    /// Send `method` to `endpoint` as a JSON-RPC notification, without an
+    /// `id`, for calls that need no answer such as telemetry pings or
+    /// cache invalidations. The peer handles it but sends no response,
+    /// so its outcome, success or error, is never known here; only a
+    /// failure to deliver it is.
+    ///
+    /// `method` is sent as given, without the agent's method namespace,
+    /// and a failed delivery is not retried.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgent;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// agent.notify("http://localhost:9001", "cache/invalidate", json!({"key": "rates"})).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn notify(&self, endpoint: &str, method: &str, params: Value) -> Result<(), A2AError> {
+        self.transport().notify(endpoint, method, Some(params)).await
+    }
+
+    /// Ask the agent at `endpoint` to describe itself via `GET /a2a/card`,
+    /// without going through a directory. The card lists its capabilities
+    /// with their input and output schemas.
+    pub async fn fetch_card(&self, endpoint: &str) -> Result<AgentInfo, A2AError> {
+        let url = format!("{}/a2a/card", endpoint.trim_end_matches('/'));
+        let response = self.http.send(self.http.client.get(&url), "a2a/card").await?;
+        let response = transport::check_status(response).await?;
+        self.http.read_json(response).await
+    }
+
+    /// Return the first agent offering all of `wanted_capabilities`.
     pub async fn discover(
         &self,
         wanted_capabilities: Vec<String>,
         directory_url: &str,
-    ) -> Result<Option<AgentInfo>, String> {
-        let params = DiscoverParams {
-            capabilities: wanted_capabilities,
+    ) -> Result<Option<AgentInfo>, A2AError> {
+        let agents = self.discover_all(wanted_capabilities, directory_url).await?;
+        Ok(agents.into_iter().next())
+    }
+
+    /// Return every agent offering all of `wanted_capabilities`, in the
+    /// order reported by the directory.
+    pub async fn discover_all(
+        &self,
+        wanted_capabilities: Vec<String>,
+        directory_url: &str,
+    ) -> Result<Vec<AgentInfo>, A2AError> {
+        self.discover_matching(CapabilityQuery::All(wanted_capabilities), directory_url)
+            .await
+    }
+
+    /// Return every agent satisfying `query`, in the order reported by
+    /// the directory.
+    pub async fn discover_matching(
+        &self,
+        query: CapabilityQuery,
+        directory_url: &str,
+    ) -> Result<Vec<AgentInfo>, A2AError> {
+        self.discover_filtered(query, HashMap::new(), directory_url).await
+    }
+
+    /// Like [`discover_matching`](Self::discover_matching), but return only
+    /// agents carrying every one of `tags`, e.g. `region = eu`. An empty
+    /// `tags` filters nothing.
+    ///
+    /// The directory is asked to filter, and the result is checked again
+    /// here for directories that ignore the `tags` param.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id)))]
+    pub async fn discover_filtered(
+        &self,
+        query: CapabilityQuery,
+        tags: HashMap<String, String>,
+        directory_url: &str,
+    ) -> Result<Vec<AgentInfo>, A2AError> {
+        let cache_key = DiscoveryCache::key(directory_url, &query, &tags);
+        if let Some(agents) = self.discovery_cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+            debug!(matches = agents.len(), "discovery served from cache");
+            return Ok(agents);
+        }
+
+        let params = match query {
+            CapabilityQuery::All(capabilities) => DiscoverParams {
+                capabilities: Some(capabilities),
+                query: None,
+                tags: tags.clone(),
+                limit: None,
+                cursor: None,
+            },
+            query => DiscoverParams {
+                capabilities: None,
+                query: Some(query),
+                tags: tags.clone(),
+                limit: None,
+                cursor: None,
+            },
         };
+        let params = serde_json::to_value(params)?;
 
         let result = self
-            .request(&format!("{}/a2a/discover", directory_url.trim_end_matches('/')), "a2a/discover", Some(params))
+            .on_directories(directory_url, |directory| {
+                let params = params.clone();
+                async move {
+                    self.request(&self.directory_path(&directory, "discover"), &self.method("discover"), Some(params))
+                        .await
+                }
+            })
             .await?;
 
-        let agents: Vec<AgentInfo> = serde_json::from_value(
+        let mut agents: Vec<AgentInfo> = serde_json::from_value(
             result.get("agents").cloned().unwrap_or(json!([]))
-        ).map_err(|e| e.to_string())?;
+        )?;
+        agents.retain(|agent| agent.matches_tags(&tags));
+        debug!(matches = agents.len(), "discovery finished");
 
-        Ok(agents.into_iter().next())
+        if let Some(cache) = &self.discovery_cache {
+            cache.insert(cache_key, agents.clone());
+        }
+        Ok(agents)
     }
 
     pub async fn send_task(
@@ -165,109 +1315,480 @@ impl A2AAgent {
         action: &str,
         input: Value,
         directory_url: &str,
-    ) -> Result<TaskResult, String> {
-        // Get target agent info
-        let agent_url = format!("{}/a2a/agents/{}", directory_url.trim_end_matches('/'), target_agent_id);
-        let response = self.client.get(&agent_url).send().await.map_err(|e| e.to_string())?;
-        
-        if !response.status().is_success() {
-            return Err(format!("Agent not found: {}", target_agent_id));
+    ) -> Result<TaskResult, A2AError> {
+        self.dispatch_task(target_agent_id, action, input, &[], directory_url, &self.method("task"))
+            .await
+    }
+
+    /// Like [`send_task`](Self::send_task), but deserialize the task's
+    /// `output` into `O`. A missing output is read as `null`, so `O` may be
+    /// an `Option`; an output that does not fit `O` is an [`A2AError::Serde`].
+    ///
+    /// ```rust,no_run
+    /// # use a2a::A2AAgent;
+    /// # use serde_json::json;
+    /// #[derive(serde::Deserialize)]
+    /// struct AddResult {
+    ///     result: i64,
+    /// }
+    ///
+    /// # async fn example(agent: A2AAgent) -> Result<(), a2a::A2AError> {
+    /// let sum: AddResult = agent
+    ///     .send_task_typed("calculator", "add", json!({"a": 10, "b": 20}), "http://localhost:8080")
+    ///     .await?;
+    /// assert_eq!(sum.result, 30);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_task_typed<O: DeserializeOwned>(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<O, A2AError> {
+        let result = self.send_task(target_agent_id, action, input, directory_url).await?;
+        Ok(serde_json::from_value(result.output.unwrap_or(Value::Null))?)
+    }
+
+    /// Like [`send_task`](Self::send_task), but serialize `input` from any
+    /// `Serialize` type. Input that cannot be represented as JSON, such as
+    /// a map with non-string keys, is an [`A2AError::Serde`].
+    pub async fn send_task_with<I: Serialize + ?Sized>(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: &I,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let input = serde_json::to_value(input)?;
+        self.send_task(target_agent_id, action, input, directory_url).await
+    }
+
+    /// Hand a task to the target and return as soon as it is accepted,
+    /// without waiting for the output. The returned result carries the
+    /// `task_id` to pass to [`get_task_status`](Self::get_task_status) or
+    /// [`wait_for_task`](Self::wait_for_task).
+    ///
+    /// Resolves only once the target acknowledged the task, answering with
+    /// status [`TaskStatus::Accepted`] and the `task_id` that was sent; any
+    /// other answer is an [`A2AError::Protocol`]. With an
+    /// [`Outbox`](Self::with_outbox) the task is saved before it is sent
+    /// and dropped once acknowledged or refused.
+    pub async fn submit_task(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let task = PendingTask::new(target_agent_id, action, input, directory_url);
+        self.measured(action, self.submit(task)).await
+    }
+
+    /// Open a persistent WebSocket session with an agent's `ws://` or
+    /// `wss://` endpoint, such as `ws://localhost:9001/ws`. The session
+    /// authenticates like this agent and reconnects when the connection
+    /// drops.
+    #[cfg(feature = "ws")]
+    pub async fn connect_ws(&self, endpoint: &str) -> Result<WsSession, A2AError> {
+        WsSession::connect(self.clone(), endpoint).await
+    }
+
+    /// Send a task over `a2a/task/stream` and yield its output as the
+    /// target produces it. The stream ends after the target's final `done`
+    /// event; an error from the handler is yielded once and ends it too.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id, target = %target_agent_id, action = %action, task_id = tracing::field::Empty)))]
+    pub async fn send_task_stream(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskStream, A2AError> {
+        let agent_info = self.resolve_agent(target_agent_id, directory_url).await?;
+
+        self.check_task(&agent_info, action, None, &input)?;
+        self.throttle(target_agent_id).await?;
+
+        let params = self.task_params(action, input);
+        record!("task_id", params.task_id.as_str());
+        let method = self.method("task/stream");
+        let request = JSONRPCRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Value::String(uuid::Uuid::new_v4().to_string()),
+            method: method.clone(),
+            params: Some(serde_json::to_value(params)?),
+        };
+
+        let response = self
+            .http
+            .send(
+                self.http
+                    .client
+                    .post(&agent_info.endpoint)
+                    .header(reqwest::header::ACCEPT, "text/event-stream")
+                    .json(&request),
+                &method,
+            )
+            .await?;
+        let response = transport::check_status(response).await?;
+
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+        if !is_event_stream {
+            // The target rejected the call before streaming began.
+            let rpc_response: JSONRPCResponse = self.http.read_json(response).await?;
+            return Err(match rpc_response.into_result() {
+                Err(e) => e,
+                Ok(_) => A2AError::Protocol(format!("{} was answered with a result instead of an event stream", method)),
+            });
         }
 
-        let agent_info: AgentInfo = response.json().await.map_err(|e| e.to_string())?;
+        Ok(stream::task_stream(response))
+    }
 
-        // Send task
-        let params = TaskParams {
+    /// Ask the target for the current status of a submitted task.
+    pub async fn get_task_status(
+        &self,
+        agent_id: &str,
+        task_id: &str,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let agent_info = self.resolve_agent(agent_id, directory_url).await?;
+        self.poll_task(&agent_info, task_id).await
+    }
+
+    /// Ask the target to cancel a task that is still running. Cancellation
+    /// is cooperative: the handler is signalled but may run to completion,
+    /// so the answer only confirms the request. Poll
+    /// [`get_task_status`](Self::get_task_status) for the outcome, which is
+    /// [`TaskStatus::Cancelled`] once the handler returns. A task that
+    /// already finished is reported unchanged.
+    pub async fn cancel_task(
+        &self,
+        agent_id: &str,
+        task_id: &str,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let agent_info = self.resolve_agent(agent_id, directory_url).await?;
+        let params = TaskStatusParams {
+            task_id: task_id.to_string(),
+            sender: self.agent_id.clone(),
+        };
+        let params = serde_json::to_value(params)?;
+
+        let result = self.call_peer(&agent_info, &self.method("task/cancel"), params, &[]).await?;
+        self.task_result(agent_id, result)
+    }
+
+    /// Poll a submitted task every `interval` until it reaches a terminal
+    /// status, giving up with [`A2AError::TaskWaitTimeout`] after `deadline`.
+    pub async fn wait_for_task(
+        &self,
+        agent_id: &str,
+        task_id: &str,
+        directory_url: &str,
+        interval: Duration,
+        deadline: Duration,
+    ) -> Result<TaskResult, A2AError> {
+        let agent_info = self.resolve_agent(agent_id, directory_url).await?;
+
+        let poll = async {
+            loop {
+                let result = self.poll_task(&agent_info, task_id).await?;
+                if result.status.is_terminal() {
+                    return Ok(result);
+                }
+                time::sleep(interval).await;
+            }
+        };
+
+        time::timeout(deadline, poll)
+            .await
+            .unwrap_or_else(|| Err(A2AError::TaskWaitTimeout(task_id.to_string())))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(agent_id = %self.agent_id, target = %target_agent_id, action = %action, task_id = tracing::field::Empty)))]
+    async fn dispatch_task(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        attachments: &[Attachment],
+        directory_url: &str,
+        method: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let task = async {
+            let agent_info = self.resolve_agent(target_agent_id, directory_url).await?;
+            self.send_to(&agent_info, action, None, input, attachments, method).await
+        };
+        self.measured(action, task).await
+    }
+
+    /// Run the task call `task`, recording its outcome in the client task
+    /// metrics.
+    async fn measured<F>(&self, action: &str, task: F) -> Result<TaskResult, A2AError>
+    where
+        F: std::future::Future<Output = Result<TaskResult, A2AError>>,
+    {
+        let started = time::Instant::now();
+        let result = task.await;
+
+        let status = match &result {
+            Ok(task_result) => task_result.status.as_str(),
+            Err(_) => "error",
+        };
+        metrics::task_finished(metrics::CLIENT, action, status, started.elapsed());
+        result
+    }
+
+    /// Check and send a task to the already resolved `agent_info`, for
+    /// `version` of `action` if the caller chose one.
+    async fn send_to(
+        &self,
+        agent_info: &AgentInfo,
+        action: &str,
+        version: Option<Version>,
+        input: Value,
+        attachments: &[Attachment],
+        method: &str,
+    ) -> Result<TaskResult, A2AError> {
+        self.check_task(agent_info, action, version.as_ref(), &input)?;
+
+        let mut params = self.task_params(action, input);
+        params.version = version;
+        self.send_params(agent_info, params, attachments, method).await
+    }
+
+    /// The params of a task sent now.
+    pub(crate) fn task_params(&self, action: &str, input: Value) -> TaskParams {
+        TaskParams {
             task_id: uuid::Uuid::new_v4().to_string(),
             action: action.to_string(),
             sender: self.agent_id.clone(),
             input,
-        };
+            correlation_id: Some(correlation::current_or_new()),
+            deadline_ms: deadline::budget_ms(),
+            version: None,
+            priority: priority::current(),
+        }
+    }
 
-        let result = self
-            .request(&agent_info.endpoint, "a2a/task", Some(params))
-            .await?;
+    /// Send the already checked task `params` to `agent_info`.
+    async fn send_params(
+        &self,
+        agent_info: &AgentInfo,
+        params: TaskParams,
+        attachments: &[Attachment],
+        method: &str,
+    ) -> Result<TaskResult, A2AError> {
+        record!("task_id", params.task_id.as_str());
+        if self.dry_run {
+            debug!(endpoint = %agent_info.endpoint, method, "dry run, not sending task");
+            return Ok(dry_run::result(params));
+        }
 
-        let task_result: TaskResult = serde_json::from_value(result).map_err(|e| e.to_string())?;
-        Ok(task_result)
+        // Send task
+        self.throttle(&agent_info.agent_id).await?;
+        debug!(endpoint = %agent_info.endpoint, method, "sending task");
+        #[cfg(not(target_arch = "wasm32"))]
+        let guard = (self.cancel_on_drop && method != self.method("task/submit"))
+            .then(|| abandon::CancelOnDrop::new(self, agent_info, &params.task_id));
+        let params = serde_json::to_value(params)?;
+
+        let result = deadline::bounded(self.call_peer(agent_info, method, params, attachments)).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(guard) = guard {
+            guard.disarm();
+        }
+
+        self.task_result(&agent_info.agent_id, result?)
     }
 
-    async fn request(&self, url: &str, method: &str, params: Option<Value>) -> Result<Value, String> {
-        let request = JSONRPCRequest {
-            jsonrpc: "2.0".to_string(),
-            id: uuid::Uuid::new_v4().to_string(),
-            method: method.to_string(),
-            params,
+    async fn poll_task(&self, agent_info: &AgentInfo, task_id: &str) -> Result<TaskResult, A2AError> {
+        let params = TaskStatusParams {
+            task_id: task_id.to_string(),
+            sender: self.agent_id.clone(),
         };
+        let params = serde_json::to_value(params)?;
 
-        let response = self.client
-            .post(url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        let result = self.call_peer(agent_info, &self.method("task/status"), params, &[]).await?;
+        self.task_result(&agent_info.agent_id, result)
+    }
 
-        if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()));
+    /// Read a task result from `agent_id`, checking its signature if a
+    /// key is configured for that agent.
+    pub(crate) fn task_result(&self, agent_id: &str, result: Value) -> Result<TaskResult, A2AError> {
+        let result: TaskResult = serde_json::from_value(result)?;
+        #[cfg(feature = "signing")]
+        if let Some(key) = self.verifying_keys.get(agent_id) {
+            key.verify(agent_id, &result)?;
         }
+        #[cfg(not(feature = "signing"))]
+        let _ = agent_id;
+        Ok(result)
+    }
 
-        let rpc_response: JSONRPCResponse = response.json().await.map_err(|e| e.to_string())?;
+    async fn resolve_agent(&self, agent_id: &str, directory_url: &str) -> Result<AgentInfo, A2AError> {
+        self.on_directories(directory_url, |directory| async move {
+            let agent_url = self.directory_path(&directory, &format!("agents/{}", agent_id));
+            match self.transport().get(&agent_url).await? {
+                Some(agent_info) => Ok(serde_json::from_value(agent_info)?),
+                None => Err(A2AError::AgentNotFound(agent_id.to_string())),
+            }
+        })
+        .await
+    }
 
-        if let Some(error) = rpc_response.error {
-            return Err(format!("RPC error {}: {}", error.code, error.message));
+    /// The opt-in local checks on a task before it is sent to `agent_info`.
+    fn check_task(&self, agent_info: &AgentInfo, action: &str, version: Option<&Version>, input: &Value) -> Result<(), A2AError> {
+        if self.check_actions {
+            validation::check_action(agent_info, action)?;
+        }
+        if self.validate_input {
+            validation::validate_input(agent_info, action, version, input)?;
         }
+        Ok(())
+    }
 
-        rpc_response.result.ok_or_else(|| "No result".to_string())
+    /// The URL of the directory endpoint `path`, e.g. `register`.
+    fn directory_path(&self, directory: &str, path: &str) -> String {
+        format!("{}{}/{}", directory, self.path_prefix, path)
     }
-}
 
-// ============ A2AServer ============
+    /// The full JSON-RPC method name for `name`, e.g. `task/submit`.
+    pub(crate) fn method(&self, name: &str) -> String {
+        format!("{}/{}", self.method_namespace, name)
+    }
 
-pub type TaskHandler = Box<dyn Fn(String, Value, String) -> Value + Send + Sync>;
+    /// Call `method` on `agent_info`'s endpoint through its circuit breaker,
+    /// if one is configured.
+    async fn call_peer(
+        &self,
+        agent_info: &AgentInfo,
+        method: &str,
+        params: Value,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        let Some(breakers) = &self.circuit_breakers else {
+            return self.request_peer(agent_info, method, params, attachments).await;
+        };
+        breakers.admit(&agent_info.agent_id)?;
+        let result = self.request_peer(agent_info, method, params, attachments).await;
+        breakers.record(&agent_info.agent_id, &result);
+        result
+    }
 
-pub struct A2AServer {
-    agent_id: String,
-    name: String,
-    capabilities: Vec<String>,
-    port: u16,
-    task_handler: Option<TaskHandler>,
-}
+    /// Make the call `method` on `agent_info`'s endpoint, with
+    /// `attachments` if there are any.
+    async fn request_peer(
+        &self,
+        agent_info: &AgentInfo,
+        method: &str,
+        params: Value,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        let url = agent_info.endpoint.as_str();
+        if attachments.is_empty() {
+            return self.request(url, method, Some(params)).await;
+        }
+        self.with_retries(url, method, || {
+            self.transport().call_with_attachments(url, method, Some(params.clone()), attachments)
+        })
+        .await
+    }
 
-impl A2AServer {
-    pub fn new(agent_id: &str, name: &str, capabilities: Vec<String>, port: u16) -> Self {
-        Self {
-            agent_id: agent_id.to_string(),
-            name: name.to_string(),
-            capabilities,
-            port,
-            task_handler: None,
+    /// Wait for, or fail without, a rate limit token for `target_agent_id`.
+    async fn throttle(&self, target_agent_id: &str) -> Result<(), A2AError> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.acquire(target_agent_id).await,
+            None => Ok(()),
         }
     }
 
-    pub fn handle_task<F>(&mut self, handler: F)
+    /// Run `op` against `directory_url`, then against each fallback
+    /// directory while the previous one is unreachable or failing with a
+    /// 5xx. `op` receives the directory URL without a trailing slash.
+    async fn on_directories<T, F, Fut>(&self, directory_url: &str, op: F) -> Result<T, A2AError>
     where
-        F: Fn(String, Value, String) -> Value + Send + Sync + 'static,
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T, A2AError>>,
     {
-        self.task_handler = Some(Box::new(handler));
+        let mut directories: Vec<&str> = Vec::new();
+        for directory in std::iter::once(directory_url).chain(self.fallback_directories.iter().map(String::as_str)) {
+            let directory = directory.trim_end_matches('/');
+            if !directories.contains(&directory) {
+                directories.push(directory);
+            }
+        }
+
+        let mut failures = Vec::new();
+        for directory in directories {
+            match op(directory.to_string()).await {
+                Err(e) if retry::is_retryable(&e) => {
+                    warn!(directory, error = %e, "directory unavailable");
+                    failures.push((directory.to_string(), e));
+                }
+                result => return result,
+            }
+        }
+
+        if failures.len() == 1 {
+            return Err(failures.remove(0).1);
+        }
+        Err(A2AError::AllDirectoriesFailed(failures))
     }
 
-    pub async fn run(&self) -> Result<(), String> {
-        let addr = format!("0.0.0.0:{}", self.port);
-        println!("🤖 Agent '{}' running on port {}", self.agent_id, self.port);
-        
-        // Simple HTTP server using axum would be better for production
-        // This is a placeholder - use with actix-web or axum for real implementation
-        Ok(())
+    async fn request(&self, url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+        self.with_retries(url, method, || self.transport().call(url, method, params.clone()))
+            .await
+    }
+
+    fn transport(&self) -> &dyn Transport {
+        self.transport.as_deref().unwrap_or(&self.http)
+    }
+
+    /// Run `op` under the agent's retry policy.
+    async fn with_retries<T, F, Fut>(&self, url: &str, method: &str, op: F) -> Result<T, A2AError>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, A2AError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(e) if attempt < self.retry.max_retries && retry::is_retryable(&e) => {
+                    let Some(delay) = self.retry.delay(&e, attempt) else {
+                        return Err(e);
+                    };
+                    debug!(url, method, attempt, delay_ms = delay.as_millis() as u64, error = %e, "retrying request");
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 }
 
-// ============ Convenience ============
+impl Default for A2AAgent {
+    fn default() -> Self {
+        let agent_id = format!("agent-{}", uuid::Uuid::new_v4());
+        Self::new(&agent_id, &agent_id, vec![])
+    }
+}
 
-pub async fn run_server<F>(agent_id: &str, name: &str, capabilities: Vec<String>, port: u16, handler: F) -> Result<(), String>
-where
-    F: Fn(String, Value, String) -> Value + Send + Sync + 'static,
-{
-    let mut server = A2AServer::new(agent_id, name, capabilities, port);
-    server.handle_task(handler);
-    server.run().await
+/// `prefix` with a leading slash and no trailing one; empty stays empty,
+/// for a directory serving its endpoints at the root.
+pub(crate) fn normalize_path_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        return String::new();
+    }
+    format!("/{}", prefix)
 }
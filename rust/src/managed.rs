@@ -0,0 +1,209 @@
+//! An agent that re-registers itself when its directory forgets it.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::{retry, time, A2AAgent, A2AError, AgentInfo, CapabilityQuery, RetryPolicy, TaskResult};
+
+/// Wraps an [`A2AAgent`] registered at one endpoint with one directory,
+/// and restores that registration when the directory loses it, e.g.
+/// after a restart with an in-memory store.
+///
+/// The task and discovery methods match the agent's, without the
+/// `directory_url` argument. When one fails in a way a restarted directory
+/// would cause, the agent checks whether the directory still knows it:
+///
+/// - [`A2AError::AgentNotFound`], since a target that registered with
+///   the old directory is missing from the new one;
+/// - errors the agent would retry, i.e. connection failures and HTTP 5xx,
+///   and [`A2AError::AllDirectoriesFailed`], while the directory is down.
+///
+/// If the directory answers that this agent is unknown, it re-registers,
+/// backing off between attempts under [`with_recovery_policy`](Self::with_recovery_policy),
+/// and retries the call once. If the directory still lists the agent, or
+/// stays unreachable through every attempt, the original error is
+/// returned. Other errors, such as a task rejected by its handler, are
+/// returned as they are.
+///
+/// ```rust,no_run
+/// use a2a::{A2AAgent, ManagedAgent};
+/// use serde_json::json;
+///
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// let agent = A2AAgent::new("planner", "Planner", vec!["plan".to_string()]);
+/// let mut managed = ManagedAgent::new(agent, "http://localhost:9002", "http://localhost:8080");
+/// managed.register().await?;
+///
+/// // Survives the directory restarting between calls.
+/// let result = managed.send_task("calculator", "add", json!({"a": 1, "b": 2})).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ManagedAgent {
+    agent: A2AAgent,
+    endpoint: String,
+    directory_url: String,
+    recovery: RetryPolicy,
+    /// Held while re-registering, so concurrent failures recover once.
+    recovering: Mutex<()>,
+}
+
+impl ManagedAgent {
+    /// Manage `agent`, which serves at `endpoint`, against `directory_url`.
+    /// Call [`register`](Self::register) before the first task.
+    pub fn new(agent: A2AAgent, endpoint: &str, directory_url: &str) -> Self {
+        Self {
+            agent,
+            endpoint: endpoint.to_string(),
+            directory_url: directory_url.to_string(),
+            recovery: RetryPolicy::new(5, Duration::from_millis(500)),
+            recovering: Mutex::new(()),
+        }
+    }
+
+    /// How often, and how patiently, to try re-registering once the
+    /// directory has lost this agent (default 5 retries from 500 ms).
+    pub fn with_recovery_policy(mut self, recovery: RetryPolicy) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// The wrapped agent.
+    pub fn agent(&self) -> &A2AAgent {
+        &self.agent
+    }
+
+    /// Unwrap the agent, which stays registered.
+    pub fn into_agent(self) -> A2AAgent {
+        self.agent
+    }
+
+    pub async fn register(&mut self) -> Result<(), A2AError> {
+        self.agent.register(&self.endpoint, &self.directory_url).await
+    }
+
+    pub async fn deregister(&mut self) -> Result<(), A2AError> {
+        self.agent.deregister(&self.directory_url).await
+    }
+
+    /// Re-register if the directory no longer knows this agent, returning
+    /// whether it had to. Useful on a timer, so a forgotten agent becomes
+    /// discoverable again before its next own call.
+    pub async fn ensure_registered(&self) -> Result<bool, A2AError> {
+        let _recovering = self.recovering.lock().await;
+        match self.agent.resolve_agent(&self.agent.agent_id, &self.directory_url).await {
+            Ok(_) => Ok(false),
+            Err(A2AError::AgentNotFound(_)) => {
+                warn!(agent_id = %self.agent.agent_id, "directory lost this agent, re-registering");
+                self.agent.send_registration(&self.endpoint, &self.directory_url).await?;
+                self.agent.clear_discovery_cache();
+                info!(agent_id = %self.agent.agent_id, "re-registered with directory");
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn discover(&self, wanted_capabilities: Vec<String>) -> Result<Option<AgentInfo>, A2AError> {
+        self.healing(|| self.agent.discover(wanted_capabilities.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn discover_all(&self, wanted_capabilities: Vec<String>) -> Result<Vec<AgentInfo>, A2AError> {
+        self.healing(|| self.agent.discover_all(wanted_capabilities.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn discover_matching(&self, query: CapabilityQuery) -> Result<Vec<AgentInfo>, A2AError> {
+        self.healing(|| self.agent.discover_matching(query.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn send_task(&self, target_agent_id: &str, action: &str, input: Value) -> Result<TaskResult, A2AError> {
+        self.healing(|| self.agent.send_task(target_agent_id, action, input.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn send_task_typed<O: DeserializeOwned>(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+    ) -> Result<O, A2AError> {
+        self.healing(|| self.agent.send_task_typed(target_agent_id, action, input.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn submit_task(&self, target_agent_id: &str, action: &str, input: Value) -> Result<TaskResult, A2AError> {
+        self.healing(|| self.agent.submit_task(target_agent_id, action, input.clone(), &self.directory_url))
+            .await
+    }
+
+    pub async fn get_task_status(&self, agent_id: &str, task_id: &str) -> Result<TaskResult, A2AError> {
+        self.healing(|| self.agent.get_task_status(agent_id, task_id, &self.directory_url))
+            .await
+    }
+
+    pub async fn cancel_task(&self, agent_id: &str, task_id: &str) -> Result<TaskResult, A2AError> {
+        self.healing(|| self.agent.cancel_task(agent_id, task_id, &self.directory_url))
+            .await
+    }
+
+    pub async fn wait_for_task(
+        &self,
+        agent_id: &str,
+        task_id: &str,
+        interval: Duration,
+        deadline: Duration,
+    ) -> Result<TaskResult, A2AError> {
+        self.healing(|| self.agent.wait_for_task(agent_id, task_id, &self.directory_url, interval, deadline))
+            .await
+    }
+
+    /// Run `op`, and once more after re-registering if it failed because
+    /// the directory may have lost this agent.
+    async fn healing<T, F, Fut>(&self, op: F) -> Result<T, A2AError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, A2AError>>,
+    {
+        match op().await {
+            Err(e) if may_have_lost_registration(&e) => {
+                debug!(agent_id = %self.agent.agent_id, error = %e, "checking registration after failure");
+                if self.recover().await {
+                    op().await
+                } else {
+                    Err(e)
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Re-register if needed, retrying while the directory is unreachable.
+    /// Returns whether this agent had to re-register.
+    async fn recover(&self) -> bool {
+        let mut attempt = 0;
+        loop {
+            match self.ensure_registered().await {
+                Ok(reregistered) => return reregistered,
+                Err(e) if attempt < self.recovery.max_retries => {
+                    debug!(agent_id = %self.agent.agent_id, attempt, error = %e, "directory not ready, backing off");
+                    time::sleep(self.recovery.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    warn!(agent_id = %self.agent.agent_id, error = %e, "could not restore registration");
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+fn may_have_lost_registration(error: &A2AError) -> bool {
+    matches!(error, A2AError::AgentNotFound(_) | A2AError::AllDirectoriesFailed(_)) || retry::is_retryable(error)
+}
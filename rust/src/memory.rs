@@ -0,0 +1,128 @@
+//! A [`Transport`] that keeps directory and peers in the same process.
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::directory::Registry;
+use crate::server::LocalServer;
+use crate::{A2AError, A2AServer, AgentInfo, Attachment, JSONRPCRequest, Transport, JSONRPC_VERSION};
+
+/// Routes calls to [`A2AServer`]s mounted in the same process, without
+/// sockets, and answers registration, update, deregistration and
+/// discovery calls from an in-memory directory.
+///
+/// Every directory URL reaches that one directory. Task calls go to the
+/// server mounted at the target's endpoint, which runs them exactly as it
+/// would over HTTP. Clones share the directory and the mounts, so give
+/// each agent in a test a clone of one transport:
+///
+/// ```
+/// use a2a::{A2AAgent, A2AError, A2AServer, InMemoryTransport};
+/// use serde_json::json;
+/// use std::sync::Arc;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let transport = InMemoryTransport::new();
+///
+/// let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+/// server.handle_task(|_ctx, input| input);
+/// transport.mount("mem://echo", &server);
+///
+/// let mut echo = A2AAgent::new("echo", "Echo", vec!["echo".to_string()])
+///     .with_transport(Arc::new(transport.clone()));
+/// echo.register("mem://echo", "mem://directory").await?;
+///
+/// let client = A2AAgent::new("client", "Client", vec![]).with_transport(Arc::new(transport));
+/// let result = client.send_task("echo", "echo", json!("hi"), "mem://directory").await?;
+/// assert_eq!(result.output, Some(json!("hi")));
+/// # Ok::<(), A2AError>(())
+/// # }).unwrap();
+/// ```
+///
+/// Streaming, batches and `ping` bypass the [`Transport`] and still need
+/// a listening server.
+#[derive(Clone, Default)]
+pub struct InMemoryTransport {
+    directory: Registry,
+    servers: Arc<Mutex<HashMap<String, LocalServer>>>,
+}
+
+impl InMemoryTransport {
+    /// An empty directory with nothing mounted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer calls to `endpoint` with `server`'s handlers, as registered
+    /// so far. Mounting another server on the same endpoint replaces it.
+    pub fn mount(&self, endpoint: impl Into<String>, server: &A2AServer) {
+        let endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self.servers.lock().unwrap().insert(endpoint, server.local());
+    }
+
+    /// Stop answering calls to `endpoint`, as if its server went down.
+    pub fn unmount(&self, endpoint: &str) {
+        self.servers.lock().unwrap().remove(endpoint.trim_end_matches('/'));
+    }
+
+    /// The agents currently registered, ordered by `agent_id`.
+    pub async fn agents(&self) -> Vec<AgentInfo> {
+        // The in-memory store cannot fail.
+        self.directory.agents().await.unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Transport for InMemoryTransport {
+    async fn call(&self, url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+        self.call_with_attachments(url, method, params, &[]).await
+    }
+
+    async fn call_with_attachments(
+        &self,
+        url: &str,
+        method: &str,
+        params: Option<Value>,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        let server = self.servers.lock().unwrap().get(url.trim_end_matches('/')).cloned();
+        let Some(server) = server else {
+            if let Some(call) = directory_call(url) {
+                return self.directory.handle_call(call, method, params.unwrap_or(json!({})), &HeaderMap::new()).await;
+            }
+            return Err(A2AError::Io(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                format!("nothing mounted at {}", url),
+            )));
+        };
+
+        let request = JSONRPCRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Value::String(uuid::Uuid::new_v4().to_string()),
+            method: method.to_string(),
+            params,
+        };
+        server.call(request, attachments.to_vec()).await.into_result()
+    }
+
+    async fn get(&self, url: &str) -> Result<Option<Value>, A2AError> {
+        let Some((_, agent_id)) = url.rsplit_once("/agents/") else {
+            return Ok(None);
+        };
+        match self.directory.get(agent_id).await? {
+            Some(agent) => Ok(Some(serde_json::to_value(agent)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The directory call a URL outside the mounted servers names by its last
+/// segment, as in `mem://directory/a2a/register`, whatever path prefix
+/// and method namespace the caller uses.
+fn directory_call(url: &str) -> Option<&str> {
+    let (_, call) = url.trim_end_matches('/').rsplit_once('/')?;
+    matches!(call, "register" | "update" | "deregister" | "discover").then_some(call)
+}
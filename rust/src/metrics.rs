@@ -0,0 +1,96 @@
+//! Prometheus metrics for tasks and requests, behind the `metrics`
+//! feature. Without it every recording call compiles to nothing.
+//!
+//! Both sides record through the [`metrics`](https://docs.rs/metrics)
+//! facade, told apart by a `side` label (`client` for [`A2AAgent`],
+//! `server` for [`A2AServer`]):
+//!
+//! - `a2a_tasks_total{side,action,status}` counts finished tasks;
+//! - `a2a_task_duration_seconds{side,action}` is a histogram of how long
+//!   they took;
+//! - `a2a_requests_total{side,method,outcome}` counts JSON-RPC and
+//!   directory calls, with `outcome` either `ok` or `error`.
+//!
+//! The first recording installs a `metrics-exporter-prometheus` recorder
+//! as the global one, unless the application installed its own first, in
+//! which case the metrics go there. [`render_metrics`] produces the
+//! Prometheus text format from the crate's recorder; the server serves it
+//! on `GET /metrics`.
+//!
+//! [`A2AAgent`]: crate::A2AAgent
+//! [`A2AServer`]: crate::A2AServer
+
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+#[cfg(feature = "metrics")]
+use std::sync::OnceLock;
+
+pub(crate) const CLIENT: &str = "client";
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const SERVER: &str = "server";
+
+#[cfg(feature = "metrics")]
+const TASKS: &str = "a2a_tasks_total";
+#[cfg(feature = "metrics")]
+const TASK_DURATION: &str = "a2a_task_duration_seconds";
+#[cfg(feature = "metrics")]
+const REQUESTS: &str = "a2a_requests_total";
+
+/// Upper bounds of the duration histogram buckets, in seconds.
+#[cfg(feature = "metrics")]
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// The crate's Prometheus recorder, installed as the global one on first
+/// use if no other is.
+#[cfg(feature = "metrics")]
+fn prometheus() -> &'static PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE.get_or_init(|| {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Full(TASK_DURATION.to_string()), &BUCKETS)
+            .expect("BUCKETS is not empty")
+            .build_recorder();
+        let handle = recorder.handle();
+        if ::metrics::set_global_recorder(recorder).is_err() {
+            debug!("a metrics recorder is already installed; recording there");
+        }
+        ::metrics::describe_counter!(TASKS, "Tasks finished, by outcome.");
+        ::metrics::describe_histogram!(TASK_DURATION, ::metrics::Unit::Seconds, "Time taken by finished tasks.");
+        ::metrics::describe_counter!(REQUESTS, "Requests made or served, by outcome.");
+        handle
+    })
+}
+
+/// Record a finished task.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn task_finished(side: &'static str, action: &str, status: &str, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        prometheus();
+        ::metrics::counter!(TASKS, "side" => side, "action" => action.to_string(), "status" => status.to_string())
+            .increment(1);
+        ::metrics::histogram!(TASK_DURATION, "side" => side, "action" => action.to_string()).record(elapsed);
+    }
+}
+
+/// Record a finished request.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn request_finished(side: &'static str, method: &str, ok: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        prometheus();
+        let outcome = if ok { "ok" } else { "error" };
+        ::metrics::counter!(REQUESTS, "side" => side, "method" => method.to_string(), "outcome" => outcome)
+            .increment(1);
+    }
+}
+
+/// Everything the crate's recorder holds, in the Prometheus text
+/// exposition format. Empty when the application installed a recorder of
+/// its own.
+#[cfg(feature = "metrics")]
+pub fn render_metrics() -> String {
+    prometheus().render()
+}
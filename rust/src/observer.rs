@@ -0,0 +1,103 @@
+//! Hooks into the lifecycle of the tasks an [`A2AServer`](crate::A2AServer)
+//! runs.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{TaskContext, TaskError, TaskResult, TaskStatus};
+
+/// Told about every task a server takes on, for metrics, audit logs or
+/// tracing that should not live in each handler. Register any number with
+/// [`A2AServer::with_observer`](crate::A2AServer::with_observer).
+///
+/// A task is received once its params parsed, the caller was authorized
+/// and its input passed the [`InputGuard`](crate::InputGuard); calls
+/// refused before that are not reported. Every received task then ends in
+/// exactly one `on_completed` or `on_failed`, `on_started` coming in
+/// between if the handler got to run. A task refused for lack of a slot
+/// or time fails without starting; a cancelled task completes with
+/// [`TaskStatus::Cancelled`]. A stream whose caller disconnects reports
+/// nothing after `on_started`.
+///
+/// Observers are awaited in registration order on the path of the task,
+/// so slow work belongs in a spawned task. All methods default to doing
+/// nothing.
+///
+/// ```
+/// use a2a::{A2AServer, TaskContext, TaskError, TaskObserver, TaskResult};
+/// use async_trait::async_trait;
+///
+/// /// Writes one audit line per finished task.
+/// struct Audit;
+///
+/// #[async_trait]
+/// impl TaskObserver for Audit {
+///     async fn on_received(&self, context: &TaskContext) {
+///         println!("{} asked for {} ({})", context.sender, context.action, context.task_id);
+///     }
+///
+///     async fn on_completed(&self, result: &TaskResult) {
+///         println!("{} {}", result.task_id, result.status.as_str());
+///     }
+///
+///     async fn on_failed(&self, task_id: &str, error: &TaskError) {
+///         println!("{} failed: {}", task_id, error.message);
+///     }
+/// }
+///
+/// let server = A2AServer::new("calc", "Calculator", vec![], 8080).with_observer(Audit);
+/// ```
+#[async_trait]
+pub trait TaskObserver: Send + Sync {
+    /// The task arrived; it may still wait for a slot.
+    async fn on_received(&self, _context: &TaskContext) {}
+
+    /// The handler is about to run.
+    async fn on_started(&self, _context: &TaskContext) {}
+
+    /// The task finished without error: completed, or cancelled.
+    async fn on_completed(&self, _result: &TaskResult) {}
+
+    /// The task failed, whether refused before starting or in the handler.
+    async fn on_failed(&self, _task_id: &str, _error: &TaskError) {}
+}
+
+/// The observers of a server, shared with its running tasks.
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Arc<Vec<Arc<dyn TaskObserver>>>);
+
+impl Observers {
+    pub(crate) fn new(observers: Vec<Arc<dyn TaskObserver>>) -> Self {
+        Self(Arc::new(observers))
+    }
+
+    pub(crate) async fn received(&self, context: &TaskContext) {
+        for observer in self.0.iter() {
+            observer.on_received(context).await;
+        }
+    }
+
+    pub(crate) async fn started(&self, context: &TaskContext) {
+        for observer in self.0.iter() {
+            observer.on_started(context).await;
+        }
+    }
+
+    pub(crate) async fn failed(&self, task_id: &str, error: &TaskError) {
+        for observer in self.0.iter() {
+            observer.on_failed(task_id, error).await;
+        }
+    }
+
+    /// Report how the task behind `result` ended.
+    pub(crate) async fn finished(&self, result: &TaskResult) {
+        match (result.status, &result.error) {
+            (TaskStatus::Failed, Some(error)) => self.failed(&result.task_id, error).await,
+            _ => {
+                for observer in self.0.iter() {
+                    observer.on_completed(result).await;
+                }
+            }
+        }
+    }
+}
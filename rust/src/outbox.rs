@@ -0,0 +1,209 @@
+//! Acknowledged submits, and keeping unacknowledged ones so they survive
+//! a crash.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{A2AAgent, A2AError, TaskResult, TaskStatus};
+
+/// A submitted task the target has not acknowledged yet, with everything
+/// needed to submit it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTask {
+    /// Kept when the task is resubmitted, so a target with
+    /// [`with_idempotency`](crate::A2AServer::with_idempotency) recognises
+    /// the copy.
+    pub task_id: String,
+    pub target_agent_id: String,
+    pub action: String,
+    pub input: Value,
+    pub directory_url: String,
+}
+
+impl PendingTask {
+    pub(crate) fn new(target_agent_id: &str, action: &str, input: Value, directory_url: &str) -> Self {
+        Self {
+            task_id: uuid::Uuid::new_v4().to_string(),
+            target_agent_id: target_agent_id.to_string(),
+            action: action.to_string(),
+            input,
+            directory_url: directory_url.to_string(),
+        }
+    }
+}
+
+/// Durable storage for submits awaiting acknowledgement, giving
+/// [`A2AAgent::submit_task`] at-least-once delivery.
+///
+/// Install one with [`A2AAgent::with_outbox`]. A task is saved once its
+/// target was found and its input checked, just before it is sent, and
+/// removed when the target acknowledges it or answers with a JSON-RPC
+/// error. When the outcome is unknown, e.g. because the connection
+/// dropped or the process died mid-call, it stays, and
+/// [`A2AAgent::resubmit_pending`] sends it again, typically at startup.
+///
+/// The catch of at-least-once is that a target which did take the task on
+/// before the acknowledgement got lost runs it twice. Resubmits keep the
+/// original `task_id`, so a target with
+/// [`with_idempotency`](crate::A2AServer::with_idempotency) drops copies
+/// arriving within its TTL; handlers of tasks resent later must tolerate
+/// running again.
+///
+/// [`FileOutbox`] keeps tasks as files. On `wasm32` the returned futures
+/// need not be `Send`; implement it with `#[async_trait(?Send)]` there.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Outbox: Send + Sync {
+    /// Store `task`, replacing any task with the same `task_id`.
+    async fn save(&self, task: &PendingTask) -> Result<(), A2AError>;
+
+    /// Forget `task_id`. Removing an unknown task is not an error.
+    async fn remove(&self, task_id: &str) -> Result<(), A2AError>;
+
+    /// Every stored task, in no particular order.
+    async fn pending(&self) -> Result<Vec<PendingTask>, A2AError>;
+}
+
+impl A2AAgent {
+    /// Submit every task still in the [`Outbox`](Self::with_outbox), as
+    /// [`submit_task`](Self::submit_task) would, and return each `task_id`
+    /// with its outcome. Tasks whose outcome is again unknown stay for the
+    /// next call. Without an outbox there is nothing to resubmit.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, FileOutbox};
+    /// use std::sync::Arc;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("billing", "Billing", vec![])
+    ///     .with_outbox(Arc::new(FileOutbox::new("/var/lib/billing/outbox")));
+    /// for (task_id, outcome) in agent.resubmit_pending().await? {
+    ///     println!("{}: {:?}", task_id, outcome.map(|result| result.status));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resubmit_pending(&self) -> Result<Vec<(String, Result<TaskResult, A2AError>)>, A2AError> {
+        let Some(outbox) = &self.outbox else {
+            return Ok(Vec::new());
+        };
+        let mut outcomes = Vec::new();
+        for task in outbox.pending().await? {
+            let task_id = task.task_id.clone();
+            let action = task.action.clone();
+            debug!(task_id = %task_id, "resubmitting task");
+            outcomes.push((task_id, self.measured(&action, self.submit(task)).await));
+        }
+        Ok(outcomes)
+    }
+
+    /// Submit `task` and wait for its acknowledgement, keeping it in the
+    /// outbox, if any, while the outcome is unknown.
+    pub(crate) async fn submit(&self, task: PendingTask) -> Result<TaskResult, A2AError> {
+        let agent_info = self.resolve_agent(&task.target_agent_id, &task.directory_url).await?;
+        self.check_task(&agent_info, &task.action, None, &task.input)?;
+
+        let mut params = self.task_params(&task.action, task.input.clone());
+        params.task_id = task.task_id.clone();
+        let outbox = self.outbox.as_ref().filter(|_| !self.dry_run);
+        if let Some(outbox) = outbox {
+            outbox.save(&task).await?;
+        }
+
+        let acknowledged = self
+            .send_params(&agent_info, params, &[], &self.method("task/submit"))
+            .await
+            .and_then(|result| acknowledgement(&task.task_id, result));
+        if let Some(outbox) = outbox {
+            if matches!(acknowledged, Ok(_) | Err(A2AError::Rpc { .. })) {
+                // The target has answered; failing now would only get the
+                // task sent twice.
+                if let Err(e) = outbox.remove(&task.task_id).await {
+                    warn!(task_id = %task.task_id, error = %e, "could not remove acknowledged task from outbox");
+                }
+            }
+        }
+        acknowledged
+    }
+}
+
+/// Check that `result` acknowledges the submit of `task_id`.
+fn acknowledgement(task_id: &str, result: TaskResult) -> Result<TaskResult, A2AError> {
+    if result.task_id != task_id {
+        return Err(A2AError::Protocol(format!(
+            "submit of task {} was answered for task {}",
+            task_id, result.task_id
+        )));
+    }
+    match result.status {
+        TaskStatus::Accepted | TaskStatus::DryRun => Ok(result),
+        status => Err(A2AError::Protocol(format!(
+            "submit of task {} was answered with status {} instead of accepted",
+            task_id,
+            status.as_str()
+        ))),
+    }
+}
+
+/// Keeps each pending task as a JSON file named after its `task_id` in one
+/// directory, created on first use. Files are written whole and renamed
+/// into place, so a crash never leaves a half-written task behind.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileOutbox {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileOutbox {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, task_id: &str, extension: &str) -> Result<std::path::PathBuf, A2AError> {
+        // Task IDs name files; keep them from reaching outside `dir`.
+        if task_id.is_empty() || !task_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(A2AError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("task ID {:?} cannot name an outbox file", task_id),
+            )));
+        }
+        Ok(self.dir.join(format!("{}.{}", task_id, extension)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl Outbox for FileOutbox {
+    async fn save(&self, task: &PendingTask) -> Result<(), A2AError> {
+        let staging = self.path(&task.task_id, "tmp")?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&staging, serde_json::to_vec(task)?).await?;
+        tokio::fs::rename(&staging, self.path(&task.task_id, "json")?).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, task_id: &str) -> Result<(), A2AError> {
+        match tokio::fs::remove_file(self.path(task_id, "json")?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn pending(&self) -> Result<Vec<PendingTask>, A2AError> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut tasks = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                tasks.push(serde_json::from_slice(&tokio::fs::read(&path).await?)?);
+            }
+        }
+        Ok(tasks)
+    }
+}
@@ -0,0 +1,154 @@
+//! Paged discovery, for directories too large to list in one response.
+
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+
+use crate::{A2AAgent, A2AError, AgentInfo, CapabilityQuery, DiscoverParams};
+
+/// Which page of discovery results to fetch.
+///
+/// ```
+/// use a2a::PageOptions;
+///
+/// let first = PageOptions::new(100);
+/// let next = PageOptions::new(100).after("cursor-from-previous-page");
+/// # let _ = (first, next);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageOptions {
+    /// Most agents to return; `None` leaves it to the directory.
+    pub limit: Option<usize>,
+    /// Where to resume, as returned in [`Page::next_cursor`]; `None`
+    /// starts from the beginning.
+    pub cursor: Option<String>,
+}
+
+impl PageOptions {
+    /// The first page of at most `limit` agents.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            cursor: None,
+        }
+    }
+
+    /// Resume after the page that returned `cursor`.
+    pub fn after(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+/// One page of results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass to [`PageOptions::after`] for the next page; `None` on the
+    /// last one.
+    pub next_cursor: Option<String>,
+}
+
+impl A2AAgent {
+    /// Fetch one page of the agents satisfying `query`, in `agent_id`
+    /// order.
+    ///
+    /// Cursors are opaque: only pass back what a page returned. A
+    /// directory without paging support returns every match on a single
+    /// page. Pages bypass the discovery cache.
+    pub async fn discover_page(
+        &self,
+        query: CapabilityQuery,
+        page: PageOptions,
+        directory_url: &str,
+    ) -> Result<Page<AgentInfo>, A2AError> {
+        let params = match query {
+            CapabilityQuery::All(capabilities) => DiscoverParams {
+                capabilities: Some(capabilities),
+                query: None,
+                tags: HashMap::new(),
+                limit: page.limit,
+                cursor: page.cursor,
+            },
+            query => DiscoverParams {
+                capabilities: None,
+                query: Some(query),
+                tags: HashMap::new(),
+                limit: page.limit,
+                cursor: page.cursor,
+            },
+        };
+        let params = serde_json::to_value(params)?;
+
+        let result = self
+            .on_directories(directory_url, |directory| {
+                let params = params.clone();
+                async move {
+                    self.request(&self.directory_path(&directory, "discover"), &self.method("discover"), Some(params))
+                        .await
+                }
+            })
+            .await?;
+
+        let items: Vec<AgentInfo> = serde_json::from_value(result.get("agents").cloned().unwrap_or(json!([])))?;
+        let next_cursor = result
+            .get("nextCursor")
+            .or_else(|| result.get("next_cursor"))
+            .and_then(|cursor| cursor.as_str())
+            .map(str::to_string);
+        debug!(matches = items.len(), more = next_cursor.is_some(), "discovery page fetched");
+        Ok(Page { items, next_cursor })
+    }
+
+    /// Every agent satisfying `query`, fetched `page_size` at a time as the
+    /// stream is read. A failed page ends the stream after its error.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, CapabilityQuery};
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let query = CapabilityQuery::All(vec!["search".to_string()]);
+    /// let agents: Vec<_> = agent.discover_stream(query, 500, "http://localhost:8080").try_collect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn discover_stream<'a>(
+        &'a self,
+        query: CapabilityQuery,
+        page_size: usize,
+        directory_url: &'a str,
+    ) -> impl Stream<Item = Result<AgentInfo, A2AError>> + 'a {
+        // (buffered agents, cursor for the next page, whether pages remain)
+        let state = (VecDeque::new(), None::<String>, true);
+        futures::stream::unfold(state, move |(mut buffered, mut cursor, mut more)| {
+            let query = query.clone();
+            async move {
+                loop {
+                    if let Some(agent) = buffered.pop_front() {
+                        return Some((Ok(agent), (buffered, cursor, more)));
+                    }
+                    if !more {
+                        return None;
+                    }
+
+                    let page = PageOptions {
+                        limit: Some(page_size),
+                        cursor: cursor.clone(),
+                    };
+                    match self.discover_page(query.clone(), page, directory_url).await {
+                        Ok(page) => {
+                            // A directory repeating a cursor would otherwise loop forever.
+                            more = page.next_cursor.is_some() && page.next_cursor != cursor;
+                            cursor = page.next_cursor;
+                            buffered.extend(page.items);
+                        }
+                        Err(e) => return Some((Err(e), (buffered, cursor, false))),
+                    }
+                }
+            }
+        })
+    }
+}
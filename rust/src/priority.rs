@@ -0,0 +1,49 @@
+//! How urgent a task is, for servers that make tasks wait for a slot.
+//!
+//! Every task sent inside [`with_priority`] carries a `priority` param.
+//! An [`A2AServer`] with
+//! [`with_max_concurrent_tasks`](crate::A2AServer::with_max_concurrent_tasks)
+//! and [`OverloadPolicy::Queue`](crate::OverloadPolicy::Queue) hands each
+//! freed slot to the waiting task of highest priority, so latency-sensitive
+//! tasks overtake queued bulk work. This is best-effort: a running handler
+//! is never interrupted for a more urgent task, and a server without a
+//! queue has nothing to reorder.
+//!
+//! Unlike deadlines, the priority is not passed on: tasks a handler sends
+//! onward carry [`DEFAULT_PRIORITY`] unless wrapped in
+//! [`with_priority`]`(ctx.priority, ..)`.
+//!
+//! [`A2AServer`]: crate::A2AServer
+
+use std::future::Future;
+
+/// The priority of tasks sent outside [`with_priority`], and of tasks
+/// arriving without one; halfway, so bulk work can go below it.
+pub const DEFAULT_PRIORITY: u8 = 128;
+
+tokio::task_local! {
+    static PRIORITY: u8;
+}
+
+/// Run `future` so every task it sends carries `priority`; higher is more
+/// urgent.
+///
+/// ```rust,no_run
+/// use a2a::{with_priority, A2AAgent};
+/// use serde_json::json;
+///
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// let agent = A2AAgent::new("client", "Client", vec![]);
+/// let task = agent.send_task("search", "query", json!({"q": "rust"}), "http://localhost:8080");
+/// let result = with_priority(255, task).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn with_priority<F: Future>(priority: u8, future: F) -> F::Output {
+    PRIORITY.scope(priority, future).await
+}
+
+/// The `priority` param for a task sent now.
+pub(crate) fn current() -> Option<u8> {
+    PRIORITY.try_with(|priority| *priority).ok()
+}
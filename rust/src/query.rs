@@ -0,0 +1,184 @@
+//! Boolean capability queries for `a2a/discover`, and the matching of
+//! agents against queries and tags that the directory and clients share.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{AgentInfo, Capability};
+
+/// Which capabilities a discovered agent must offer.
+///
+/// Sent to the directory as the `query` param of `a2a/discover`, in
+/// externally tagged form:
+///
+/// - `{"all": ["search", "summarize"]}` matches agents offering every
+///   listed capability.
+/// - `{"any": ["search", "translate"]}` matches agents offering at least
+///   one of them.
+/// - `{"allOf": [q1, q2]}` and `{"anyOf": [q1, q2]}` combine nested
+///   queries the same way.
+///
+/// A plain [`All`](Self::All) query is sent as the legacy
+/// `"capabilities": [..]` list instead, so older directories keep
+/// understanding it. Directories should treat that list as `all`.
+/// An empty `all` matches every agent; an empty `any` matches none.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CapabilityQuery {
+    All(Vec<String>),
+    Any(Vec<String>),
+    #[serde(alias = "all_of")]
+    AllOf(Vec<CapabilityQuery>),
+    #[serde(alias = "any_of")]
+    AnyOf(Vec<CapabilityQuery>),
+}
+
+impl CapabilityQuery {
+    /// Whether an agent offering `capabilities` satisfies this query.
+    pub fn matches(&self, capabilities: &[Capability]) -> bool {
+        let offers = |wanted: &String| capabilities.iter().any(|c| c.fits(wanted));
+        match self {
+            CapabilityQuery::All(names) => names.iter().all(offers),
+            CapabilityQuery::Any(names) => names.iter().any(offers),
+            CapabilityQuery::AllOf(queries) => queries.iter().all(|q| q.matches(capabilities)),
+            CapabilityQuery::AnyOf(queries) => queries.iter().any(|q| q.matches(capabilities)),
+        }
+    }
+
+    /// The same query with every name list sorted and de-duplicated, so
+    /// equivalent queries compare equal.
+    pub(crate) fn normalized(&self) -> CapabilityQuery {
+        let sorted = |names: &[String]| {
+            let mut names = names.to_vec();
+            names.sort();
+            names.dedup();
+            names
+        };
+        match self {
+            CapabilityQuery::All(names) => CapabilityQuery::All(sorted(names)),
+            CapabilityQuery::Any(names) => CapabilityQuery::Any(sorted(names)),
+            CapabilityQuery::AllOf(queries) => CapabilityQuery::AllOf(queries.iter().map(Self::normalized).collect()),
+            CapabilityQuery::AnyOf(queries) => CapabilityQuery::AnyOf(queries.iter().map(Self::normalized).collect()),
+        }
+    }
+}
+
+impl AgentInfo {
+    /// Whether this agent satisfies `query`, as a directory answering
+    /// `a2a/discover` decides it, e.g. to filter cached discovery results.
+    ///
+    /// ```
+    /// use a2a::{AgentInfo, CapabilityQuery};
+    ///
+    /// let agent: AgentInfo = serde_json::from_value(serde_json::json!({
+    ///     "agentId": "calc",
+    ///     "name": "Calculator",
+    ///     "capabilities": ["add", "subtract"],
+    ///     "endpoint": "http://localhost:9001",
+    ///     "registeredAt": null,
+    /// }))?;
+    /// assert!(agent.matches(&CapabilityQuery::All(vec!["add".into(), "subtract".into()])));
+    /// assert!(agent.matches(&CapabilityQuery::Any(vec!["add".into(), "divide".into()])));
+    /// assert!(!agent.matches(&CapabilityQuery::Any(vec![])));
+    ///
+    /// let add = CapabilityQuery::All(vec!["add".into()]);
+    /// let divide = CapabilityQuery::All(vec!["divide".into()]);
+    /// assert!(agent.matches(&CapabilityQuery::AnyOf(vec![add.clone(), divide.clone()])));
+    /// assert!(!agent.matches(&CapabilityQuery::AllOf(vec![add, divide])));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn matches(&self, query: &CapabilityQuery) -> bool {
+        query.matches(&self.capabilities)
+    }
+
+    /// Whether this agent carries every one of `tags` with the same value.
+    /// An empty `tags` matches every agent.
+    pub fn matches_tags(&self, tags: &HashMap<String, String>) -> bool {
+        tags.iter().all(|(key, value)| self.tags.get(key) == Some(value))
+    }
+}
+
+impl From<Vec<String>> for CapabilityQuery {
+    fn from(names: Vec<String>) -> Self {
+        CapabilityQuery::All(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn calculator() -> AgentInfo {
+        let capabilities = names(&["add", "subtract"]);
+        let mut agent = AgentInfo::new("calc", "Calculator", capabilities, "http://localhost:9001").unwrap();
+        agent.tags.insert("region".to_string(), "eu".to_string());
+        agent
+    }
+
+    #[test]
+    fn each_query_shape_matches_as_documented() {
+        let agent = calculator();
+        let all = |list: &[&str]| CapabilityQuery::All(names(list));
+        let any = |list: &[&str]| CapabilityQuery::Any(names(list));
+
+        assert!(agent.matches(&all(&["add", "subtract"])));
+        assert!(!agent.matches(&all(&["add", "divide"])));
+        assert!(agent.matches(&all(&[])));
+
+        assert!(agent.matches(&any(&["divide", "subtract"])));
+        assert!(!agent.matches(&any(&["divide", "multiply"])));
+        assert!(!agent.matches(&any(&[])));
+
+        assert!(agent.matches(&CapabilityQuery::AllOf(vec![all(&["add"]), any(&["divide", "subtract"])])));
+        assert!(!agent.matches(&CapabilityQuery::AllOf(vec![all(&["add"]), any(&["divide"])])));
+        assert!(agent.matches(&CapabilityQuery::AnyOf(vec![all(&["divide"]), any(&["add"])])));
+        assert!(!agent.matches(&CapabilityQuery::AnyOf(vec![all(&["divide"]), any(&["multiply"])])));
+        assert!(agent.matches(&CapabilityQuery::AllOf(vec![])));
+        assert!(!agent.matches(&CapabilityQuery::AnyOf(vec![])));
+    }
+
+    #[test]
+    fn versioned_names_match_only_versioned_capabilities() {
+        let mut agent = calculator();
+        agent.capabilities.push(Capability::new("divide").with_version("2.1.0".parse().unwrap()));
+
+        assert!(agent.matches(&CapabilityQuery::All(names(&["divide@^2.0"]))));
+        assert!(!agent.matches(&CapabilityQuery::All(names(&["divide@^3.0"]))));
+        assert!(!agent.matches(&CapabilityQuery::All(names(&["add@^1.0"]))));
+    }
+
+    #[test]
+    fn tags_must_all_match() {
+        let agent = calculator();
+        let tags = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        assert!(agent.matches_tags(&tags(&[])));
+        assert!(agent.matches_tags(&tags(&[("region", "eu")])));
+        assert!(!agent.matches_tags(&tags(&[("region", "us")])));
+        assert!(!agent.matches_tags(&tags(&[("region", "eu"), ("tier", "gold")])));
+    }
+
+    #[test]
+    fn queries_are_externally_tagged() {
+        let query = CapabilityQuery::AnyOf(vec![CapabilityQuery::All(names(&["add"]))]);
+        assert_eq!(serde_json::to_value(&query).unwrap(), json!({"anyOf": [{"all": ["add"]}]}));
+
+        let parsed: CapabilityQuery = serde_json::from_value(json!({"all_of": [{"any": ["add"]}]})).unwrap();
+        assert_eq!(parsed, CapabilityQuery::AllOf(vec![CapabilityQuery::Any(names(&["add"]))]));
+    }
+
+    #[test]
+    fn equivalent_queries_normalize_equal() {
+        let one = CapabilityQuery::AllOf(vec![CapabilityQuery::Any(names(&["b", "a", "b"]))]);
+        let other = CapabilityQuery::AllOf(vec![CapabilityQuery::Any(names(&["a", "b"]))]);
+        assert_ne!(one, other);
+        assert_eq!(one.normalized(), other.normalized());
+    }
+}
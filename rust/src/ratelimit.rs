@@ -0,0 +1,100 @@
+//! Client-side rate limiting of outbound tasks, per target agent.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::time::{self, Instant};
+use crate::A2AError;
+
+/// Token-bucket limit applied to tasks sent to each target agent.
+///
+/// Every target gets its own bucket holding up to `burst` tokens, refilled
+/// at `requests_per_sec`. Sending a task takes one token; when the bucket
+/// is empty the send waits for the next token, or fails with
+/// [`A2AError::RateLimited`] if [`fail_fast`](Self::fail_fast) is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_sec: f64,
+    pub burst: u32,
+    /// Reject instead of waiting when no token is available.
+    pub fail_fast: bool,
+}
+
+impl RateLimit {
+    pub fn new(requests_per_sec: f64, burst: u32) -> Self {
+        Self {
+            requests_per_sec,
+            burst,
+            fail_fast: false,
+        }
+    }
+
+    /// Return [`A2AError::RateLimited`] instead of waiting for a token.
+    pub fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Buckets for every target seen so far, shared by clones of an agent.
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `agent_id`, waiting for one unless the limit fails fast.
+    pub(crate) async fn acquire(&self, agent_id: &str) -> Result<(), A2AError> {
+        loop {
+            let wait = match self.try_acquire(agent_id) {
+                None => return Ok(()),
+                Some(wait) if self.limit.fail_fast => {
+                    return Err(A2AError::RateLimited {
+                        target: agent_id.to_string(),
+                        retry_after: Some(wait),
+                    })
+                }
+                Some(wait) => wait,
+            };
+            debug!(target = agent_id, wait_ms = wait.as_millis() as u64, "rate limited, waiting");
+            time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token if one is available, otherwise return how long until
+    /// the next one is.
+    fn try_acquire(&self, agent_id: &str) -> Option<Duration> {
+        let burst = f64::from(self.limit.burst.max(1));
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(agent_id.to_string()).or_insert(Bucket {
+            tokens: burst,
+            refilled: now,
+        });
+
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.limit.requests_per_sec).min(burst);
+        bucket.refilled = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return None;
+        }
+        let wait = (1.0 - bucket.tokens) / self.limit.requests_per_sec;
+        Some(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX))
+    }
+}
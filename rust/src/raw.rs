@@ -0,0 +1,76 @@
+//! Sending task calls and getting back the JSON-RPC response as it came,
+//! for debugging peers.
+
+use serde_json::Value;
+
+use crate::{deadline, A2AAgent, A2AError, TaskRequest};
+
+impl A2AAgent {
+    /// Like [`send_task`](Self::send_task), but return the whole JSON-RPC
+    /// response envelope unparsed: `jsonrpc`, `id`, and `result` or
+    /// `error`, whatever shape the peer gave them.
+    ///
+    /// This is an escape hatch for debugging peers that answer in
+    /// non-standard ways, not for everyday use. A JSON-RPC error or a
+    /// result that is no [`TaskResult`](crate::TaskResult) is returned, not
+    /// raised, and neither the response `id` nor its `jsonrpc` version is
+    /// checked. HTTP failures, non-JSON bodies and failed lookups are still
+    /// errors. It always goes over HTTP, with the agent's headers,
+    /// credentials and retry policy, bypassing a custom
+    /// [`Transport`](crate::Transport), the rate limit, circuit breakers,
+    /// dry-run mode and result signature checks.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgent;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let response = agent
+    ///     .send_task_raw("calculator", "add", json!({"a": 1, "b": 2}), "http://localhost:8080")
+    ///     .await?;
+    /// println!("id {} said {}", response["id"], response.get("error").unwrap_or(&response["result"]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_task_raw(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<Value, A2AError> {
+        let task = self
+            .build_task_request(target_agent_id, action, input, directory_url)
+            .await?;
+        self.send_request_raw(&task).await
+    }
+
+    /// Send a call built with [`build_task_request`](Self::build_task_request),
+    /// possibly edited since, and return the response envelope unparsed, as
+    /// [`send_task_raw`](Self::send_task_raw) does.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgent;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let task = agent
+    ///     .build_task_request("calculator", "add", json!({"a": 1, "b": 2}), "http://localhost:8080")
+    ///     .await?;
+    /// let response = agent.send_request_raw(&task).await?;
+    /// assert_eq!(response["id"], task.request.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request_raw(&self, task: &TaskRequest) -> Result<Value, A2AError> {
+        let url = task.endpoint.as_str();
+        let method = task.request.method.as_str();
+        let call = self.with_retries(url, method, || async {
+            let response = self.http.post_json(url, method, &task.request).await?;
+            self.http.read_json(response).await
+        });
+        deadline::bounded(call).await
+    }
+}
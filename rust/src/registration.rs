@@ -0,0 +1,52 @@
+//! Deciding which agents may advertise what in an
+//! [`A2ADirectory`](crate::A2ADirectory).
+
+use axum::http::HeaderMap;
+
+use crate::AgentInfo;
+
+/// Vets every registration, and every capability update, before an
+/// [`A2ADirectory`](crate::A2ADirectory) stores it.
+///
+/// Install one with
+/// [`A2ADirectory::with_registration_policy`](crate::A2ADirectory::with_registration_policy).
+/// `allow` sees the agent as it would be listed, with the capabilities an
+/// update would leave it with, and the headers of the call that asked; a
+/// refused registration fails with JSON-RPC error `-32003`
+/// "Unauthorized", which clients see as
+/// [`A2AError::RegistrationRejected`](crate::A2AError::RegistrationRejected)
+/// with reason `ForbiddenCapability`, and leaves the directory as it was.
+/// The default, [`AllowAllRegistrations`], accepts everything.
+///
+/// ```
+/// use a2a::{A2ADirectory, AgentInfo, RegistrationPolicy};
+/// use axum::http::HeaderMap;
+///
+/// /// Lets only callers holding the operator token advertise `admin`.
+/// struct AdminNeedsToken(String);
+///
+/// impl RegistrationPolicy for AdminNeedsToken {
+///     fn allow(&self, info: &AgentInfo, headers: &HeaderMap) -> bool {
+///         let operator = headers
+///             .get("authorization")
+///             .and_then(|value| value.to_str().ok())
+///             .is_some_and(|value| value.strip_prefix("Bearer ") == Some(self.0.as_str()));
+///         operator || !info.capabilities.iter().any(|capability| capability.name == "admin")
+///     }
+/// }
+///
+/// let directory = A2ADirectory::new(8080).with_registration_policy(AdminNeedsToken("s3cret".to_string()));
+/// ```
+pub trait RegistrationPolicy: Send + Sync {
+    fn allow(&self, info: &AgentInfo, headers: &HeaderMap) -> bool;
+}
+
+/// Accepts every registration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAllRegistrations;
+
+impl RegistrationPolicy for AllowAllRegistrations {
+    fn allow(&self, _info: &AgentInfo, _headers: &HeaderMap) -> bool {
+        true
+    }
+}
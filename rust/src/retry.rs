@@ -0,0 +1,135 @@
+//! Retry policy for outbound JSON-RPC calls.
+
+use std::time::Duration;
+
+use crate::{time, timestamp, A2AError};
+
+/// How often, and how patiently, the agent retries a failed request.
+///
+/// Only connection failures, HTTP 5xx and HTTP 429 responses are retried.
+/// Other HTTP 4xx and JSON-RPC application errors are deterministic and
+/// fail immediately. A 429 with a `Retry-After` header is retried after the
+/// delay the server asked for instead of the usual backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every further attempt.
+    pub base_backoff: Duration,
+    /// Longest `Retry-After` worth waiting for; a 429 asking for more
+    /// fails at once with [`A2AError::RateLimited`].
+    pub max_retry_after: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            base_backoff,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Never retry.
+    pub fn none() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    /// Wait at most `max_retry_after` when a server sends `Retry-After`
+    /// (default: 60s).
+    pub fn with_max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Delay before retrying after `error` on retry number `attempt`, or
+    /// `None` if the server asked for a longer wait than the policy allows.
+    pub(crate) fn delay(&self, error: &A2AError, attempt: u32) -> Option<Duration> {
+        match error {
+            A2AError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => (*retry_after <= self.max_retry_after).then_some(*retry_after),
+            _ => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// Delay before retry number `attempt` (starting at 0): exponential
+    /// backoff with jitter drawn from the upper half of the window.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.base_backoff.saturating_mul(2u32.saturating_pow(attempt));
+        let half = delay / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(2, Duration::from_millis(250))
+    }
+}
+
+pub(crate) fn is_retryable(error: &A2AError) -> bool {
+    match error {
+        A2AError::Http(e) => is_connect(e) || e.status().is_some_and(|s| s.is_server_error()),
+        A2AError::HttpStatus { status, .. } => status.is_server_error(),
+        A2AError::RateLimited { .. } => true,
+        _ => false,
+    }
+}
+
+/// Read a `Retry-After` value, either delay seconds such as `120` or an
+/// HTTP-date such as `Wed, 21 Oct 2015 07:28:00 GMT`. A date already past
+/// means no delay.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(Duration::from_secs(value.parse().unwrap_or(u64::MAX)));
+    }
+    let date = timestamp::parse_http_date(value)?;
+    let now = chrono::DateTime::<chrono::Utc>::from(time::system_now());
+    Some(date.signed_duration_since(now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect(error: &reqwest::Error) -> bool {
+    error.is_connect()
+}
+
+/// `fetch` does not say why a request failed, so any failure to get a
+/// response counts as a connection error.
+#[cfg(target_arch = "wasm32")]
+fn is_connect(error: &reqwest::Error) -> bool {
+    error.is_request()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_is_read_as_seconds_or_a_date() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("-1"), None);
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let later = chrono::DateTime::<chrono::Utc>::from(time::system_now() + Duration::from_secs(90));
+        let waited = parse_retry_after(&later.format("%a, %d %b %Y %H:%M:%S GMT").to_string()).unwrap();
+        assert!(waited > Duration::from_secs(85) && waited <= Duration::from_secs(90), "{:?}", waited);
+    }
+
+    #[test]
+    fn long_retry_afters_are_not_waited_for() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(10)).with_max_retry_after(Duration::from_secs(5));
+        let asked = |secs| A2AError::RateLimited {
+            target: "peer".to_string(),
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        assert_eq!(policy.delay(&asked(5), 0), Some(Duration::from_secs(5)));
+        assert_eq!(policy.delay(&asked(6), 0), None);
+        let unknown = A2AError::RateLimited { target: "peer".to_string(), retry_after: None };
+        assert!(policy.delay(&unknown, 0).unwrap() <= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,162 @@
+//! Client-side choice among agents that all match a discovery query.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::{A2AAgent, A2AError, AgentInfo, CapabilityQuery, TaskResult};
+
+/// How [`A2AAgent::discover_select`] picks one of several matching agents.
+///
+/// Round-robin and least-recently-used state lives in the agent, shared by
+/// its clones, and only counts picks made through `discover_select`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// The first match in directory order, like [`A2AAgent::discover`].
+    #[default]
+    First,
+    /// A uniformly random match.
+    Random,
+    /// Each match in turn, in `agent_id` order, per directory and query.
+    RoundRobin,
+    /// The match picked longest ago, or never picked.
+    LeastRecentlyUsed,
+}
+
+/// Per-agent state for the stateful strategies.
+#[derive(Default)]
+pub(crate) struct Selector {
+    /// Next round-robin position per directory and normalized query.
+    turns: Mutex<HashMap<(String, CapabilityQuery), usize>>,
+    /// When each agent was last picked, as a pick count.
+    last_used: Mutex<(u64, HashMap<String, u64>)>,
+}
+
+impl Selector {
+    fn select(
+        &self,
+        agents: Vec<AgentInfo>,
+        strategy: SelectionStrategy,
+        directory_url: &str,
+        query: &CapabilityQuery,
+    ) -> Option<AgentInfo> {
+        self.order(agents, strategy, directory_url, query).into_iter().next()
+    }
+
+    /// `agents` with the one `strategy` picks first, then the others in
+    /// the order it would fall back to them. Only the first counts as
+    /// picked.
+    fn order(
+        &self,
+        mut agents: Vec<AgentInfo>,
+        strategy: SelectionStrategy,
+        directory_url: &str,
+        query: &CapabilityQuery,
+    ) -> Vec<AgentInfo> {
+        if agents.is_empty() {
+            return agents;
+        }
+        match strategy {
+            SelectionStrategy::First => {}
+            SelectionStrategy::Random => fastrand::shuffle(&mut agents),
+            SelectionStrategy::RoundRobin => {
+                // Sort so the rotation does not depend on directory order.
+                agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+                let key = (directory_url.trim_end_matches('/').to_string(), query.normalized());
+                let mut turns = self.turns.lock().unwrap();
+                let turn = turns.entry(key).or_insert(0);
+                let index = *turn % agents.len();
+                *turn = index + 1;
+                agents.rotate_left(index);
+            }
+            SelectionStrategy::LeastRecentlyUsed => {
+                let mut last_used = self.last_used.lock().unwrap();
+                let (clock, picks) = &mut *last_used;
+                agents.sort_by_key(|agent| picks.get(&agent.agent_id).copied().unwrap_or(0));
+                *clock += 1;
+                picks.insert(agents[0].agent_id.clone(), *clock);
+            }
+        }
+        agents
+    }
+}
+
+impl A2AAgent {
+    /// Discover every agent satisfying `query` and pick one by `strategy`,
+    /// spreading load across them without a separate balancer.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, CapabilityQuery, SelectionStrategy};
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let query = CapabilityQuery::All(vec!["add".to_string()]);
+    /// let calculator = agent
+    ///     .discover_select(query, SelectionStrategy::RoundRobin, "http://localhost:8080")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn discover_select(
+        &self,
+        query: CapabilityQuery,
+        strategy: SelectionStrategy,
+        directory_url: &str,
+    ) -> Result<Option<AgentInfo>, A2AError> {
+        let agents = self.discover_matching(query.clone(), directory_url).await?;
+        let agent = self.selector.select(agents, strategy, directory_url, &query);
+        if let Some(agent) = &agent {
+            debug!(selected = %agent.agent_id, ?strategy, "agent selected");
+        }
+        Ok(agent)
+    }
+
+    /// Discover every agent satisfying `query` and send the task to each in
+    /// turn until one succeeds, so any agent able to do it will. `strategy`
+    /// orders the candidates: the one [`discover_select`](Self::discover_select)
+    /// would pick goes first, and the rest follow as fallbacks.
+    ///
+    /// Any error moves on to the next candidate. If every candidate fails,
+    /// the result is [`A2AError::AllCandidatesFailed`] with each one's
+    /// error in the order tried; with no candidates at all, that list is
+    /// empty.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, CapabilityQuery, SelectionStrategy};
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let query = CapabilityQuery::All(vec!["add".to_string()]);
+    /// let result = agent
+    ///     .send_task_any(query, SelectionStrategy::RoundRobin, "add", json!({"a": 1, "b": 2}), "http://localhost:8080")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_task_any(
+        &self,
+        query: CapabilityQuery,
+        strategy: SelectionStrategy,
+        action: &str,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let agents = self.discover_matching(query.clone(), directory_url).await?;
+        let method = self.method("task");
+
+        let mut failures = Vec::new();
+        for agent in self.selector.order(agents, strategy, directory_url, &query) {
+            let task = self.send_to(&agent, action, None, input.clone(), &[], &method);
+            match self.measured(action, task).await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    warn!(candidate = %agent.agent_id, error = %e, "candidate failed");
+                    failures.push((agent.agent_id, e));
+                }
+            }
+        }
+        Err(A2AError::AllCandidatesFailed(failures))
+    }
+}
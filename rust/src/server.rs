@@ -0,0 +1,1572 @@
+//! HTTP server side of the A2A protocol.
+//!
+//! An [`A2AServer`] exposes a single `POST /` JSON-RPC endpoint and
+//! dispatches `a2a/task` calls to the registered task handler. It also
+//! describes itself on `GET /a2a/card`.
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::{FutureExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use crate::attachment;
+use crate::authorizer::Authorizer;
+use crate::cancellation::{Cancellations, Registration};
+use crate::codec::{self, Codecs};
+use crate::concurrency::{ConcurrencyLimit, InFlight, OverloadPolicy, Slot};
+use crate::correlation::{self, with_correlation_id};
+use crate::deadline;
+use crate::guard::InputGuard;
+use crate::idempotency::{Attempt, IdempotencyCache};
+use crate::metrics;
+use crate::observer::{Observers, TaskObserver};
+use crate::stream::{CHUNK_EVENT, DONE_EVENT, ERROR_EVENT};
+#[cfg(feature = "ws")]
+use crate::ws;
+use crate::{
+    A2AError, AgentInfo, Attachment, Capability, JSONRPCError, JSONRPCRequest, JSONRPCResponse, JSONRPC_VERSION, TaskError, TaskParams, TaskResult, TaskStatus,
+    TaskChunk, TaskStatusParams, Version, DEADLINE_EXCEEDED, DEFAULT_METHOD_NAMESPACE, DEFAULT_PRIORITY,
+};
+
+pub(crate) const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i32 = -32601;
+pub(crate) const INVALID_PARAMS: i32 = -32602;
+pub(crate) const INTERNAL_ERROR: i32 = -32603;
+const SERVER_BUSY: i32 = -32000;
+pub(crate) const UNAUTHORIZED: i32 = -32003;
+
+/// How long a finished submitted task stays queryable via `a2a/task/status`.
+const TASK_RETENTION: Duration = Duration::from_secs(300);
+
+/// The request body limit axum applies unless told otherwise, 2 MiB.
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// What a task handler may return: either a bare `Value`, which always
+/// counts as success, or a `Result<Value, TaskError>`.
+pub trait IntoTaskOutput {
+    fn into_task_output(self) -> Result<Value, TaskError>;
+}
+
+impl IntoTaskOutput for Value {
+    fn into_task_output(self) -> Result<Value, TaskError> {
+        Ok(self)
+    }
+}
+
+impl IntoTaskOutput for Result<Value, TaskError> {
+    fn into_task_output(self) -> Result<Value, TaskError> {
+        self
+    }
+}
+
+/// What a handler knows about the task it is running, besides its input.
+#[derive(Debug, Clone)]
+pub struct TaskContext {
+    pub task_id: String,
+    pub action: String,
+    /// `agent_id` of the calling agent.
+    pub sender: String,
+    /// See [`current_correlation_id`](crate::current_correlation_id).
+    pub correlation_id: String,
+    /// HTTP headers of the request that carried the task.
+    pub headers: HeaderMap,
+    /// Cancelled when the caller sends `a2a/task/cancel`. Cancellation is
+    /// cooperative: nothing is aborted by force, so a long-running handler
+    /// should check `is_cancelled()` or race its work against
+    /// `cancelled()` and return early.
+    pub cancellation: CancellationToken,
+    /// When the caller's deadline passes, if it sent one. Tasks the
+    /// handler sends onward inherit it; see [`with_deadline`](crate::with_deadline).
+    pub deadline: Option<Instant>,
+    /// Binary parts sent with the task through
+    /// [`send_task_with_attachments`](crate::A2AAgent::send_task_with_attachments),
+    /// in the order sent.
+    pub attachments: Vec<Attachment>,
+    /// The version of the action the caller chose with
+    /// [`send_task_versioned`](crate::A2AAgent::send_task_versioned), if any.
+    pub version: Option<Version>,
+    /// As the caller set it with [`with_priority`](crate::with_priority),
+    /// or [`DEFAULT_PRIORITY`](crate::DEFAULT_PRIORITY).
+    pub priority: u8,
+}
+
+impl TaskContext {
+    /// Time left before the caller's deadline, if it set one; zero once it
+    /// passed.
+    pub fn remaining_budget(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+pub type AsyncTaskHandler = Box<
+    dyn Fn(TaskContext, Value) -> Pin<Box<dyn Future<Output = Result<Value, TaskError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// What a custom method handler knows about its call, besides the params.
+#[derive(Debug, Clone)]
+pub struct MethodContext {
+    pub method: String,
+    /// HTTP headers of the request that carried the call.
+    pub headers: HeaderMap,
+}
+
+pub type MethodHandler = Box<
+    dyn Fn(MethodContext, Value) -> Pin<Box<dyn Future<Output = Result<Value, TaskError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+pub type StreamTaskHandler = Box<
+    dyn Fn(TaskContext, Value) -> Pin<Box<dyn Stream<Item = Result<Value, TaskError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+pub struct A2AServer {
+    agent_id: String,
+    name: String,
+    capabilities: Vec<Capability>,
+    endpoint: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    port: u16,
+    method_namespace: String,
+    task_handler: Option<Arc<AsyncTaskHandler>>,
+    action_handlers: HashMap<String, Arc<AsyncTaskHandler>>,
+    stream_handler: Option<Arc<StreamTaskHandler>>,
+    methods: HashMap<String, Arc<MethodHandler>>,
+    health_token: Option<String>,
+    idempotency: Option<(Duration, usize)>,
+    concurrency: Option<(usize, OverloadPolicy)>,
+    input_guard: InputGuard,
+    handler_timeout: Option<Duration>,
+    action_timeouts: HashMap<String, Duration>,
+    max_attachment_bytes: Option<usize>,
+    authorizer: Option<Arc<dyn Authorizer>>,
+    observers: Vec<Arc<dyn TaskObserver>>,
+    codecs: Codecs,
+    in_flight: InFlight,
+    #[cfg(feature = "signing")]
+    signing_key: Option<Arc<crate::SigningKey>>,
+    #[cfg(feature = "compression")]
+    compression: Option<crate::Compression>,
+    #[cfg(feature = "tls")]
+    tls: Option<crate::ServerTlsConfig>,
+}
+
+struct ServerState {
+    agent_id: String,
+    card: AgentInfo,
+    /// `https` behind [`A2AServer::with_tls`], for the card's endpoint.
+    scheme: &'static str,
+    started: Instant,
+    method_namespace: String,
+    task_handler: Option<Arc<AsyncTaskHandler>>,
+    action_handlers: HashMap<String, Arc<AsyncTaskHandler>>,
+    stream_handler: Option<Arc<StreamTaskHandler>>,
+    methods: HashMap<String, Arc<MethodHandler>>,
+    health_token: Option<String>,
+    tasks: TaskStore,
+    idempotency: Option<IdempotencyCache>,
+    limit: Option<ConcurrencyLimit>,
+    input_guard: InputGuard,
+    handler_timeout: Option<Duration>,
+    action_timeouts: HashMap<String, Duration>,
+    max_attachment_bytes: Option<usize>,
+    authorizer: Option<Arc<dyn Authorizer>>,
+    observers: Observers,
+    codecs: Codecs,
+    in_flight: InFlight,
+    cancellations: Cancellations,
+    #[cfg(feature = "signing")]
+    signing_key: Option<Arc<crate::SigningKey>>,
+    #[cfg(feature = "compression")]
+    compression: Option<crate::Compression>,
+}
+
+impl ServerState {
+    /// `method` without the server's namespace, e.g. `task/submit`, or
+    /// `None` if it is outside the namespace.
+    fn local_method<'a>(&self, method: &'a str) -> Option<&'a str> {
+        method.strip_prefix(self.method_namespace.as_str())?.strip_prefix('/')
+    }
+
+    /// How long handlers of `action` may run, if bounded.
+    fn handler_timeout(&self, action: &str) -> Option<Duration> {
+        self.action_timeouts.get(action).copied().or(self.handler_timeout)
+    }
+}
+
+/// Status of tasks started through `a2a/task/submit`, by sender and
+/// `task_id`, plus the handles of those still running so shutdown can
+/// wait for them.
+#[derive(Default)]
+struct TaskStore {
+    tasks: Mutex<HashMap<(String, String), (TaskResult, Instant)>>,
+    running: Mutex<JoinSet<()>>,
+}
+
+impl TaskStore {
+    fn set(&self, sender: &str, result: TaskResult) {
+        let now = Instant::now();
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|_, (result, updated)| {
+            !result.status.is_terminal() || now.duration_since(*updated) < TASK_RETENTION
+        });
+        tasks.insert((sender.to_string(), result.task_id.clone()), (result, now));
+    }
+
+    fn get(&self, sender: &str, task_id: &str) -> Option<TaskResult> {
+        let key = (sender.to_string(), task_id.to_string());
+        self.tasks.lock().unwrap().get(&key).map(|(result, _)| result.clone())
+    }
+
+    fn spawn<F: Future<Output = ()> + Send + 'static>(&self, task: F) {
+        let mut running = self.running.lock().unwrap();
+        while running.try_join_next().is_some() {}
+        running.spawn(task);
+    }
+
+    /// Wait for every task spawned so far to finish.
+    async fn drain(&self) {
+        let mut running = std::mem::take(&mut *self.running.lock().unwrap());
+        while running.join_next().await.is_some() {}
+    }
+}
+
+impl A2AServer {
+    pub fn new(agent_id: &str, name: &str, capabilities: Vec<String>, port: u16) -> Self {
+        Self {
+            agent_id: agent_id.to_string(),
+            name: name.to_string(),
+            capabilities: capabilities.into_iter().map(Capability::new).collect(),
+            endpoint: None,
+            version: None,
+            description: None,
+            port,
+            method_namespace: DEFAULT_METHOD_NAMESPACE.to_string(),
+            task_handler: None,
+            action_handlers: HashMap::new(),
+            stream_handler: None,
+            methods: HashMap::new(),
+            health_token: None,
+            idempotency: None,
+            concurrency: None,
+            input_guard: InputGuard::default(),
+            handler_timeout: None,
+            action_timeouts: HashMap::new(),
+            max_attachment_bytes: None,
+            authorizer: None,
+            observers: Vec::new(),
+            codecs: Codecs::default(),
+            in_flight: InFlight::default(),
+            #[cfg(feature = "signing")]
+            signing_key: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Replace the plain capability names with fully described
+    /// capabilities, including input/output schemas.
+    pub fn with_capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The URL to report on `GET /a2a/card`. Without it the card names
+    /// `http://` plus the `Host` the request was sent to.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// The version to report on `GET /a2a/card`.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// The description to report on `GET /a2a/card`.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Answer `{namespace}/task`, `{namespace}/task/submit` and so on
+    /// instead of `a2a/...`, for clients configured with the same
+    /// [`A2AAgent::with_method_namespace`](crate::A2AAgent::with_method_namespace).
+    pub fn with_method_namespace(mut self, namespace: &str) -> Self {
+        self.method_namespace = namespace.trim_matches('/').to_string();
+        self
+    }
+
+    /// Require `Authorization: Bearer <token>` on `GET /health`, which is
+    /// open by default.
+    pub fn with_health_auth(mut self, token: impl Into<String>) -> Self {
+        self.health_token = Some(token.into());
+        self
+    }
+
+    /// Remember the outcome of `a2a/task` and `a2a/task/submit` calls for
+    /// `ttl`, keyed by sender and `taskId`, and answer a repeated task with
+    /// the remembered outcome instead of running the handler again. At
+    /// most `max_entries` outcomes are kept; the oldest is dropped first.
+    /// Only tasks that reached their handler are remembered: one refused
+    /// as the server was busy or its deadline had passed runs if resent.
+    pub fn with_idempotency(mut self, ttl: Duration, max_entries: usize) -> Self {
+        self.idempotency = Some((ttl, max_entries));
+        self
+    }
+
+    /// Run at most `max_concurrent_tasks` handlers at once, across
+    /// `a2a/task`, `a2a/task/submit` and `a2a/task/stream`. Excess tasks
+    /// wait or are rejected with `-32000` depending on `policy`; see
+    /// [`OverloadPolicy`] for the tradeoff. Waiting tasks start in order of
+    /// [priority](crate::with_priority).
+    pub fn with_max_concurrent_tasks(mut self, max_concurrent_tasks: usize, policy: OverloadPolicy) -> Self {
+        self.concurrency = Some((max_concurrent_tasks, policy));
+        self
+    }
+
+    /// Refuse tasks whose `input` breaks `guard` with `-32602` before any
+    /// handler runs; see [`InputGuard`].
+    pub fn with_input_guard(mut self, guard: InputGuard) -> Self {
+        self.input_guard = guard;
+        self
+    }
+
+    /// Give up on an `a2a/task` or `a2a/task/submit` handler that runs
+    /// longer than `timeout`, failing the task with `-32001` "Task timeout"
+    /// and freeing its slot.
+    ///
+    /// The handler's future is dropped at its next `.await`, so a handler
+    /// should hold no state that a drop there would leave inconsistent,
+    /// and work it spawns should watch [`TaskContext::cancellation`] or
+    /// bound itself. Synchronous handlers never yield and always run to
+    /// completion.
+    pub fn with_handler_timeout(mut self, timeout: Duration) -> Self {
+        self.handler_timeout = Some(timeout);
+        self
+    }
+
+    /// Time out handlers of `action` after `timeout` in place of the
+    /// [`with_handler_timeout`](Self::with_handler_timeout) default.
+    pub fn with_action_timeout(mut self, action: &str, timeout: Duration) -> Self {
+        self.action_timeouts.insert(action.to_string(), timeout);
+        self
+    }
+
+    /// Ask `authorizer` about every task before running it, and every
+    /// status or cancel call before answering it, failing those it
+    /// refuses with `-32003` "Unauthorized"; see [`Authorizer`] and the
+    /// ready-made [`BearerAuthorizer`](crate::BearerAuthorizer).
+    pub fn with_authorizer(mut self, authorizer: impl Authorizer + 'static) -> Self {
+        self.authorizer = Some(Arc::new(authorizer));
+        self
+    }
+
+    /// Tell `observer` about the lifecycle of every task, after the
+    /// observers added before it; see [`TaskObserver`].
+    pub fn with_observer(mut self, observer: impl TaskObserver + 'static) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Accept at most `max_bytes` of attachments per request, refusing
+    /// more with `-32600`, and raise the request body limit to fit them.
+    /// Without it, attachments count against the default 2 MiB body
+    /// limit.
+    pub fn with_max_attachment_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_attachment_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sign every task result this server returns with `key`, so callers
+    /// holding its [`verifying_key`](crate::SigningKey::verifying_key) can
+    /// check where the result came from.
+    #[cfg(feature = "signing")]
+    pub fn with_signing_key(mut self, key: crate::SigningKey) -> Self {
+        self.signing_key = Some(Arc::new(key));
+        self
+    }
+
+    /// Inflate compressed requests, and compress JSON responses of at
+    /// least `compression.min_bytes` for callers whose `Accept-Encoding`
+    /// allows it, preferring `compression.encoding`.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: crate::Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Also read and answer calls in `codec`, besides JSON and the codecs
+    /// built in with the enabled features; see [`Codec`](crate::Codec).
+    pub fn with_codec(mut self, codec: Arc<dyn crate::Codec>) -> Self {
+        self.codecs = self.codecs.with(codec);
+        self
+    }
+
+    /// Serve HTTPS with `tls` instead of plain HTTP; see [`ServerTlsConfig`](crate::ServerTlsConfig).
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::ServerTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Number of task handlers running right now, for metrics. Queued
+    /// tasks are not counted.
+    pub fn in_flight_tasks(&self) -> usize {
+        self.in_flight.get()
+    }
+
+    pub fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn capabilities(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// Register the handler for `a2a/task` and `a2a/task/submit`. It gets
+    /// the task's [`TaskContext`] and input. With handlers registered per
+    /// action through [`on_action`](Self::on_action), it only gets the
+    /// tasks whose action none of them match.
+    pub fn handle_task<F, R>(&mut self, handler: F)
+    where
+        F: Fn(TaskContext, Value) -> R + Send + Sync + 'static,
+        R: IntoTaskOutput,
+    {
+        self.task_handler = Some(Arc::new(Box::new(move |context, input| {
+            let output = handler(context, input).into_task_output();
+            Box::pin(std::future::ready(output))
+        })));
+    }
+
+    /// Register a handler with the original `(action, input, sender)`
+    /// signature.
+    #[deprecated(note = "use `handle_task`, which passes a `TaskContext`")]
+    pub fn handle_task_legacy<F, R>(&mut self, handler: F)
+    where
+        F: Fn(String, Value, String) -> R + Send + Sync + 'static,
+        R: IntoTaskOutput,
+    {
+        self.handle_task(move |context, input| handler(context.action, input, context.sender));
+    }
+
+    /// Register a handler that can `.await` before producing its output,
+    /// e.g. to query a database or call another agent.
+    pub fn handle_task_async<F, Fut>(&mut self, handler: F)
+    where
+        F: Fn(TaskContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoTaskOutput,
+    {
+        self.task_handler = Some(Arc::new(Box::new(move |context, input| {
+            let output = handler(context, input);
+            Box::pin(async move { output.await.into_task_output() })
+        })));
+    }
+
+    /// Route tasks whose `action` is `action` to `handler`, ahead of the
+    /// catch-all [`handle_task`](Self::handle_task) handler. Without a
+    /// catch-all, a task with an action no handler matches fails with
+    /// `-32601` "Unknown action".
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AServer, TaskContext};
+    /// use serde_json::{json, Value};
+    ///
+    /// let mut server = A2AServer::new("calculator", "Calculator", vec!["add".to_string(), "echo".to_string()], 9001);
+    /// server.on_action("add", |_ctx: TaskContext, input: Value| {
+    ///     json!(input["a"].as_i64().unwrap_or(0) + input["b"].as_i64().unwrap_or(0))
+    /// });
+    /// server.on_action("echo", |_ctx: TaskContext, input: Value| input);
+    /// ```
+    pub fn on_action<F, R>(&mut self, action: &str, handler: F)
+    where
+        F: Fn(TaskContext, Value) -> R + Send + Sync + 'static,
+        R: IntoTaskOutput,
+    {
+        let handler: AsyncTaskHandler = Box::new(move |context, input| {
+            let output = handler(context, input).into_task_output();
+            Box::pin(std::future::ready(output))
+        });
+        self.action_handlers.insert(action.to_string(), Arc::new(handler));
+    }
+
+    /// Like [`on_action`](Self::on_action), for a handler that can
+    /// `.await`.
+    pub fn on_action_async<F, Fut>(&mut self, action: &str, handler: F)
+    where
+        F: Fn(TaskContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoTaskOutput,
+    {
+        let handler: AsyncTaskHandler = Box::new(move |context, input| {
+            let output = handler(context, input);
+            Box::pin(async move { output.await.into_task_output() })
+        });
+        self.action_handlers.insert(action.to_string(), Arc::new(handler));
+    }
+
+    /// Register a handler for `a2a/task/stream` that yields output
+    /// incrementally. Each item is sent to the caller as soon as it is
+    /// produced; an `Err` item is sent as the final event.
+    pub fn handle_task_stream<F, S>(&mut self, handler: F)
+    where
+        F: Fn(TaskContext, Value) -> S + Send + Sync + 'static,
+        S: Stream<Item = Result<Value, TaskError>> + Send + 'static,
+    {
+        self.stream_handler = Some(Arc::new(Box::new(move |context, input| {
+            Box::pin(handler(context, input))
+        })));
+    }
+
+    /// Answer the JSON-RPC method `name`, such as `a2a/stats`, with
+    /// `handler`, which gets the call's params (`null` if there are none).
+    /// An `Err` is sent back as the JSON-RPC error. Registered methods take
+    /// precedence over the built-in `a2a/task` family, so registering one
+    /// of those names replaces it; other unknown methods still fail with
+    /// `-32601`.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AServer, MethodContext};
+    /// use serde_json::{json, Value};
+    ///
+    /// let mut server = A2AServer::new("calculator", "Calculator", vec!["add".to_string()], 9001);
+    /// server.register_method("a2a/stats", |_ctx: MethodContext, _params: Value| async {
+    ///     json!({"tasksServed": 42})
+    /// });
+    /// ```
+    pub fn register_method<F, Fut>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(MethodContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoTaskOutput,
+    {
+        let handler: MethodHandler = Box::new(move |context, params| {
+            let output = handler(context, params);
+            Box::pin(async move { output.await.into_task_output() })
+        });
+        self.methods.insert(name.to_string(), Arc::new(handler));
+    }
+
+    /// Bind to `0.0.0.0:{port}` and serve JSON-RPC requests until the
+    /// process is killed.
+    pub async fn run(&self) -> Result<(), A2AError> {
+        self.run_with_shutdown(std::future::pending()).await
+    }
+
+    /// Like [`run`](Self::run), but stop gracefully once `shutdown`
+    /// completes: the listener closes, in-flight requests and submitted
+    /// tasks are allowed to finish, and then this returns.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(server: a2a::A2AServer) -> Result<(), a2a::A2AError> {
+    /// server
+    ///     .run_with_shutdown(async {
+    ///         tokio::signal::ctrl_c().await.ok();
+    ///     })
+    ///     .await
+    /// # }
+    /// ```
+    pub async fn run_with_shutdown<F>(&self, shutdown: F) -> Result<(), A2AError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (_addr, serve) = self.bind_with_shutdown(shutdown).await?;
+        serve.await
+    }
+
+    /// Bind the listener without serving yet, returning the address it is
+    /// bound to and the future that serves it. With port `0` the OS picks
+    /// a free port, which the address reports.
+    ///
+    /// Binding and serving are split so a caller can learn the port, and
+    /// be sure the listener accepts connections, before the server runs:
+    /// connections made in between wait until the future is driven. The
+    /// future owns everything it needs, so it can be spawned. It serves
+    /// until the process is killed, like [`run`](Self::run), and handlers
+    /// registered after `bind` are not used.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(server: a2a::A2AServer) -> Result<(), a2a::A2AError> {
+    /// let (addr, serve) = server.bind().await?;
+    /// tokio::spawn(serve);
+    /// let endpoint = format!("http://127.0.0.1:{}", addr.port());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bind(&self) -> Result<(SocketAddr, impl Future<Output = Result<(), A2AError>> + Send + 'static), A2AError> {
+        self.bind_with_shutdown(std::future::pending()).await
+    }
+
+    /// Like [`bind`](Self::bind), but the future stops gracefully once
+    /// `shutdown` completes, as in [`run_with_shutdown`](Self::run_with_shutdown).
+    pub async fn bind_with_shutdown<F>(
+        &self,
+        shutdown: F,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), A2AError>> + Send + 'static), A2AError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        #[cfg(feature = "tls")]
+        let acceptor = self.tls.as_ref().map(crate::ServerTlsConfig::acceptor).transpose()?;
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", self.port)).await?;
+        let addr = listener.local_addr()?;
+        let state = self.state();
+
+        #[cfg(feature = "tls")]
+        if let Some(acceptor) = acceptor {
+            info!(agent_id = %self.agent_id, %addr, "A2A server listening over TLS");
+            let listener = crate::tls::TlsListener::new(listener, acceptor)?;
+            return Ok((addr, serve(listener, state, shutdown).boxed()));
+        }
+
+        info!(agent_id = %self.agent_id, %addr, "A2A server listening");
+        Ok((addr, serve(listener, state, shutdown).boxed()))
+    }
+
+    fn state(&self) -> Arc<ServerState> {
+        Arc::new(ServerState {
+            agent_id: self.agent_id.clone(),
+            card: AgentInfo {
+                agent_id: self.agent_id.clone(),
+                name: self.name.clone(),
+                capabilities: self.capabilities.clone(),
+                endpoint: self.endpoint.clone().unwrap_or_default(),
+                registered_at: None,
+                version: self.version.clone(),
+                description: self.description.clone(),
+                tags: HashMap::new(),
+            },
+            scheme: self.scheme(),
+            started: Instant::now(),
+            method_namespace: self.method_namespace.clone(),
+            task_handler: self.task_handler.clone(),
+            action_handlers: self.action_handlers.clone(),
+            stream_handler: self.stream_handler.clone(),
+            methods: self.methods.clone(),
+            health_token: self.health_token.clone(),
+            tasks: TaskStore::default(),
+            idempotency: self
+                .idempotency
+                .map(|(ttl, max_entries)| IdempotencyCache::new(ttl, max_entries)),
+            limit: self
+                .concurrency
+                .map(|(max_concurrent_tasks, policy)| ConcurrencyLimit::new(max_concurrent_tasks, policy)),
+            input_guard: self.input_guard,
+            handler_timeout: self.handler_timeout,
+            action_timeouts: self.action_timeouts.clone(),
+            max_attachment_bytes: self.max_attachment_bytes,
+            authorizer: self.authorizer.clone(),
+            observers: Observers::new(self.observers.clone()),
+            codecs: self.codecs.clone(),
+            in_flight: self.in_flight.clone(),
+            cancellations: Cancellations::default(),
+            #[cfg(feature = "signing")]
+            signing_key: self.signing_key.clone(),
+            #[cfg(feature = "compression")]
+            compression: self.compression,
+        })
+    }
+
+    /// The URL scheme this server is reached by.
+    fn scheme(&self) -> &'static str {
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() {
+            return "https";
+        }
+        "http"
+    }
+
+    /// This server's handlers, answering calls without a listener.
+    pub(crate) fn local(&self) -> LocalServer {
+        LocalServer(self.state())
+    }
+}
+
+/// An [`A2AServer`] served in process, as mounted on an
+/// [`InMemoryTransport`](crate::InMemoryTransport).
+#[derive(Clone)]
+pub(crate) struct LocalServer(Arc<ServerState>);
+
+impl LocalServer {
+    pub(crate) async fn call(&self, request: JSONRPCRequest, attachments: Vec<Attachment>) -> JSONRPCResponse {
+        dispatch(&self.0, &HeaderMap::new(), request, attachments).await
+    }
+}
+
+/// Serve `listener` until `shutdown`, then wait for submitted tasks.
+async fn serve<L>(listener: L, state: Arc<ServerState>, shutdown: impl Future<Output = ()> + Send + 'static) -> Result<(), A2AError>
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    axum::serve(listener, router(state.clone()))
+        .with_graceful_shutdown(shutdown)
+        .await?;
+
+    info!(agent_id = %state.agent_id, "A2A server draining submitted tasks");
+    state.tasks.drain().await;
+    Ok(())
+}
+
+fn router(state: Arc<ServerState>) -> Router {
+    let router = Router::new()
+        .route("/", post(handle_rpc))
+        .route("/health", get(handle_health))
+        .route("/a2a/card", get(handle_card));
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", get(handle_metrics));
+    #[cfg(feature = "ws")]
+    let router = router.route("/ws", get(handle_ws));
+    let router = match state.max_attachment_bytes {
+        Some(max_bytes) => router.layer(DefaultBodyLimit::max(DEFAULT_BODY_LIMIT.saturating_add(max_bytes))),
+        None => router,
+    };
+    #[cfg(feature = "compression")]
+    let router = match state.compression {
+        Some(compression) => router.layer(axum::middleware::from_fn_with_state(compression, crate::compression::layer)),
+        None => router,
+    };
+    router.with_state(state)
+}
+
+#[cfg(feature = "metrics")]
+async fn handle_metrics() -> Response {
+    let content_type = [(header::CONTENT_TYPE, "text/plain; version=0.0.4")];
+    (content_type, metrics::render_metrics()).into_response()
+}
+
+async fn handle_health(State(state): State<Arc<ServerState>>, headers: HeaderMap) -> Response {
+    if let Some(token) = &state.health_token {
+        let expected = format!("Bearer {}", token);
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .is_some_and(|value| value.as_bytes() == expected.as_bytes());
+        if !authorized {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+
+    Json(json!({
+        "status": "ok",
+        "agent_id": state.agent_id,
+        "uptime_secs": state.started.elapsed().as_secs(),
+    }))
+    .into_response()
+}
+
+/// This agent's [`AgentInfo`], with capabilities and their schemas.
+async fn handle_card(State(state): State<Arc<ServerState>>, headers: HeaderMap) -> Response {
+    let mut card = state.card.clone();
+    if card.endpoint.is_empty() {
+        if let Some(host) = headers.get(header::HOST).and_then(|host| host.to_str().ok()) {
+            card.endpoint = format!("{}://{}", state.scheme, host);
+        }
+    }
+    Json(card).into_response()
+}
+
+async fn handle_rpc(State(state): State<Arc<ServerState>>, headers: HeaderMap, body: Bytes) -> Response {
+    let response = rpc_response(&state, &headers, &body);
+    #[cfg(feature = "otel")]
+    let response = crate::trace_context::serve(&state.agent_id, &headers, response);
+    response.await
+}
+
+async fn rpc_response(state: &Arc<ServerState>, headers: &HeaderMap, body: &Bytes) -> Response {
+    let (body, attachments) = match split_attachments(state, headers, body) {
+        Ok(split) => split,
+        Err(message) => return Json(error_response(Value::Null, INVALID_REQUEST, message)).into_response(),
+    };
+    let codec = state.codecs.for_request(headers);
+    let reply = state.codecs.for_response(headers, &codec);
+    let raw = match codec::decode(&codec, body) {
+        Ok(raw) => raw,
+        Err(e) => return codec::respond(&reply, &error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+    };
+
+    if let Value::Array(batch) = raw {
+        if !attachments.is_empty() {
+            return codec::respond(&reply, &error_response(Value::Null, INVALID_REQUEST, "Invalid request: batches cannot carry attachments".to_string()));
+        }
+        if batch.is_empty() {
+            return codec::respond(&reply, &error_response(Value::Null, INVALID_REQUEST, "Invalid request: empty batch".to_string()));
+        }
+
+        let responses = futures::future::join_all(batch.into_iter().map(|raw| handle_single(state, headers, raw))).await;
+        let responses: Vec<JSONRPCResponse> = responses.into_iter().flatten().collect();
+        if responses.is_empty() {
+            return StatusCode::NO_CONTENT.into_response();
+        }
+        return codec::respond(&reply, &responses);
+    }
+
+    let notification = is_notification(&raw);
+    let request = match parse_request(raw) {
+        Ok(request) => request,
+        Err(response) => return codec::respond(&reply, &response),
+    };
+    if notification {
+        dispatch(state, headers, request, attachments).await;
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    if state.local_method(&request.method) == Some("task/stream") {
+        return match stream_task(state, headers, request.params, attachments).await {
+            Ok(events) => events.into_response(),
+            Err(error) => codec::respond(&reply, &error_response(request.id, error.code, error.message)),
+        };
+    }
+
+    codec::respond(&reply, &dispatch(state, headers, request, attachments).await)
+}
+
+/// The JSON-RPC body of a request and the attachments that came with it,
+/// if it is `multipart/related`.
+fn split_attachments<'a>(
+    state: &ServerState,
+    headers: &HeaderMap,
+    body: &'a [u8],
+) -> Result<(&'a [u8], Vec<Attachment>), String> {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let Some(content_type) = content_type.filter(|v| attachment::is_multipart(v)) else {
+        return Ok((body, Vec::new()));
+    };
+    let (body, attachments) =
+        attachment::decode(content_type, body).map_err(|e| format!("Invalid request: malformed multipart body: {}", e))?;
+
+    let size: usize = attachments.iter().map(|attachment| attachment.bytes.len()).sum();
+    match state.max_attachment_bytes {
+        Some(max_bytes) if size > max_bytes => Err(format!("Invalid request: attachments exceed {} bytes", max_bytes)),
+        _ => Ok((body, attachments)),
+    }
+}
+
+/// Handle one member of a batch, answering it unless it is a
+/// notification. Streaming is not available in batches.
+async fn handle_single(state: &Arc<ServerState>, headers: &HeaderMap, raw: Value) -> Option<JSONRPCResponse> {
+    let notification = is_notification(&raw);
+    let response = match parse_request(raw) {
+        Ok(request) => dispatch(state, headers, request, Vec::new()).await,
+        Err(response) => return Some(*response),
+    };
+    (!notification).then_some(response)
+}
+
+/// Whether `raw` is sent as a notification: a request object without an
+/// `id`, which is handled but never answered. Invalid requests are still
+/// answered with an error.
+fn is_notification(raw: &Value) -> bool {
+    raw.as_object().is_some_and(|request| !request.contains_key("id"))
+}
+
+pub(crate) fn parse_request(raw: Value) -> Result<JSONRPCRequest, Box<JSONRPCResponse>> {
+    let id = raw.get("id").cloned().unwrap_or(Value::Null);
+    let request: JSONRPCRequest = serde_json::from_value(raw)
+        .map_err(|e| Box::new(error_response(id.clone(), INVALID_REQUEST, format!("Invalid request: {}", e))))?;
+
+    if request.jsonrpc != JSONRPC_VERSION {
+        let message = format!("Invalid request: jsonrpc must be \"{}\", got {:?}", JSONRPC_VERSION, request.jsonrpc);
+        return Err(Box::new(error_response(id, INVALID_REQUEST, message)));
+    }
+    Ok(request)
+}
+
+/// Answer `a2a/task/stream` with one SSE `chunk` event per item the
+/// handler yields, followed by `done`, or by `error` if an item fails.
+async fn stream_task(
+    state: &ServerState,
+    headers: &HeaderMap,
+    params: Option<Value>,
+    attachments: Vec<Attachment>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, JSONRPCError> {
+    let stream = start_stream(state, headers, params, attachments).await?;
+
+    let events = futures::stream::unfold(Some((stream, 0)), |progress| async move {
+        let (mut stream, index) = progress?;
+        match stream.next_chunk().await {
+            Some(Ok(data)) => {
+                let chunk = TaskChunk {
+                    task_id: stream.task_id.clone(),
+                    index,
+                    data,
+                };
+                let event = Event::default().event(CHUNK_EVENT).json_data(chunk);
+                Some((event, Some((stream, index + 1))))
+            }
+            Some(Err(e)) => {
+                let event = Event::default().event(ERROR_EVENT).json_data(rpc_error(e.code, e.message));
+                Some((event, None))
+            }
+            None => {
+                let event = Event::default().event(DONE_EVENT).json_data(stream.finished());
+                Some((event, None))
+            }
+        }
+    });
+
+    Ok(Sse::new(events))
+}
+
+/// A stream handler's output, with the slot it holds until it finishes
+/// or the caller disconnects.
+struct RunningStream {
+    task_id: String,
+    correlation_id: String,
+    deadline: Option<Instant>,
+    chunks: Pin<Box<dyn Stream<Item = Result<Value, TaskError>> + Send>>,
+    cancellation: CancellationToken,
+    observers: Observers,
+    _registration: Registration,
+    _slot: Slot,
+}
+
+impl RunningStream {
+    /// The handler's next item, produced with the task's correlation ID and
+    /// deadline in scope. Observers hear of the end of the stream.
+    async fn next_chunk(&mut self) -> Option<Result<Value, TaskError>> {
+        let next = deadline::scope(self.deadline, self.chunks.next());
+        let chunk = with_correlation_id(self.correlation_id.clone(), next).await;
+        match &chunk {
+            Some(Ok(_)) => {}
+            Some(Err(e)) => self.observers.failed(&self.task_id, e).await,
+            None => self.observers.finished(&self.finished()).await,
+        }
+        chunk
+    }
+
+    /// The result reported once the handler's stream ends.
+    fn finished(&self) -> TaskResult {
+        TaskResult::new(&self.task_id, finished_status(&self.cancellation, TaskStatus::Completed))
+    }
+}
+
+async fn start_stream(
+    state: &ServerState,
+    headers: &HeaderMap,
+    params: Option<Value>,
+    attachments: Vec<Attachment>,
+) -> Result<RunningStream, JSONRPCError> {
+    authorize(state, headers, &params).await?;
+    let handler = state
+        .stream_handler
+        .as_ref()
+        .ok_or_else(|| rpc_error(METHOD_NOT_FOUND, "No stream handler registered".to_string()))?;
+    let params: TaskParams = parse_params(params)?;
+    check_input(state, &params)?;
+    let (context, input, registration) = task_context(state, params, headers, attachments);
+    let task_id = context.task_id.clone();
+    let correlation_id = context.correlation_id.clone();
+    let deadline = context.deadline;
+    let cancellation = context.cancellation.clone();
+    state.observers.received(&context).await;
+
+    let admitted = async {
+        let slot = admit(state, context.priority).await.map_err(task_error)?;
+        check_deadline(&context)?;
+        Ok(slot)
+    };
+    let slot = match admitted.await {
+        Ok(slot) => slot,
+        Err(e) => {
+            state.observers.failed(&task_id, &e).await;
+            return Err(rpc_error(e.code, e.message));
+        }
+    };
+    state.observers.started(&context).await;
+    let chunks = correlation::sync_scope(correlation_id.clone(), || {
+        deadline::sync_scope(deadline, || handler(context, input))
+    });
+    Ok(RunningStream {
+        task_id,
+        correlation_id,
+        deadline,
+        chunks,
+        cancellation,
+        observers: state.observers.clone(),
+        _registration: registration,
+        _slot: slot,
+    })
+}
+
+#[cfg(feature = "ws")]
+async fn handle_ws(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    upgrade: Result<axum::extract::WebSocketUpgrade, axum::extract::ws::rejection::WebSocketUpgradeRejection>,
+) -> Response {
+    let Ok(upgrade) = upgrade else {
+        return (StatusCode::BAD_REQUEST, "Expected a WebSocket upgrade").into_response();
+    };
+    ws::accept(upgrade, move |text, outbox| handle_ws_message(state.clone(), headers.clone(), text, outbox))
+}
+
+/// Answer one JSON-RPC message received over a WebSocket.
+#[cfg(feature = "ws")]
+async fn handle_ws_message(state: Arc<ServerState>, headers: HeaderMap, text: String, outbox: ws::Outbox) {
+    let raw: Value = match serde_json::from_str(&text) {
+        Ok(raw) => raw,
+        Err(e) => {
+            outbox.send(&error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e)));
+            return;
+        }
+    };
+    let notification = is_notification(&raw);
+    let request = match parse_request(raw) {
+        Ok(request) => request,
+        Err(response) => {
+            outbox.send(&response);
+            return;
+        }
+    };
+
+    let response = if state.local_method(&request.method) == Some("task/stream") {
+        ws_stream_task(&state, &headers, request, &outbox).await
+    } else {
+        dispatch(&state, &headers, request, Vec::new()).await
+    };
+    if !notification {
+        outbox.send(&response);
+    }
+}
+
+/// Push each chunk as an `a2a/task/chunk` notification, then answer with
+/// the final result.
+#[cfg(feature = "ws")]
+async fn ws_stream_task(
+    state: &ServerState,
+    headers: &HeaderMap,
+    request: JSONRPCRequest,
+    outbox: &ws::Outbox,
+) -> JSONRPCResponse {
+    let mut stream = match start_stream(state, headers, request.params, Vec::new()).await {
+        Ok(stream) => stream,
+        Err(error) => return error_response(request.id, error.code, error.message),
+    };
+
+    let mut index = 0;
+    loop {
+        match stream.next_chunk().await {
+            Some(Ok(data)) => {
+                let chunk = TaskChunk {
+                    task_id: stream.task_id.clone(),
+                    index,
+                    data,
+                };
+                if !outbox.notify(&format!("{}/task/chunk", state.method_namespace), &chunk) {
+                    return error_response(request.id, INTERNAL_ERROR, "Connection closed".to_string());
+                }
+                index += 1;
+            }
+            Some(Err(e)) => return error_response(request.id, e.code, e.message),
+            None => {
+                return match to_result(stream.finished()) {
+                    Ok(result) => success_response(request.id, result),
+                    Err(error) => error_response(request.id, error.code, error.message),
+                }
+            }
+        }
+    }
+}
+
+/// Answer `request`. `attachments` go to the handler of a task and are
+/// dropped for other methods.
+async fn dispatch(
+    state: &Arc<ServerState>,
+    headers: &HeaderMap,
+    request: JSONRPCRequest,
+    attachments: Vec<Attachment>,
+) -> JSONRPCResponse {
+    debug!(method = %request.method, "handling request");
+    record!("a2a.method", request.method.as_str());
+    if let Some(handler) = state.methods.get(&request.method) {
+        return call_method(handler, headers, request).await;
+    }
+
+    let key = idempotency_key(&request.params);
+    let local_method = state.local_method(&request.method);
+    let result = match local_method {
+        Some("task") => {
+            async {
+                authorize(state, headers, &request.params).await?;
+                deduplicated(state, key, run_task(state, headers, request.params, attachments)).await
+            }
+            .await
+        }
+        Some("task/submit") => {
+            async {
+                authorize(state, headers, &request.params).await?;
+                deduplicated(state, key, submit_task(state, headers, request.params, attachments)).await
+            }
+            .await
+        }
+        Some("task/status") => {
+            async {
+                let params: TaskStatusParams = parse_params(request.params)?;
+                authorize_lookup(state, headers, &request.method, &params).await?;
+                task_status(state, &params)
+            }
+            .await
+        }
+        Some("task/cancel") => {
+            async {
+                let params: TaskStatusParams = parse_params(request.params)?;
+                authorize_lookup(state, headers, &request.method, &params).await?;
+                cancel_task(state, &params)
+            }
+            .await
+        }
+        _ => Err(rpc_error(METHOD_NOT_FOUND, format!("Method not found: {}", request.method))),
+    };
+
+    // Unknown method names come from the caller; keep them out of the
+    // label set so they cannot grow it without bound.
+    let method = match local_method {
+        Some("task" | "task/submit" | "task/status" | "task/cancel") => request.method.as_str(),
+        _ => "unknown",
+    };
+    metrics::request_finished(metrics::SERVER, method, result.is_ok());
+
+    match result {
+        Ok(result) => success_response(request.id, result),
+        Err(error) => error_response(request.id, error.code, error.message),
+    }
+}
+
+/// Answer `request` with a method registered through
+/// [`A2AServer::register_method`].
+async fn call_method(handler: &MethodHandler, headers: &HeaderMap, request: JSONRPCRequest) -> JSONRPCResponse {
+    let context = MethodContext {
+        method: request.method.clone(),
+        headers: headers.clone(),
+    };
+    let result = handler(context, request.params.unwrap_or(Value::Null)).await;
+    metrics::request_finished(metrics::SERVER, &request.method, result.is_ok());
+
+    match result {
+        Ok(result) => success_response(request.id, result),
+        Err(error) => error_response(request.id, error.code, error.message),
+    }
+}
+
+/// The fields of [`TaskParams`] naming a task, read the same way, so
+/// either spelling of `taskId` counts.
+#[derive(Deserialize)]
+struct TaskKey {
+    #[serde(rename = "taskId", alias = "task_id")]
+    task_id: String,
+    sender: String,
+}
+
+/// The sender and `taskId` of a task call, if present.
+fn idempotency_key(params: &Option<Value>) -> Option<(String, String)> {
+    let key = TaskKey::deserialize(params.as_ref()?).ok()?;
+    Some((key.sender, key.task_id))
+}
+
+/// Run `task` through the idempotency cache, if one is configured.
+async fn deduplicated<F>(state: &ServerState, key: Option<(String, String)>, task: F) -> Result<Value, JSONRPCError>
+where
+    F: Future<Output = Attempt>,
+{
+    match (&state.idempotency, key) {
+        (Some(cache), Some((sender, task_id))) => cache.run(&sender, &task_id, || task).await,
+        _ => task.await.and_then(|outcome| outcome),
+    }
+}
+
+async fn run_task(
+    state: &ServerState,
+    headers: &HeaderMap,
+    params: Option<Value>,
+    attachments: Vec<Attachment>,
+) -> Attempt {
+    let params: TaskParams = parse_params(params)?;
+    check_input(state, &params)?;
+    let handler = task_handler(state, &params.action)?;
+    let (context, input, _registration) = task_context(state, params, headers, attachments);
+    let task_id = context.task_id.clone();
+    let cancellation = context.cancellation.clone();
+    state.observers.received(&context).await;
+
+    let admitted = async {
+        let slot = admit(state, context.priority).await.map_err(task_error)?;
+        check_deadline(&context)?;
+        Ok(slot)
+    }
+    .await;
+    let refused = admitted.is_err();
+    let output = match admitted {
+        Ok(_slot) => {
+            state.observers.started(&context).await;
+            let timeout = state.handler_timeout(&context.action);
+            call_handler(handler, context, input, timeout).await
+        }
+        Err(e) => Err(e),
+    };
+
+    let result = handler_result(&task_id, output, &cancellation);
+    state.observers.finished(&result).await;
+    let outcome = match (result.status, &result.error) {
+        (TaskStatus::Failed, Some(e)) => Err(rpc_error(e.code, e.message.clone())),
+        _ => task_result(state, result),
+    };
+    match outcome {
+        Err(error) if refused => Err(error),
+        outcome => Ok(outcome),
+    }
+}
+
+/// Start the handler in the background and answer straight away; the
+/// caller polls `a2a/task/status` for the outcome.
+async fn submit_task(
+    state: &Arc<ServerState>,
+    headers: &HeaderMap,
+    params: Option<Value>,
+    attachments: Vec<Attachment>,
+) -> Attempt {
+    let params: TaskParams = parse_params(params)?;
+    check_input(state, &params)?;
+    let handler = task_handler(state, &params.action)?.clone();
+    let (context, input, registration) = task_context(state, params, headers, attachments);
+    let task_id = context.task_id.clone();
+    let sender = context.sender.clone();
+    let cancellation = context.cancellation.clone();
+    state.observers.received(&context).await;
+
+    let admitted = check_deadline(&context).and_then(|()| {
+        state
+            .in_flight
+            .try_admit(state.limit.as_ref())
+            .map_err(|_| task_error(server_busy()))
+    });
+    let slot = match admitted {
+        Ok(slot) => slot,
+        Err(e) => {
+            state.observers.failed(&task_id, &e).await;
+            return Err(rpc_error(e.code, e.message));
+        }
+    };
+    state.tasks.set(&sender, TaskResult::new(&task_id, TaskStatus::Pending));
+
+    let shared = state.clone();
+    let running_id = task_id.clone();
+    state.tasks.spawn(async move {
+        let _registration = registration;
+        let _slot = match slot {
+            Some(slot) => slot,
+            None => shared.in_flight.wait(shared.limit.as_ref(), context.priority).await,
+        };
+        let result = if cancellation.is_cancelled() {
+            TaskResult::new(&running_id, TaskStatus::Cancelled)
+        } else if let Err(e) = check_deadline(&context) {
+            // The deadline may have passed while the task waited for a slot.
+            handler_result(&running_id, Err(e), &cancellation)
+        } else {
+            shared.tasks.set(&sender, TaskResult::new(&running_id, TaskStatus::Running));
+            shared.observers.started(&context).await;
+            let timeout = shared.handler_timeout(&context.action);
+            let output = call_handler(&handler, context, input, timeout).await;
+            handler_result(&running_id, output, &cancellation)
+        };
+        // Observers hear of the outcome before pollers can see it.
+        shared.observers.finished(&result).await;
+        shared.tasks.set(&sender, result);
+    });
+
+    Ok(task_result(state, TaskResult::new(&task_id, TaskStatus::Accepted)))
+}
+
+/// The status of a task the caller submitted. Tasks of other senders
+/// are unknown to it.
+fn task_status(state: &ServerState, params: &TaskStatusParams) -> Result<Value, JSONRPCError> {
+    match state.tasks.get(&params.sender, &params.task_id) {
+        Some(result) => task_result(state, result),
+        None => Err(rpc_error(INVALID_PARAMS, format!("Unknown task: {}", params.task_id))),
+    }
+}
+
+/// Ask a running task to stop. A task that already finished is reported
+/// unchanged.
+fn cancel_task(state: &ServerState, params: &TaskStatusParams) -> Result<Value, JSONRPCError> {
+    if state.cancellations.cancel(&params.sender, &params.task_id) {
+        info!(task_id = %params.task_id, sender = %params.sender, "cancelling task");
+        return task_result(state, TaskResult::new(&params.task_id, TaskStatus::Cancelled));
+    }
+    task_status(state, params)
+}
+
+/// Split `params` into the handler's context and input, and make the task
+/// cancellable for as long as the returned registration is held. A caller
+/// that sent no correlation ID gets a fresh one.
+fn task_context(
+    state: &ServerState,
+    params: TaskParams,
+    headers: &HeaderMap,
+    attachments: Vec<Attachment>,
+) -> (TaskContext, Value, Registration) {
+    let (cancellation, registration) = state.cancellations.register(&params.sender, &params.task_id);
+    let (context, input) = context(params, headers, cancellation, attachments);
+    debug!(task_id = %context.task_id, correlation_id = %context.correlation_id, action = %context.action, "handling task");
+    record!("a2a.task_id", context.task_id.as_str());
+    (context, input, registration)
+}
+
+fn context(
+    params: TaskParams,
+    headers: &HeaderMap,
+    cancellation: CancellationToken,
+    attachments: Vec<Attachment>,
+) -> (TaskContext, Value) {
+    let correlation_id = params
+        .correlation_id
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let context = TaskContext {
+        task_id: params.task_id,
+        action: params.action,
+        sender: params.sender,
+        correlation_id,
+        headers: headers.clone(),
+        cancellation,
+        deadline: params.deadline_ms.map(|ms| deadline::after(Duration::from_millis(ms))),
+        attachments,
+        version: params.version,
+        priority: params.priority.unwrap_or(DEFAULT_PRIORITY),
+    };
+    (context, params.input)
+}
+
+/// Refuse a task the server's [`Authorizer`] rejects. This runs ahead of
+/// the idempotency cache, so a refused caller cannot read a remembered
+/// outcome by reusing another sender's `taskId`, and is not itself
+/// remembered.
+async fn authorize(state: &ServerState, headers: &HeaderMap, params: &Option<Value>) -> Result<(), JSONRPCError> {
+    let Some(authorizer) = &state.authorizer else {
+        return Ok(());
+    };
+    let params: TaskParams = parse_params(params.clone())?;
+    let (context, _) = context(params, headers, CancellationToken::new(), Vec::new());
+    authorized(authorizer.as_ref(), &context, headers).await
+}
+
+/// Refuse a status or cancel call the server's [`Authorizer`] rejects,
+/// asking it as about a task of the caller's whose action is the method
+/// called, e.g. `a2a/task/status`.
+async fn authorize_lookup(
+    state: &ServerState,
+    headers: &HeaderMap,
+    method: &str,
+    params: &TaskStatusParams,
+) -> Result<(), JSONRPCError> {
+    let Some(authorizer) = &state.authorizer else {
+        return Ok(());
+    };
+    let params = TaskParams {
+        task_id: params.task_id.clone(),
+        action: method.to_string(),
+        sender: params.sender.clone(),
+        input: Value::Null,
+        correlation_id: None,
+        deadline_ms: None,
+        version: None,
+        priority: None,
+    };
+    let (context, _) = context(params, headers, CancellationToken::new(), Vec::new());
+    authorized(authorizer.as_ref(), &context, headers).await
+}
+
+async fn authorized(
+    authorizer: &dyn Authorizer,
+    context: &TaskContext,
+    headers: &HeaderMap,
+) -> Result<(), JSONRPCError> {
+    authorizer.authorize(context, headers).await.map_err(|e| {
+        warn!(task_id = %context.task_id, sender = %context.sender, reason = %e, "rejecting call: unauthorized");
+        rpc_error(UNAUTHORIZED, format!("Unauthorized: {}", e))
+    })
+}
+
+/// Refuse a task whose input breaks the server's [`InputGuard`].
+fn check_input(state: &ServerState, params: &TaskParams) -> Result<(), JSONRPCError> {
+    state.input_guard.check(&params.input).map_err(|message| rpc_error(INVALID_PARAMS, message))
+}
+
+/// Refuse a task whose caller has no time left for it.
+fn check_deadline(context: &TaskContext) -> Result<(), TaskError> {
+    match context.remaining_budget() {
+        Some(budget) if budget.is_zero() => {
+            warn!(task_id = %context.task_id, sender = %context.sender, "rejecting task: deadline exceeded");
+            Err(TaskError::new(DEADLINE_EXCEEDED, format!("Deadline exceeded before task {} started", context.task_id)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// What a handler run amounts to. A task whose cancellation was requested
+/// meanwhile counts as cancelled, whatever the handler returned.
+fn handler_result(task_id: &str, output: Result<Value, TaskError>, cancellation: &CancellationToken) -> TaskResult {
+    let mut result = TaskResult::new(task_id, TaskStatus::Completed);
+    match output {
+        Ok(output) => result.output = Some(output),
+        Err(e) => {
+            result.status = TaskStatus::Failed;
+            result.error = Some(e);
+        }
+    }
+    result.status = finished_status(cancellation, result.status);
+    result
+}
+
+fn finished_status(cancellation: &CancellationToken, status: TaskStatus) -> TaskStatus {
+    if cancellation.is_cancelled() {
+        TaskStatus::Cancelled
+    } else {
+        status
+    }
+}
+
+/// Run `handler` with the task's correlation ID and deadline in scope,
+/// both while it builds its future and while that future runs, giving up
+/// on the future after `timeout`.
+async fn call_handler(
+    handler: &AsyncTaskHandler,
+    context: TaskContext,
+    input: Value,
+    timeout: Option<Duration>,
+) -> Result<Value, TaskError> {
+    let started = Instant::now();
+    let action = context.action.clone();
+    let correlation_id = context.correlation_id.clone();
+    let deadline = context.deadline;
+    let output = correlation::sync_scope(correlation_id.clone(), || {
+        deadline::sync_scope(deadline, || handler(context, input))
+    });
+    let output = with_correlation_id(correlation_id, deadline::scope(deadline, output));
+    let output = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, output).await.unwrap_or_else(|_| {
+            warn!(%action, ?timeout, "task handler timed out");
+            Err(TaskError::failed(format!("Task timeout: handler ran longer than {:?}", timeout)))
+        }),
+        None => output.await,
+    };
+
+    let status = if output.is_ok() { TaskStatus::Completed } else { TaskStatus::Failed };
+    metrics::task_finished(metrics::SERVER, &action, status.as_str(), started.elapsed());
+    output
+}
+
+/// Wait for, or be refused, a slot to run a handler in.
+async fn admit(state: &ServerState, priority: u8) -> Result<Slot, JSONRPCError> {
+    match state.in_flight.try_admit(state.limit.as_ref()) {
+        Ok(Some(slot)) => Ok(slot),
+        Ok(None) => Ok(state.in_flight.wait(state.limit.as_ref(), priority).await),
+        Err(()) => Err(server_busy()),
+    }
+}
+
+/// `error` as a handler would have failed with it, for observers.
+fn task_error(error: JSONRPCError) -> TaskError {
+    TaskError::new(error.code, error.message)
+}
+
+fn server_busy() -> JSONRPCError {
+    warn!("rejecting task: too many concurrent tasks");
+    rpc_error(SERVER_BUSY, "Server busy: too many concurrent tasks".to_string())
+}
+
+/// The handler registered for `action`, or else the catch-all one.
+fn task_handler<'a>(state: &'a ServerState, action: &str) -> Result<&'a Arc<AsyncTaskHandler>, JSONRPCError> {
+    if let Some(handler) = state.action_handlers.get(action) {
+        return Ok(handler);
+    }
+    match &state.task_handler {
+        Some(handler) => Ok(handler),
+        None if state.action_handlers.is_empty() => {
+            Err(rpc_error(METHOD_NOT_FOUND, "No task handler registered".to_string()))
+        }
+        None => Err(rpc_error(METHOD_NOT_FOUND, format!("Unknown action: {}", action))),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, JSONRPCError> {
+    serde_json::from_value(params.unwrap_or(Value::Null))
+        .map_err(|e| rpc_error(INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+/// `result` as a JSON-RPC result, signed if the server has a key.
+fn task_result(state: &ServerState, result: TaskResult) -> Result<Value, JSONRPCError> {
+    #[cfg(feature = "signing")]
+    let result = match &state.signing_key {
+        Some(key) => key.sign(result).map_err(|e| rpc_error(INTERNAL_ERROR, e.to_string()))?,
+        None => result,
+    };
+    #[cfg(not(feature = "signing"))]
+    let _ = state;
+    to_result(result)
+}
+
+fn to_result<T: Serialize>(result: T) -> Result<Value, JSONRPCError> {
+    serde_json::to_value(result).map_err(|e| rpc_error(INTERNAL_ERROR, e.to_string()))
+}
+
+fn rpc_error(code: i32, message: String) -> JSONRPCError {
+    JSONRPCError { code, message, data: None }
+}
+
+pub(crate) fn success_response(id: Value, result: Value) -> JSONRPCResponse {
+    JSONRPCResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        result: Some(result),
+        error: None,
+    }
+}
+
+pub(crate) fn error_response(id: Value, code: i32, message: String) -> JSONRPCResponse {
+    JSONRPCResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        result: None,
+        error: Some(JSONRPCError { code, message, data: None }),
+    }
+}
+
+// ============ Convenience ============
+
+pub async fn run_server<F, R>(agent_id: &str, name: &str, capabilities: Vec<String>, port: u16, handler: F) -> Result<(), A2AError>
+where
+    F: Fn(TaskContext, Value) -> R + Send + Sync + 'static,
+    R: IntoTaskOutput,
+{
+    let mut server = A2AServer::new(agent_id, name, capabilities, port);
+    server.handle_task(handler);
+    server.run().await
+}
@@ -0,0 +1,148 @@
+//! Ed25519 signatures over task results (`signing` feature).
+//!
+//! A server given a [`SigningKey`] signs every [`TaskResult`] it returns.
+//! An agent given the target's [`VerifyingKey`] rejects results from that
+//! target that are unsigned, signed with another key, or altered on the
+//! way, with [`A2AError::SignatureInvalid`].
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{A2AError, TaskResult};
+
+/// The signature a server attaches to a [`TaskResult`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultSignature {
+    /// Names the key that made the signature, so keys can be rotated.
+    #[serde(rename = "keyId", alias = "key_id")]
+    pub key_id: String,
+    /// Base64-encoded Ed25519 signature over the result's JSON, without
+    /// its `signature` field.
+    pub signature: String,
+}
+
+/// An Ed25519 private key for [`A2AServer::with_signing_key`](crate::A2AServer::with_signing_key).
+///
+/// The `Debug` output never contains the private key.
+///
+/// ```
+/// use a2a::{A2AAgent, A2AServer, SigningKey};
+///
+/// let key = SigningKey::generate("calc-2024")?;
+/// // Persist `key.pkcs8()` to reload the key with `SigningKey::from_pkcs8`.
+/// let verifying_key = key.verifying_key();
+///
+/// let server = A2AServer::new("calc", "Calculator", vec!["add".to_string()], 9001).with_signing_key(key);
+/// let agent = A2AAgent::new("client", "Client", vec![]).with_verifying_key("calc", verifying_key);
+/// # Ok::<(), a2a::A2AError>(())
+/// ```
+pub struct SigningKey {
+    key_id: String,
+    pair: Ed25519KeyPair,
+    pkcs8: Vec<u8>,
+}
+
+impl SigningKey {
+    /// Generate a fresh key named `key_id`.
+    pub fn generate(key_id: impl Into<String>) -> Result<Self, A2AError> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| A2AError::InvalidKey("could not generate an Ed25519 key".to_string()))?;
+        Self::from_pkcs8(key_id, pkcs8.as_ref())
+    }
+
+    /// Load the key named `key_id` from a PKCS #8 v2 DER document, as
+    /// returned by [`pkcs8`](Self::pkcs8).
+    pub fn from_pkcs8(key_id: impl Into<String>, pkcs8: &[u8]) -> Result<Self, A2AError> {
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8).map_err(|e| A2AError::InvalidKey(e.to_string()))?;
+        Ok(Self {
+            key_id: key_id.into(),
+            pair,
+            pkcs8: pkcs8.to_vec(),
+        })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// The private key as PKCS #8 v2 DER, for storing it.
+    pub fn pkcs8(&self) -> &[u8] {
+        &self.pkcs8
+    }
+
+    /// The matching public key, to hand to agents calling this server.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey::new(self.key_id.clone(), self.pair.public_key().as_ref())
+    }
+
+    /// `result` with a fresh signature in place of any old one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn sign(&self, mut result: TaskResult) -> Result<TaskResult, serde_json::Error> {
+        result.signature = None;
+        let signature = self.pair.sign(&serde_json::to_vec(&result)?);
+        result.signature = Some(ResultSignature {
+            key_id: self.key_id.clone(),
+            signature: STANDARD.encode(signature.as_ref()),
+        });
+        Ok(result)
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey").field("key_id", &self.key_id).finish_non_exhaustive()
+    }
+}
+
+/// An Ed25519 public key for [`A2AAgent::with_verifying_key`](crate::A2AAgent::with_verifying_key).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey {
+    key_id: String,
+    public_key: Vec<u8>,
+}
+
+impl VerifyingKey {
+    /// The 32-byte public key named `key_id`.
+    pub fn new(key_id: impl Into<String>, public_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            public_key: public_key.into(),
+        }
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Check that `result`, said to come from `agent_id`, carries a valid
+    /// signature made with this key.
+    pub(crate) fn verify(&self, agent_id: &str, result: &TaskResult) -> Result<(), A2AError> {
+        let invalid = |reason: &str| A2AError::SignatureInvalid {
+            agent_id: agent_id.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let signature = result.signature.as_ref().ok_or_else(|| invalid("result is not signed"))?;
+        if signature.key_id != self.key_id {
+            return Err(invalid(&format!("signed with unknown key {:?}", signature.key_id)));
+        }
+        let bytes = STANDARD
+            .decode(&signature.signature)
+            .map_err(|_| invalid("signature is not valid base64"))?;
+
+        let mut unsigned = result.clone();
+        unsigned.signature = None;
+        let message = serde_json::to_vec(&unsigned)?;
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(&message, &bytes)
+            .map_err(|_| invalid("signature does not match the result"))
+    }
+}
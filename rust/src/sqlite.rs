@@ -0,0 +1,151 @@
+//! A [`RegistryStore`] persisted to SQLite through `rusqlite`.
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{A2AError, AgentInfo, RegistryStore, StoredAgent};
+
+/// Keeps a directory's agents in an SQLite database, so they survive a
+/// restart. Requires the `sqlite` feature, which builds SQLite in.
+///
+/// ```rust,no_run
+/// # fn example() -> Result<(), a2a::A2AError> {
+/// use a2a::{A2ADirectory, SqliteRegistryStore};
+/// use std::sync::Arc;
+///
+/// let store = SqliteRegistryStore::open("directory.db")?;
+/// let directory = A2ADirectory::new(8080).with_store(Arc::new(store));
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqliteRegistryStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteRegistryStore {
+    /// Open the database at `path`, creating it and its `agents` table if
+    /// needed. `":memory:"` opens a private in-memory database.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, A2AError> {
+        let connection = Connection::open(path).map_err(store_error)?;
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS agents (
+                    agent_id TEXT PRIMARY KEY,
+                    info TEXT NOT NULL,
+                    registered_ms INTEGER NOT NULL
+                )",
+                [],
+            )
+            .map_err(store_error)?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run `f` on the connection off the async runtime.
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, A2AError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, A2AError> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || f(&connection.lock().unwrap()))
+            .await
+            .map_err(|e| A2AError::Store(e.to_string()))?
+    }
+}
+
+#[async_trait]
+impl RegistryStore for SqliteRegistryStore {
+    async fn put(&self, stored: StoredAgent) -> Result<(), A2AError> {
+        let info = serde_json::to_string(&stored.agent)?;
+        self.with_connection(move |connection| {
+            connection
+                .execute(
+                    "INSERT OR REPLACE INTO agents (agent_id, info, registered_ms) VALUES (?1, ?2, ?3)",
+                    params![stored.agent.agent_id, info, to_millis(stored.registered)],
+                )
+                .map_err(store_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove(&self, agent_id: &str) -> Result<(), A2AError> {
+        let agent_id = agent_id.to_string();
+        self.with_connection(move |connection| {
+            connection
+                .execute("DELETE FROM agents WHERE agent_id = ?1", [agent_id])
+                .map_err(store_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get(&self, agent_id: &str) -> Result<Option<StoredAgent>, A2AError> {
+        let agent_id = agent_id.to_string();
+        self.with_connection(move |connection| {
+            let row = connection
+                .query_row(
+                    "SELECT info, registered_ms FROM agents WHERE agent_id = ?1",
+                    [agent_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(store_error)?;
+            row.map(stored).transpose()
+        })
+        .await
+    }
+
+    async fn list(&self) -> Result<Vec<StoredAgent>, A2AError> {
+        self.with_connection(|connection| {
+            let mut statement = connection
+                .prepare("SELECT info, registered_ms FROM agents ORDER BY agent_id")
+                .map_err(store_error)?;
+            let rows = statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(store_error)?;
+            let mut agents = Vec::new();
+            for row in rows {
+                agents.push(stored(row.map_err(store_error)?)?);
+            }
+            Ok(agents)
+        })
+        .await
+    }
+
+    async fn purge(&self, cutoff: SystemTime) -> Result<usize, A2AError> {
+        self.with_connection(move |connection| {
+            connection
+                .execute("DELETE FROM agents WHERE registered_ms < ?1", [to_millis(cutoff)])
+                .map_err(store_error)
+        })
+        .await
+    }
+}
+
+/// An `(info, registered_ms)` row read back into an agent.
+fn stored((info, registered_ms): (String, i64)) -> Result<StoredAgent, A2AError> {
+    let agent: AgentInfo = serde_json::from_str(&info)?;
+    Ok(StoredAgent {
+        agent,
+        registered: from_millis(registered_ms),
+    })
+}
+
+fn store_error(e: rusqlite::Error) -> A2AError {
+    A2AError::Store(e.to_string())
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as i64)
+}
+
+fn from_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
@@ -0,0 +1,80 @@
+//! Where an [`A2ADirectory`](crate::A2ADirectory) keeps its registrations.
+
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::{A2AError, AgentInfo};
+
+/// An agent as kept by a [`RegistryStore`], with the time it last
+/// registered.
+#[derive(Debug, Clone)]
+pub struct StoredAgent {
+    pub agent: AgentInfo,
+    pub registered: SystemTime,
+}
+
+/// Storage behind a directory. Registration, deregistration, lookup and
+/// discovery all go through it, so a persistent store keeps agents
+/// across restarts.
+///
+/// [`MemoryRegistryStore`] is the default; enable the `sqlite` feature
+/// for `SqliteRegistryStore`.
+#[async_trait]
+pub trait RegistryStore: Send + Sync {
+    /// Insert `stored`, replacing any agent with the same `agent_id`.
+    async fn put(&self, stored: StoredAgent) -> Result<(), A2AError>;
+
+    /// Remove `agent_id`. Removing an unknown agent is not an error.
+    async fn remove(&self, agent_id: &str) -> Result<(), A2AError>;
+
+    async fn get(&self, agent_id: &str) -> Result<Option<StoredAgent>, A2AError>;
+
+    /// Every stored agent, ordered by `agent_id`.
+    async fn list(&self) -> Result<Vec<StoredAgent>, A2AError>;
+
+    /// Remove agents that last registered before `cutoff` and return how
+    /// many there were.
+    async fn purge(&self, cutoff: SystemTime) -> Result<usize, A2AError>;
+}
+
+/// Keeps registrations in memory; they are lost when the process exits.
+#[derive(Default)]
+pub struct MemoryRegistryStore {
+    agents: Mutex<BTreeMap<String, StoredAgent>>,
+}
+
+impl MemoryRegistryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RegistryStore for MemoryRegistryStore {
+    async fn put(&self, stored: StoredAgent) -> Result<(), A2AError> {
+        self.agents.lock().unwrap().insert(stored.agent.agent_id.clone(), stored);
+        Ok(())
+    }
+
+    async fn remove(&self, agent_id: &str) -> Result<(), A2AError> {
+        self.agents.lock().unwrap().remove(agent_id);
+        Ok(())
+    }
+
+    async fn get(&self, agent_id: &str) -> Result<Option<StoredAgent>, A2AError> {
+        Ok(self.agents.lock().unwrap().get(agent_id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<StoredAgent>, A2AError> {
+        Ok(self.agents.lock().unwrap().values().cloned().collect())
+    }
+
+    async fn purge(&self, cutoff: SystemTime) -> Result<usize, A2AError> {
+        let mut agents = self.agents.lock().unwrap();
+        let before = agents.len();
+        agents.retain(|_, stored| stored.registered >= cutoff);
+        Ok(before - agents.len())
+    }
+}
@@ -0,0 +1,148 @@
+//! Streaming task results delivered as server-sent events.
+//!
+//! `a2a/task/stream` answers with `text/event-stream`. Each `chunk` event
+//! carries one [`TaskChunk`] as JSON, an `error` event carries a JSON-RPC
+//! error object and ends the stream, and a final `done` event marks a
+//! clean finish.
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use crate::{A2AError, JSONRPCError};
+
+/// One piece of incremental task output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskChunk {
+    #[serde(rename = "taskId", alias = "task_id")]
+    pub task_id: String,
+    /// Position of this chunk in the stream, starting at 0.
+    pub index: u64,
+    pub data: Value,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type TaskStream = Pin<Box<dyn Stream<Item = Result<TaskChunk, A2AError>> + Send>>;
+
+/// Browser response bodies are not `Send`, so neither are their streams.
+#[cfg(target_arch = "wasm32")]
+pub type TaskStream = Pin<Box<dyn Stream<Item = Result<TaskChunk, A2AError>>>>;
+
+pub(crate) const CHUNK_EVENT: &str = "chunk";
+pub(crate) const ERROR_EVENT: &str = "error";
+pub(crate) const DONE_EVENT: &str = "done";
+
+#[derive(Debug)]
+pub(crate) struct SseEvent {
+    pub(crate) event: String,
+    pub(crate) data: String,
+}
+
+/// Incremental `text/event-stream` parser fed with raw body bytes.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend(bytes.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = String::from("message");
+    let mut data = Vec::new();
+
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+
+    if data.is_empty() && event == "message" {
+        return None;
+    }
+
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+struct StreamState<S> {
+    body: S,
+    decoder: SseDecoder,
+    pending: VecDeque<SseEvent>,
+    finished: bool,
+}
+
+/// Turn an SSE response body into a stream of task chunks.
+pub(crate) fn task_stream(response: reqwest::Response) -> TaskStream {
+    let state = StreamState {
+        body: response.bytes_stream(),
+        decoder: SseDecoder::default(),
+        pending: VecDeque::new(),
+        finished: false,
+    };
+
+    let stream = futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if state.finished {
+                return None;
+            }
+
+            if let Some(event) = state.pending.pop_front() {
+                match event.event.as_str() {
+                    CHUNK_EVENT => {
+                        let chunk = serde_json::from_str(&event.data).map_err(A2AError::from);
+                        return Some((chunk, state));
+                    }
+                    ERROR_EVENT => {
+                        state.finished = true;
+                        let error = match serde_json::from_str::<JSONRPCError>(&event.data) {
+                            Ok(error) => A2AError::Rpc {
+                                code: error.code,
+                                message: error.message,
+                            },
+                            Err(e) => A2AError::Serde(e),
+                        };
+                        return Some((Err(error), state));
+                    }
+                    DONE_EVENT => return None,
+                    _ => continue,
+                }
+            }
+
+            match state.body.next().await {
+                Some(Ok(bytes)) => {
+                    let events = state.decoder.push(&bytes);
+                    state.pending.extend(events);
+                }
+                Some(Err(e)) => {
+                    state.finished = true;
+                    return Some((Err(A2AError::from(e)), state));
+                }
+                None => {
+                    state.finished = true;
+                    return Some((Err(A2AError::StreamClosed), state));
+                }
+            }
+        }
+    });
+
+    Box::pin(stream)
+}
@@ -0,0 +1,187 @@
+//! Loopback directories and agents for integration tests, behind the
+//! `testing` feature.
+//!
+//! [`MockDirectory`] and [`MockAgent`] run a real [`A2ADirectory`] and
+//! [`A2AServer`] on free ports, so the agent under test talks HTTP to
+//! them exactly as it would in production. Both shut down gracefully
+//! when dropped; `stop().await` does the same and waits until they have.
+//!
+//! ```
+//! use a2a::testing::{MockAgent, MockDirectory};
+//! use a2a::A2AAgent;
+//! use serde_json::json;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let directory = MockDirectory::start().await?;
+//! let echo = MockAgent::echo("echo", &["echo"]).await?;
+//! echo.register_with(&directory).await?;
+//!
+//! let client = A2AAgent::new("client", "Client", vec![]);
+//! let result = client.send_task("echo", "echo", json!("hi"), directory.url()).await?;
+//! assert_eq!(result.output, Some(json!("hi")));
+//! # Ok::<(), a2a::A2AError>(())
+//! # }).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::timestamp;
+use crate::{A2ADirectory, A2AError, A2AServer, AgentInfo, MemoryRegistryStore, RegistryStore, StoredAgent};
+
+/// An in-memory [`A2ADirectory`] listening on a free port. Agents can
+/// register with it over HTTP, or be put in directly with
+/// [`seed`](Self::seed).
+pub struct MockDirectory {
+    url: String,
+    store: Arc<MemoryRegistryStore>,
+    serving: Serving,
+}
+
+impl MockDirectory {
+    /// An empty directory, already accepting connections.
+    pub async fn start() -> Result<Self, A2AError> {
+        let store = Arc::new(MemoryRegistryStore::new());
+        let (shutdown, stopped) = oneshot::channel();
+        let (addr, serve) = A2ADirectory::new(0)
+            .with_store(store.clone())
+            .bind_with_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await?;
+        Ok(Self {
+            url: format!("http://127.0.0.1:{}", addr.port()),
+            store,
+            serving: Serving::spawn(shutdown, serve),
+        })
+    }
+
+    /// The base URL to pass agents as their `directory_url`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Register `agent` as if it had registered itself just now, without
+    /// a running server behind its endpoint if the test needs none.
+    pub async fn seed(&self, mut agent: AgentInfo) -> Result<(), A2AError> {
+        let registered = SystemTime::now();
+        agent.registered_at.get_or_insert_with(|| timestamp::format(registered));
+        self.store.put(StoredAgent { agent, registered }).await
+    }
+
+    /// The agents currently registered, ordered by `agent_id`.
+    pub async fn agents(&self) -> Vec<AgentInfo> {
+        // The in-memory store cannot fail.
+        let stored = self.store.list().await.unwrap_or_default();
+        stored.into_iter().map(|stored| stored.agent).collect()
+    }
+
+    /// Shut down and wait until every connection is closed.
+    pub async fn stop(mut self) -> Result<(), A2AError> {
+        self.serving.stop().await
+    }
+}
+
+/// An [`A2AServer`] listening on a free port, with the [`AgentInfo`] a
+/// directory needs to route tasks to it.
+pub struct MockAgent {
+    info: AgentInfo,
+    serving: Serving,
+}
+
+impl MockAgent {
+    /// Serve `server` with the handlers registered so far. Create it on
+    /// port `0` so it gets a free port.
+    pub async fn start(server: &A2AServer) -> Result<Self, A2AError> {
+        let (shutdown, stopped) = oneshot::channel();
+        let (addr, serve) = server
+            .bind_with_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await?;
+        let info = AgentInfo {
+            agent_id: server.agent_id().to_string(),
+            name: server.name().to_string(),
+            capabilities: server.capabilities().to_vec(),
+            endpoint: format!("http://127.0.0.1:{}", addr.port()),
+            registered_at: None,
+            version: None,
+            description: None,
+            tags: HashMap::new(),
+        };
+        Ok(Self {
+            info,
+            serving: Serving::spawn(shutdown, serve),
+        })
+    }
+
+    /// An agent offering `capabilities` that answers every task with its
+    /// input.
+    pub async fn echo(agent_id: &str, capabilities: &[&str]) -> Result<Self, A2AError> {
+        let capabilities = capabilities.iter().map(|capability| capability.to_string()).collect();
+        let mut server = A2AServer::new(agent_id, agent_id, capabilities, 0);
+        server.handle_task(|_ctx, input| input);
+        Self::start(&server).await
+    }
+
+    /// The base URL the server answers on.
+    pub fn url(&self) -> &str {
+        &self.info.endpoint
+    }
+
+    /// How a directory should list this agent.
+    pub fn info(&self) -> &AgentInfo {
+        &self.info
+    }
+
+    /// Put this agent in `directory`, as [`MockDirectory::seed`] does.
+    pub async fn register_with(&self, directory: &MockDirectory) -> Result<(), A2AError> {
+        directory.seed(self.info.clone()).await
+    }
+
+    /// Shut down, waiting for tasks still running and until every
+    /// connection is closed.
+    pub async fn stop(mut self) -> Result<(), A2AError> {
+        self.serving.stop().await
+    }
+}
+
+/// A spawned serving future and the signal that shuts it down, sent at
+/// the latest when this is dropped.
+struct Serving {
+    shutdown: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<Result<(), A2AError>>>,
+}
+
+impl Serving {
+    fn spawn<F>(shutdown: oneshot::Sender<()>, serve: F) -> Self
+    where
+        F: std::future::Future<Output = Result<(), A2AError>> + Send + 'static,
+    {
+        Self {
+            shutdown: Some(shutdown),
+            task: Some(tokio::spawn(serve)),
+        }
+    }
+
+    async fn stop(&mut self) -> Result<(), A2AError> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        match self.task.take() {
+            Some(task) => task.await.unwrap_or(Ok(())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Serving {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
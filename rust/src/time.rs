@@ -0,0 +1,122 @@
+//! Clocks and timers for both native targets and the browser.
+//!
+//! Natively these are std's [`Instant`] and tokio's timers. On `wasm32`
+//! `std::time::Instant` panics and tokio has no timer, so they are built on
+//! `Date.now()` and `setTimeout` instead.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::sleep;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use wasm::{sleep, system_now, Instant};
+
+/// The wall-clock time, e.g. to compare with a date a server sent.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn system_now() -> std::time::SystemTime {
+    std::time::SystemTime::now()
+}
+
+/// Run `future` for at most `duration`, or `None` if it takes longer.
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::timeout(duration, future).await.ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        use futures::future::{select, Either};
+
+        match select(std::pin::pin!(future), std::pin::pin!(sleep(duration))).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use js_sys::{Date, Function, Promise, Reflect};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    /// Longest delay `setTimeout` accepts, about 24.8 days.
+    const MAX_TIMEOUT_MS: u128 = i32::MAX as u128;
+
+    /// Wall-clock milliseconds, which is as close to a monotonic clock as
+    /// every JavaScript environment gets.
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub(crate) struct Instant(f64);
+
+    impl Instant {
+        pub(crate) fn now() -> Self {
+            Self(Date::now())
+        }
+
+        /// Zero if the clock went backwards.
+        pub(crate) fn duration_since(&self, earlier: Instant) -> Duration {
+            Duration::from_secs_f64(((self.0 - earlier.0) / 1000.0).max(0.0))
+        }
+
+        pub(crate) fn elapsed(&self) -> Duration {
+            Self::now().duration_since(*self)
+        }
+    }
+
+    impl std::ops::Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, duration: Duration) -> Instant {
+            Instant(self.0 + duration.as_secs_f64() * 1000.0)
+        }
+    }
+
+    /// `SystemTime::now` panics here too.
+    pub(crate) fn system_now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs_f64(Date::now() / 1000.0)
+    }
+
+    /// Resolve after `duration`, using the global `setTimeout` so it works
+    /// in windows and workers alike.
+    pub(crate) async fn sleep(duration: Duration) {
+        let millis = duration.as_millis().min(MAX_TIMEOUT_MS) as f64;
+        let promise = Promise::new(&mut |resolve, _reject| {
+            let set_timeout: Function = Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .expect("setTimeout is not available")
+                .unchecked_into();
+            let _ = set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from_f64(millis));
+        });
+        let _ = JsFuture::from(promise).await;
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    async fn sleep_waits_on_set_timeout() {
+        let started = Instant::now();
+        sleep(Duration::from_millis(30)).await;
+        assert!(started.elapsed() >= Duration::from_millis(25));
+        assert!(Instant::now() >= started + Duration::from_millis(25));
+    }
+
+    #[wasm_bindgen_test]
+    async fn timeout_gives_up_on_slow_futures() {
+        assert_eq!(timeout(Duration::from_millis(50), async { 7 }).await, Some(7));
+        assert_eq!(timeout(Duration::from_millis(10), sleep(Duration::from_secs(5))).await, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn system_now_follows_the_js_clock() {
+        let now = system_now().duration_since(std::time::UNIX_EPOCH).unwrap();
+        assert!((now.as_millis() as f64 - js_sys::Date::now()).abs() < 1000.0);
+    }
+}
@@ -0,0 +1,71 @@
+//! Timestamps on the wire: RFC 3339 in `registeredAt`, HTTP-dates in
+//! `Retry-After`.
+
+use chrono::{DateTime, Utc};
+#[cfg(not(target_arch = "wasm32"))]
+use chrono::SecondsFormat;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+/// Parse an RFC 3339 timestamp such as `2024-05-01T10:00:00.123Z` or
+/// `2024-05-01T12:00:00+02:00`. Anything else, including a timestamp
+/// without an offset, is `None`.
+pub(crate) fn parse(text: &str) -> Option<DateTime<Utc>> {
+    let time = DateTime::parse_from_rfc3339(text).ok()?;
+    Some(time.with_timezone(&Utc))
+}
+
+/// Parse an HTTP-date in the IMF-fixdate form senders must use, such as
+/// `Sun, 06 Nov 1994 08:49:37 GMT`. The obsolete RFC 850 and asctime forms
+/// are not accepted.
+pub(crate) fn parse_http_date(text: &str) -> Option<DateTime<Utc>> {
+    // IMF-fixdate is the RFC 2822 form, always in GMT.
+    if !text.ends_with(" GMT") {
+        return None;
+    }
+    let time = DateTime::parse_from_rfc2822(text).ok()?;
+    Some(time.with_timezone(&Utc))
+}
+
+/// Format `time` in UTC with millisecond precision, e.g.
+/// `2024-05-01T10:00:00.123Z`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn format(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn rfc3339_needs_an_offset() {
+        let noon = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        assert_eq!(parse("2024-05-01T10:00:00Z"), Some(noon));
+        assert_eq!(parse("2024-05-01T12:00:00+02:00"), Some(noon));
+        assert_eq!(parse("2024-05-01T10:00:00.250Z"), Some(noon + chrono::Duration::milliseconds(250)));
+
+        for text in ["2024-05-01T10:00:00", "2024-02-30T10:00:00Z", "2024-05-01", "yesterday", ""] {
+            assert_eq!(parse(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn http_dates_must_be_gmt() {
+        let date = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(date));
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 +0000"), None);
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), None);
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn formats_round_trip() {
+        let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_714_557_600_123);
+        let text = format(time);
+        assert_eq!(text, "2024-05-01T10:00:00.123Z");
+        assert_eq!(parse(&text), Some(DateTime::<Utc>::from(time)));
+    }
+}
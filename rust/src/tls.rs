@@ -0,0 +1,249 @@
+//! Client certificates and custom roots for mutual TLS, and the server
+//! certificate for serving HTTPS (`tls` feature).
+
+use axum::serve::Listener;
+use reqwest::{Certificate, ClientBuilder, Identity};
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_native_tls::TlsStream;
+
+use crate::A2AError;
+
+/// TLS settings for every directory and peer call an agent makes.
+///
+/// The `Debug` output never contains the private key.
+///
+/// ```rust,no_run
+/// use a2a::{A2AAgent, TlsConfig};
+///
+/// # fn example() -> Result<(), a2a::A2AError> {
+/// let tls = TlsConfig::new()
+///     .with_identity_pem(&std::fs::read("agent.pem")?, &std::fs::read("agent.key")?)?
+///     .with_root_certificates_pem(&std::fs::read("ca.pem")?)?;
+///
+/// let agent = A2AAgent::builder("my-agent", "My Agent").tls(tls).build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct TlsConfig {
+    identity: Option<Identity>,
+    root_certificates: Vec<Certificate>,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Present a client certificate chain (PEM, leaf first) with its
+    /// PKCS #8 PEM private key.
+    pub fn with_identity_pem(mut self, certificate_chain: &[u8], private_key: &[u8]) -> Result<Self, A2AError> {
+        self.identity = Some(Identity::from_pkcs8_pem(certificate_chain, private_key).map_err(A2AError::Tls)?);
+        Ok(self)
+    }
+
+    /// Present a client identity from a DER-encoded PKCS #12 archive.
+    pub fn with_identity_pkcs12(mut self, der: &[u8], password: &str) -> Result<Self, A2AError> {
+        self.identity = Some(Identity::from_pkcs12_der(der, password).map_err(A2AError::Tls)?);
+        Ok(self)
+    }
+
+    /// Trust every certificate in a PEM bundle as an extra root CA, on
+    /// top of the system roots.
+    pub fn with_root_certificates_pem(mut self, bundle: &[u8]) -> Result<Self, A2AError> {
+        let certificates = Certificate::from_pem_bundle(bundle).map_err(A2AError::Tls)?;
+        self.root_certificates.extend(certificates);
+        Ok(self)
+    }
+
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("identity", &self.identity.as_ref().map(|_| "***"))
+            .field("root_certificates", &self.root_certificates.len())
+            .finish()
+    }
+}
+
+/// Whether `error` was caused by a failed TLS handshake.
+pub(crate) fn is_handshake_error(error: &reqwest::Error) -> bool {
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        if cause.is::<native_tls::Error>() {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
+
+/// The certificate an [`A2AServer`](crate::A2AServer) presents when it
+/// serves HTTPS instead of plain HTTP.
+///
+/// The chain and PKCS #8 private key are PEM, given either as bytes or as
+/// paths read when the server binds. Either way they are parsed at bind
+/// time, so a bad certificate fails `run` with [`A2AError::TlsSetup`]
+/// before any connection is accepted. The `Debug` output never contains
+/// the private key.
+///
+/// ```rust,no_run
+/// use a2a::{A2AServer, ServerTlsConfig};
+///
+/// # async fn example() -> Result<(), a2a::A2AError> {
+/// let tls = ServerTlsConfig::from_pem_files("server.pem", "server.key");
+/// let server = A2AServer::new("calculator", "Calculator", vec!["add".to_string()], 9443).with_tls(tls);
+/// server.run().await
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ServerTlsConfig {
+    source: PemSource,
+}
+
+#[derive(Clone)]
+enum PemSource {
+    Bytes { certificate_chain: Vec<u8>, private_key: Vec<u8> },
+    Files { certificate_chain: PathBuf, private_key: PathBuf },
+}
+
+impl ServerTlsConfig {
+    /// Present `certificate_chain` (PEM, leaf first) with its PKCS #8 PEM
+    /// `private_key`.
+    pub fn from_pem(certificate_chain: impl Into<Vec<u8>>, private_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            source: PemSource::Bytes {
+                certificate_chain: certificate_chain.into(),
+                private_key: private_key.into(),
+            },
+        }
+    }
+
+    /// Like [`from_pem`](Self::from_pem), reading both from files when the
+    /// server binds.
+    pub fn from_pem_files(certificate_chain: impl Into<PathBuf>, private_key: impl Into<PathBuf>) -> Self {
+        Self {
+            source: PemSource::Files {
+                certificate_chain: certificate_chain.into(),
+                private_key: private_key.into(),
+            },
+        }
+    }
+
+    pub(crate) fn acceptor(&self) -> Result<tokio_native_tls::TlsAcceptor, A2AError> {
+        let identity = match &self.source {
+            PemSource::Bytes { certificate_chain, private_key } => native_tls::Identity::from_pkcs8(certificate_chain, private_key),
+            PemSource::Files { certificate_chain, private_key } => {
+                let read = |path: &PathBuf| {
+                    std::fs::read(path).map_err(|e| A2AError::TlsSetup(format!("cannot read {}: {}", path.display(), e)))
+                };
+                native_tls::Identity::from_pkcs8(&read(certificate_chain)?, &read(private_key)?)
+            }
+        }
+        .map_err(|e| A2AError::TlsSetup(format!("invalid certificate or private key: {}", e)))?;
+
+        let acceptor = native_tls::TlsAcceptor::new(identity).map_err(|e| A2AError::TlsSetup(e.to_string()))?;
+        Ok(acceptor.into())
+    }
+}
+
+impl fmt::Debug for ServerTlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ServerTlsConfig");
+        match &self.source {
+            PemSource::Bytes { .. } => debug.field("certificate_chain", &"<pem>"),
+            PemSource::Files { certificate_chain, .. } => debug.field("certificate_chain", certificate_chain),
+        };
+        debug.field("private_key", &"***").finish()
+    }
+}
+
+/// How long a client may take over its TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Accepts TCP connections and completes their TLS handshakes in the
+/// background, so one slow client cannot hold up the others.
+pub(crate) struct TlsListener {
+    local_addr: SocketAddr,
+    handshaken: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    _accepting: AbortOnDrop,
+}
+
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl TlsListener {
+    pub(crate) fn new(listener: TcpListener, acceptor: tokio_native_tls::TlsAcceptor) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (handshaken, receiver) = mpsc::channel(64);
+        let accepting = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        // Typically out of file descriptors; retrying at
+                        // once would spin.
+                        debug!(error = %e, "accept failed");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let handshaken = handshaken.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = handshaken.send((stream, peer)).await;
+                        }
+                        Ok(Err(e)) => debug!(%peer, error = %e, "TLS handshake failed"),
+                        Err(_) => debug!(%peer, "TLS handshake timed out"),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            handshaken: receiver,
+            _accepting: AbortOnDrop(accepting),
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.handshaken.recv().await {
+            Some(accepted) => accepted,
+            // The accept loop only ends when this listener aborts it.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
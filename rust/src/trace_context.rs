@@ -0,0 +1,103 @@
+//! W3C Trace Context propagation and request spans through OpenTelemetry,
+//! behind the `otel` feature.
+//!
+//! Every JSON-RPC call an agent makes runs in an `a2a.request` span and
+//! sends `traceparent` and `tracestate` headers for it, written by the W3C
+//! [`TraceContextPropagator`] from the span's OpenTelemetry context. An
+//! [`A2AServer`] extracts them and answers in an `a2a.server` span whose
+//! parent is the caller's span. That span is in scope while the handler
+//! runs, so the calls a handler makes in turn continue the trace too. Both
+//! spans carry `a2a.method`, `a2a.agent_id`, `a2a.task_id` and
+//! `http.status_code`.
+//!
+//! Spans get their OpenTelemetry context from a `tracing-opentelemetry`
+//! layer on the application's subscriber, which also exports them, e.g.
+//! over OTLP. Without one there is no trace, and no headers are sent.
+//!
+//! Work moved onto a separate `tokio::spawn` leaves the span in scope;
+//! instrument it with [`Instrument::in_current_span`] to stay in the
+//! trace. Items of a streamed task are produced outside it.
+//!
+//! [`A2AServer`]: crate::A2AServer
+
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use reqwest::header::HeaderMap;
+use reqwest::RequestBuilder;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The `traceparent` header.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Add the trace context headers of the span in scope.
+pub(crate) fn apply(request: RequestBuilder) -> RequestBuilder {
+    let mut headers = HashMap::new();
+    TraceContextPropagator::new().inject_context(&tracing::Span::current().context(), &mut headers);
+    headers.into_iter().fold(request, |request, (name, value)| request.header(name, value))
+}
+
+/// A request's headers, for the propagator to read.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// The `a2a.request` span of an outgoing call, below the span in scope.
+pub(crate) struct RequestScope {
+    span: tracing::Span,
+}
+
+impl RequestScope {
+    pub(crate) fn new(method: &str, params: Option<&Value>) -> Self {
+        let field = |name: &str| params.and_then(|params| params.get(name)).and_then(Value::as_str);
+        let span = tracing::info_span!(
+            "a2a.request",
+            a2a.method = %method,
+            a2a.agent_id = field("sender").unwrap_or_default(),
+            a2a.task_id = field("taskId").or_else(|| field("task_id")),
+            http.status_code = tracing::field::Empty,
+        );
+        Self { span }
+    }
+
+    /// Run `call` in the span.
+    pub(crate) async fn run<F: Future>(self, call: F) -> F::Output {
+        call.instrument(self.span).await
+    }
+}
+
+/// Answer a request to `agent_id` in an `a2a.server` span below the
+/// caller's, or at the root of a new trace if the caller sent no valid
+/// `traceparent`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn serve<F>(agent_id: &str, headers: &HeaderMap, response: F) -> axum::response::Response
+where
+    F: Future<Output = axum::response::Response>,
+{
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    // The method and task are recorded once the body is parsed.
+    let span = tracing::info_span!(
+        "a2a.server",
+        a2a.method = tracing::field::Empty,
+        a2a.agent_id = agent_id,
+        a2a.task_id = tracing::field::Empty,
+        http.status_code = tracing::field::Empty,
+    );
+    // Fails only without a `tracing-opentelemetry` layer, which leaves no
+    // trace to join.
+    let _ = span.set_parent(parent);
+    let response = response.instrument(span.clone()).await;
+    span.record("http.status_code", response.status().as_u16());
+    response
+}
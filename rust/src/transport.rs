@@ -0,0 +1,506 @@
+//! How [`A2AAgent`](crate::A2AAgent) reaches directories and peers.
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::body_log::BodyLog;
+use crate::{
+    attachment, codec, headers, metrics, retry, A2AError, Attachment, AuthConfig, Codec, Interceptor, JSONRPCRequest, JSONRPCResponse, RequestParts, ResponseParts,
+    TokenProvider, JSONRPC_VERSION,
+};
+
+/// Carries an agent's directory and task calls.
+///
+/// [`HttpTransport`] is the default. Install another with
+/// [`A2AAgent::with_transport`](crate::A2AAgent::with_transport) to route
+/// calls elsewhere, e.g. to handlers in the same process in a test:
+///
+/// ```
+/// use a2a::{A2AAgent, A2AError, Transport};
+/// use async_trait::async_trait;
+/// use serde_json::{json, Value};
+/// use std::sync::Arc;
+///
+/// /// Knows one agent and answers every task with its input.
+/// struct Echo;
+///
+/// #[async_trait]
+/// impl Transport for Echo {
+///     async fn call(&self, _url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+///         let params = params.unwrap_or_default();
+///         match method {
+///             "a2a/task" => Ok(json!({"taskId": params["taskId"], "status": "completed", "output": params["input"]})),
+///             _ => Ok(json!({})),
+///         }
+///     }
+///
+///     async fn get(&self, _url: &str) -> Result<Option<Value>, A2AError> {
+///         Ok(Some(json!({"agentId": "echo", "name": "Echo", "capabilities": [], "endpoint": "mem://echo"})))
+///     }
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let agent = A2AAgent::new("client", "Client", vec![]).with_transport(Arc::new(Echo));
+/// let result = agent.send_task("echo", "say", json!("hi"), "mem://directory").await?;
+/// assert_eq!(result.output, Some(json!("hi")));
+/// # Ok::<(), A2AError>(())
+/// # }).unwrap();
+/// ```
+///
+/// On `wasm32` the returned futures need not be `Send`, as `fetch` futures
+/// are not; implement it with `#[async_trait(?Send)]` there.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Transport: Send + Sync {
+    /// Make the JSON-RPC call `method` on `url` and return its `result`,
+    /// or its `error` as [`A2AError::Rpc`].
+    async fn call(&self, url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError>;
+
+    /// Like [`call`](Self::call), but send `attachments` along with the
+    /// request. The default fails with [`A2AError::InvalidAttachment`], for
+    /// transports that cannot carry binary parts.
+    async fn call_with_attachments(
+        &self,
+        url: &str,
+        method: &str,
+        params: Option<Value>,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        let _ = (url, method, params, attachments);
+        Err(A2AError::InvalidAttachment("this transport cannot carry attachments".to_string()))
+    }
+
+    /// Send `method` to `url` as a JSON-RPC notification, expecting no
+    /// response. The default makes a regular [`call`](Self::call) and
+    /// discards its result, including a JSON-RPC error, for transports
+    /// without a way to send notifications.
+    async fn notify(&self, url: &str, method: &str, params: Option<Value>) -> Result<(), A2AError> {
+        match self.call(url, method, params).await {
+            Ok(_) | Err(A2AError::Rpc { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch the JSON document at `url`, such as a directory's record of
+    /// one agent under `/a2a/agents/{agent_id}`. `Ok(None)` means there is
+    /// no such document; any other failure, such as being refused access,
+    /// is an error.
+    async fn get(&self, url: &str) -> Result<Option<Value>, A2AError>;
+}
+
+/// Largest response body read by default, 16 MiB.
+pub(crate) const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Sent as `User-Agent` unless the agent is given another.
+pub(crate) const DEFAULT_USER_AGENT: &str = concat!("a2a-rust/", env!("CARGO_PKG_VERSION"));
+
+/// How much of an error response's body [`A2AError::HttpStatus`] keeps.
+const ERROR_SNIPPET_BYTES: usize = 512;
+
+/// JSON-RPC over HTTP with `reqwest`, the default [`Transport`]. The
+/// agent's default headers, credentials and interceptors apply to every
+/// request.
+#[derive(Clone)]
+pub struct HttpTransport {
+    pub(crate) client: Client,
+    pub(crate) default_headers: HeaderMap,
+    pub(crate) user_agent: String,
+    pub(crate) auth: Option<AuthConfig>,
+    pub(crate) token_provider: Option<Arc<dyn TokenProvider>>,
+    pub(crate) interceptors: Vec<Arc<dyn Interceptor>>,
+    pub(crate) max_response_bytes: usize,
+    pub(crate) body_log: Option<Arc<BodyLog>>,
+    /// How call bodies are encoded; `None` is JSON.
+    pub(crate) codec: Option<Arc<dyn Codec>>,
+    #[cfg(feature = "compression")]
+    pub(crate) compression: Option<crate::Compression>,
+    /// The browser client has no client-wide timeout, so it is set on
+    /// each request.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) timeout: Option<std::time::Duration>,
+}
+
+impl HttpTransport {
+    /// Send plain requests through `client`, without credentials or
+    /// interceptors.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            default_headers: HeaderMap::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            auth: None,
+            token_provider: None,
+            interceptors: Vec::new(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            body_log: None,
+            codec: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(target_arch = "wasm32")]
+            timeout: None,
+        }
+    }
+
+    /// Read `response` as JSON, or in the agent's codec if it is declared
+    /// so, failing with [`A2AError::ResponseTooLarge`] as soon as the body
+    /// passes `max_response_bytes` instead of buffering all of it, and with
+    /// [`A2AError::UnexpectedContentType`] if it is declared as something
+    /// else. Compressed bodies are inflated, and the limit applies both
+    /// before and after.
+    pub(crate) async fn read_json<T: DeserializeOwned>(&self, response: reqwest::Response) -> Result<T, A2AError> {
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+        let codec = self.codec.as_ref().filter(|codec| {
+            content_type.is_some_and(|v| codec::media_type(v).eq_ignore_ascii_case(codec.content_type()))
+        });
+        if let Some(content_type) = content_type.filter(|v| codec.is_none() && !v.contains("json")) {
+            return Err(A2AError::UnexpectedContentType {
+                content_type: content_type.to_string(),
+                body: snippet(response).await,
+            });
+        }
+
+        #[cfg(feature = "compression")]
+        let encoding = crate::compression::content_encoding(response.headers())?;
+
+        let limit = self.max_response_bytes;
+        if response.content_length().is_some_and(|len| len > limit as u64) {
+            return Err(A2AError::ResponseTooLarge { limit });
+        }
+
+        let body = read_limited(response, limit).await?;
+        #[cfg(feature = "compression")]
+        let body = match encoding {
+            Some(encoding) => crate::compression::decompress(encoding, &body, limit)?,
+            None => body,
+        };
+        match codec {
+            Some(codec) => Ok(serde_json::from_value(codec.decode(&body)?)?),
+            None => Ok(serde_json::from_slice(&body)?),
+        }
+    }
+
+    pub(crate) async fn post_json<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        method: &str,
+        body: &T,
+    ) -> Result<reqwest::Response, A2AError> {
+        check_status(self.send(self.encode(self.client.post(url), body)?, method).await?).await
+    }
+
+    /// Post `envelope` as the root part of a `multipart/related` body, with
+    /// one part per attachment after it. Attachments are sent as they are,
+    /// never compressed.
+    async fn post_multipart<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        method: &str,
+        envelope: &T,
+        attachments: &[Attachment],
+    ) -> Result<reqwest::Response, A2AError> {
+        let (content_type, body) = attachment::encode(&serde_json::to_vec(envelope)?, attachments);
+        let request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        check_status(self.send(request, method).await?).await
+    }
+
+    /// Make the JSON-RPC call `method` on `url`, as `multipart/related` if
+    /// there are `attachments`.
+    async fn rpc(
+        &self,
+        url: &str,
+        method: &str,
+        params: Option<Value>,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        #[cfg(feature = "otel")]
+        let scope = crate::trace_context::RequestScope::new(method, params.as_ref());
+        let call = self.exchange(url, method, params, attachments);
+        #[cfg(feature = "otel")]
+        let call = scope.run(call);
+        call.await
+    }
+
+    async fn exchange(
+        &self,
+        url: &str,
+        method: &str,
+        params: Option<Value>,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        let id = Value::String(uuid::Uuid::new_v4().to_string());
+        let request = JSONRPCRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: id.clone(),
+            method: method.to_string(),
+            params,
+        };
+
+        if let Some(body_log) = &self.body_log {
+            body_log.request(url, &request);
+        }
+        let response = match attachments {
+            [] => self.post_json(url, method, &request).await?,
+            _ => self.post_multipart(url, method, &request, attachments).await?,
+        };
+        let rpc_response: JSONRPCResponse = match &self.body_log {
+            None => self.read_json(response).await?,
+            Some(body_log) => {
+                let body: Value = self.read_json(response).await?;
+                body_log.response(url, &body);
+                serde_json::from_value(body)?
+            }
+        };
+        rpc_response.check_id(&id)?;
+        rpc_response.into_result()
+    }
+
+    /// Attach `body` as JSON or in the agent's codec, compressed if it is
+    /// large enough.
+    fn encode<T: Serialize + ?Sized>(
+        &self,
+        request: reqwest::RequestBuilder,
+        body: &T,
+    ) -> Result<reqwest::RequestBuilder, A2AError> {
+        let (request, body) = match &self.codec {
+            Some(codec) => (
+                request
+                    .header(reqwest::header::CONTENT_TYPE, codec.content_type())
+                    .header(reqwest::header::ACCEPT, codec.content_type()),
+                codec.encode(&serde_json::to_value(body)?)?,
+            ),
+            None => (request.header(reqwest::header::CONTENT_TYPE, "application/json"), serde_json::to_vec(body)?),
+        };
+        #[cfg(feature = "compression")]
+        if let Some(compression) = self.compression.as_ref().filter(|compression| body.len() >= compression.min_bytes) {
+            let compressed = crate::compression::compress(compression.encoding, &body)?;
+            return Ok(request
+                .header(reqwest::header::CONTENT_ENCODING, compression.encoding.as_str())
+                .body(compressed));
+        }
+        Ok(request.body(body))
+    }
+
+    /// Add headers and credentials, run the interceptor chain and send
+    /// `request`.
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        method: &str,
+    ) -> Result<reqwest::Response, A2AError> {
+        let result = self.execute(request, method).await;
+        #[cfg(feature = "otel")]
+        if let Ok(response) = &result {
+            record!("http.status_code", response.status().as_u16());
+        }
+        let ok = result.as_ref().is_ok_and(|response| response.status().is_success());
+        metrics::request_finished(metrics::CLIENT, method, ok);
+        result
+    }
+
+    async fn execute(&self, request: reqwest::RequestBuilder, method: &str) -> Result<reqwest::Response, A2AError> {
+        #[cfg(feature = "compression")]
+        let request = match self.compression {
+            Some(_) => request.header(reqwest::header::ACCEPT_ENCODING, crate::compression::ACCEPT_ENCODING),
+            None => request,
+        };
+        #[cfg(target_arch = "wasm32")]
+        let request = match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+        let mut request = self.authorize(request).await?.build()?;
+        if self.interceptors.is_empty() {
+            return Ok(self.client.execute(request).await?);
+        }
+
+        let mut parts = RequestParts {
+            method: method.to_string(),
+            url: request.url().to_string(),
+            headers: std::mem::take(request.headers_mut()),
+        };
+        for interceptor in &self.interceptors {
+            interceptor.on_request(&mut parts).await;
+        }
+        *request.headers_mut() = parts.headers;
+
+        let started = crate::time::Instant::now();
+        let result = self.client.execute(request).await;
+
+        let parts = ResponseParts {
+            method: parts.method,
+            url: parts.url,
+            status: result.as_ref().ok().map(|response| response.status()),
+            elapsed: started.elapsed(),
+        };
+        for interceptor in self.interceptors.iter().rev() {
+            interceptor.on_response(&parts).await;
+        }
+
+        Ok(result?)
+    }
+
+    /// Add the `User-Agent`, then the default headers, then credentials,
+    /// then the `traceparent` of the trace in scope, then the headers of a
+    /// [`with_headers`](crate::with_headers) scope, each overriding the
+    /// last.
+    pub(crate) async fn authorize(&self, request: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder, A2AError> {
+        let request = request
+            .header(reqwest::header::USER_AGENT, self.user_agent.as_str())
+            .headers(self.default_headers.clone());
+
+        let request = if let Some(provider) = &self.token_provider {
+            let token = provider.token().await?;
+            request.bearer_auth(token)
+        } else {
+            match &self.auth {
+                Some(auth) => auth.apply(request),
+                None => request,
+            }
+        };
+
+        #[cfg(feature = "otel")]
+        let request = crate::trace_context::apply(request);
+        Ok(headers::apply(request))
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Transport for HttpTransport {
+    async fn call(&self, url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+        self.rpc(url, method, params, &[]).await
+    }
+
+    async fn call_with_attachments(
+        &self,
+        url: &str,
+        method: &str,
+        params: Option<Value>,
+        attachments: &[Attachment],
+    ) -> Result<Value, A2AError> {
+        self.rpc(url, method, params, attachments).await
+    }
+
+    async fn notify(&self, url: &str, method: &str, params: Option<Value>) -> Result<(), A2AError> {
+        let notification = JSONRPCRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Value::Null,
+            method: method.to_string(),
+            params,
+        };
+        if let Some(body_log) = &self.body_log {
+            body_log.request(url, &notification);
+        }
+        self.post_json(url, method, &notification).await?;
+        Ok(())
+    }
+
+    async fn get(&self, url: &str) -> Result<Option<Value>, A2AError> {
+        // Agent lookups are the only documents fetched this way.
+        let response = self.send(self.client.get(url), "a2a/agents").await?;
+
+        // Only a 404 means the directory has no such agent; a 401 or 403
+        // from an auth proxy in front of it must not read as one.
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check_status(response).await?;
+
+        Ok(Some(self.read_json(response).await?))
+    }
+}
+
+/// Pass `response` through if it succeeded, or fail with
+/// [`A2AError::HttpStatus`] carrying the start of its body, e.g. a
+/// gateway's HTML error page. A 429 fails with [`A2AError::RateLimited`]
+/// instead, carrying its `Retry-After`.
+pub(crate) async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, A2AError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(retry::parse_retry_after);
+        return Err(A2AError::RateLimited {
+            target: response.url().to_string(),
+            retry_after,
+        });
+    }
+    Err(A2AError::HttpStatus {
+        status,
+        body: snippet(response).await,
+    })
+}
+
+/// The whole body, failing with [`A2AError::ResponseTooLarge`] as soon as
+/// it passes `limit`.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>, A2AError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(A2AError::ResponseTooLarge { limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// The whole body, failing with [`A2AError::ResponseTooLarge`] if it
+/// passes `limit`. `fetch` only hands over complete bodies.
+#[cfg(target_arch = "wasm32")]
+async fn read_limited(response: reqwest::Response, limit: usize) -> Result<Vec<u8>, A2AError> {
+    let body = response.bytes().await?;
+    if body.len() > limit {
+        return Err(A2AError::ResponseTooLarge { limit });
+    }
+    Ok(body.to_vec())
+}
+
+/// At least the first `len` bytes of the body, or as much of it as could
+/// be read.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_head(mut response: reqwest::Response, len: usize) -> Vec<u8> {
+    let mut body = Vec::new();
+    while body.len() < len {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    body
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn read_head(response: reqwest::Response, _len: usize) -> Vec<u8> {
+    response.bytes().await.map(|body| body.to_vec()).unwrap_or_default()
+}
+
+/// Up to [`ERROR_SNIPPET_BYTES`] of the body, for error messages.
+async fn snippet(response: reqwest::Response) -> String {
+    let mut body = read_head(response, ERROR_SNIPPET_BYTES).await;
+
+    let truncated = body.len() > ERROR_SNIPPET_BYTES;
+    body.truncate(ERROR_SNIPPET_BYTES);
+    let mut snippet = String::from_utf8_lossy(&body).trim().to_string();
+    if truncated {
+        snippet.push_str("...");
+    }
+    snippet
+}
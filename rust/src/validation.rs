@@ -0,0 +1,57 @@
+//! Client-side checks of tasks against the target's declared capabilities.
+
+use serde_json::Value;
+
+use crate::{A2AError, AgentInfo, Capability, Version};
+
+/// Fail unless `agent` lists `action` among its capabilities.
+pub(crate) fn check_action(agent: &AgentInfo, action: &str) -> Result<(), A2AError> {
+    if agent.capabilities.iter().any(|capability| capability.name == action) {
+        return Ok(());
+    }
+    Err(A2AError::UnsupportedAction {
+        agent_id: agent.agent_id.clone(),
+        action: action.to_string(),
+    })
+}
+
+/// Every version of `action` that `agent` declares.
+pub(crate) fn versions<'a>(agent: &'a AgentInfo, action: &'a str) -> impl Iterator<Item = Version> + 'a {
+    agent
+        .capabilities
+        .iter()
+        .filter(move |capability| capability.name == action)
+        .filter_map(|capability| capability.version)
+}
+
+/// The capability `agent` declares for `action`, at `version` if given.
+fn capability<'a>(agent: &'a AgentInfo, action: &str, version: Option<&Version>) -> Option<&'a Capability> {
+    agent
+        .capabilities
+        .iter()
+        .find(|capability| capability.name == action && version.is_none_or(|version| capability.version.as_ref() == Some(version)))
+}
+
+/// Validate `input` against the `input_schema` that `agent` declares for
+/// `action`, at `version` if given. Passes when the agent declares no
+/// such capability or no schema for it.
+pub(crate) fn validate_input(agent: &AgentInfo, action: &str, version: Option<&Version>, input: &Value) -> Result<(), A2AError> {
+    let Some(schema) = capability(agent, action, version).and_then(|capability| capability.input_schema.as_ref()) else {
+        return Ok(());
+    };
+
+    let validator = jsonschema::validator_for(schema).map_err(|e| A2AError::InvalidInput {
+        errors: vec![format!("invalid input schema for '{}': {}", action, e)],
+    })?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(input)
+        .map(|e| format!("{}: {}", e.instance_path(), e))
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(A2AError::InvalidInput { errors })
+    }
+}
@@ -0,0 +1,333 @@
+//! Semantic versions of capabilities, and the constraints tasks put on them.
+//!
+//! Only `major.minor.patch` versions are understood; pre-release and build
+//! metadata suffixes are rejected.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{validation, A2AAgent, A2AError, AgentInfo, TaskResult};
+
+/// The version of a capability's contract, such as `2.1.0`, written as a
+/// string on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = A2AError;
+
+    fn from_str(text: &str) -> Result<Self, A2AError> {
+        match Partial::parse(text.trim()) {
+            Some(Partial {
+                major,
+                minor: Some(minor),
+                patch: Some(patch),
+            }) => Ok(Version::new(major, minor, patch)),
+            _ => Err(A2AError::InvalidVersion(format!("expected major.minor.patch, got {:?}", text))),
+        }
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which versions of a capability a task accepts, as comma-separated
+/// comparators that must all hold, like Cargo's: `^1.2` (the default
+/// when no operator is given), `~1.2`, `=1.2.3`, `>=2.0`, `>1`, `<3`,
+/// `<=2.4` or `*`.
+///
+/// ```
+/// use a2a::{Version, VersionReq};
+///
+/// let req: VersionReq = ">=1.2, <3".parse()?;
+/// assert!(req.matches(&Version::new(2, 9, 0)));
+/// assert!(!req.matches(&Version::new(3, 0, 0)));
+///
+/// let caret: VersionReq = "^0.2".parse()?;
+/// assert!(caret.matches(&Version::new(0, 2, 7)));
+/// assert!(!caret.matches(&Version::new(0, 3, 0)));
+/// # Ok::<(), a2a::A2AError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    /// Whether `version` satisfies every comparator.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|comparator| comparator.matches(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, comparator) in self.comparators.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", comparator)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = A2AError;
+
+    fn from_str(text: &str) -> Result<Self, A2AError> {
+        let comparators = text
+            .split(',')
+            .map(|part| {
+                Comparator::parse(part.trim())
+                    .ok_or_else(|| A2AError::InvalidVersion(format!("invalid version requirement {:?}", text)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(VersionReq { comparators })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    Wildcard,
+}
+
+impl Op {
+    fn as_str(self) -> &'static str {
+        match self {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "*",
+        }
+    }
+}
+
+/// A version with its minor and patch numbers possibly left out, as in
+/// `^1` or `~1.2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Partial {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl Partial {
+    fn parse(text: &str) -> Option<Partial> {
+        let number = |part: &str| {
+            (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| part.parse().ok())
+                .flatten()
+        };
+        let mut parts = text.split('.');
+        let major = number(parts.next()?)?;
+        let minor = parts.next().map(number);
+        let patch = parts.next().map(number);
+        if parts.next().is_some() || matches!(minor, Some(None)) || matches!(patch, Some(None)) {
+            return None;
+        }
+        Some(Partial {
+            major,
+            minor: minor.flatten(),
+            patch: patch.flatten(),
+        })
+    }
+
+    /// The lowest version this partial version covers.
+    fn lowest(&self) -> Version {
+        Version::new(self.major, self.minor.unwrap_or(0), self.patch.unwrap_or(0))
+    }
+
+    /// The lowest version past everything this partial version covers,
+    /// e.g. `2.0.0` for `1` and `1.3.0` for `1.2`.
+    fn past(&self) -> Version {
+        match (self.minor, self.patch) {
+            (None, _) => Version::new(self.major.saturating_add(1), 0, 0),
+            (Some(minor), None) => Version::new(self.major, minor.saturating_add(1), 0),
+            (Some(minor), Some(patch)) => Version::new(self.major, minor, patch.saturating_add(1)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Comparator {
+    op: Op,
+    version: Partial,
+}
+
+impl Comparator {
+    fn parse(text: &str) -> Option<Comparator> {
+        if text == "*" {
+            return Some(Comparator {
+                op: Op::Wildcard,
+                version: Partial {
+                    major: 0,
+                    minor: None,
+                    patch: None,
+                },
+            });
+        }
+        let operators = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ];
+        let (op, rest) = operators
+            .iter()
+            .find_map(|&(prefix, op)| text.strip_prefix(prefix).map(|rest| (op, rest)))
+            .unwrap_or((Op::Caret, text));
+        Some(Comparator {
+            op,
+            version: Partial::parse(rest.trim())?,
+        })
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let (low, past) = self.range();
+        low.is_none_or(|low| *version >= low) && past.is_none_or(|past| *version < past)
+    }
+
+    /// The versions this comparator accepts, from the first bound
+    /// (inclusive) to the second (exclusive); `None` leaves that side open.
+    fn range(&self) -> (Option<Version>, Option<Version>) {
+        let v = self.version;
+        match self.op {
+            Op::Wildcard => (None, None),
+            Op::Exact => (Some(v.lowest()), Some(v.past())),
+            Op::GreaterEq => (Some(v.lowest()), None),
+            Op::Greater => (Some(v.past()), None),
+            Op::Less => (None, Some(v.lowest())),
+            Op::LessEq => (None, Some(v.past())),
+            Op::Tilde => {
+                let minor = Partial { patch: None, ..v };
+                (Some(v.lowest()), Some(minor.past()))
+            }
+            // The leftmost non-zero part must not change.
+            Op::Caret => {
+                let fixed = match (v.major, v.minor, v.patch) {
+                    (0, Some(0), Some(_)) => v,
+                    (0, Some(_), _) => Partial { patch: None, ..v },
+                    _ => Partial {
+                        minor: None,
+                        patch: None,
+                        ..v
+                    },
+                };
+                (Some(v.lowest()), Some(fixed.past()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.op.as_str())?;
+        if self.op == Op::Wildcard {
+            return Ok(());
+        }
+        write!(f, "{}", self.version.major)?;
+        if let Some(minor) = self.version.minor {
+            write!(f, ".{}", minor)?;
+        }
+        if let Some(patch) = self.version.patch {
+            write!(f, ".{}", patch)?;
+        }
+        Ok(())
+    }
+}
+
+impl A2AAgent {
+    /// Like [`send_task`](Self::send_task), but only send the task if the
+    /// target declares a version of `action` satisfying `requirement`,
+    /// failing with [`A2AError::VersionMismatch`] otherwise. The highest
+    /// such version is chosen and sent along, so the handler finds it in
+    /// [`TaskContext::version`](crate::TaskContext::version), and the input
+    /// is validated against that version's schema if validation is on.
+    ///
+    /// ```rust,no_run
+    /// use a2a::A2AAgent;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), a2a::A2AError> {
+    /// let agent = A2AAgent::new("client", "Client", vec![]);
+    /// let result = agent
+    ///     .send_task_versioned("calc", "add", &"^2.0".parse()?, json!({"a": 1, "b": 2}), "http://localhost:8080")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_task_versioned(
+        &self,
+        target_agent_id: &str,
+        action: &str,
+        requirement: &VersionReq,
+        input: Value,
+        directory_url: &str,
+    ) -> Result<TaskResult, A2AError> {
+        let task = async {
+            let agent_info = self.resolve_agent(target_agent_id, directory_url).await?;
+            let version = best_version(&agent_info, action, requirement)?;
+            self.send_to(&agent_info, action, Some(version), input, &[], &self.method("task"))
+                .await
+        };
+        self.measured(action, task).await
+    }
+}
+
+/// The highest version of `action` that `agent` declares and `requirement`
+/// accepts.
+fn best_version(agent: &AgentInfo, action: &str, requirement: &VersionReq) -> Result<Version, A2AError> {
+    let mut available: Vec<Version> = validation::versions(agent, action).collect();
+    available.sort();
+    match available.iter().rev().find(|version| requirement.matches(version)) {
+        Some(version) => Ok(*version),
+        None => Err(A2AError::VersionMismatch {
+            agent_id: agent.agent_id.clone(),
+            action: action.to_string(),
+            required: requirement.to_string(),
+            available,
+        }),
+    }
+}
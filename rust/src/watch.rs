@@ -0,0 +1,216 @@
+//! Following directory changes as they happen.
+//!
+//! `GET /a2a/watch` on a directory answers with `text/event-stream`: one
+//! event per change, named after its [`DirectoryEventKind`], carrying the
+//! [`DirectoryEvent`] as JSON. Idle streams get a keep-alive comment every
+//! 15 seconds.
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::stream::{SseDecoder, SseEvent};
+use crate::{time, transport, A2AAgent, A2AError, AgentInfo, CapabilityQuery};
+
+/// What happened to the agent of a [`DirectoryEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryEventKind {
+    /// The agent was not listed before, or its registration had expired.
+    Registered,
+    /// A listed agent registered again with different details, or changed
+    /// its capabilities. Heartbeats that change nothing send no event.
+    Updated,
+    /// The agent deregistered. Agents dropped because their TTL ran out
+    /// send no event.
+    Deregistered,
+}
+
+impl DirectoryEventKind {
+    /// The SSE event name, as in `"registered"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DirectoryEventKind::Registered => "registered",
+            DirectoryEventKind::Updated => "updated",
+            DirectoryEventKind::Deregistered => "deregistered",
+        }
+    }
+}
+
+/// One change to a directory's listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryEvent {
+    pub kind: DirectoryEventKind,
+    /// The agent as listed after the change; for `Deregistered`, as it
+    /// was last listed.
+    pub agent: AgentInfo,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type DirectoryEventStream = Pin<Box<dyn Stream<Item = DirectoryEvent> + Send>>;
+
+/// Browser response bodies are not `Send`, so neither are their streams.
+#[cfg(target_arch = "wasm32")]
+pub type DirectoryEventStream = Pin<Box<dyn Stream<Item = DirectoryEvent>>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type Body = Pin<Box<dyn Stream<Item = Result<Vec<u8>, reqwest::Error>> + Send>>;
+
+#[cfg(target_arch = "wasm32")]
+type Body = Pin<Box<dyn Stream<Item = Result<Vec<u8>, reqwest::Error>>>>;
+
+/// First wait before reconnecting, doubled after each failed attempt.
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A stream that has not even sent a keep-alive for this long is dead.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Overrides the agent's request timeout, which would otherwise cut the
+/// stream off after a few seconds.
+#[cfg(not(target_arch = "wasm32"))]
+const STREAM_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+impl A2AAgent {
+    /// Follow changes to the agents in `directory_url` satisfying
+    /// `filter`, as they happen; an empty `CapabilityQuery::All` follows
+    /// every agent. Nothing is sent until the stream is first polled.
+    ///
+    /// The stream never ends. When the connection fails or drops it
+    /// reconnects, waiting from half a second up to 30 seconds between
+    /// attempts, and logs why; changes made while disconnected are not
+    /// replayed, so re-read the listing with
+    /// [`discover_matching`](Self::discover_matching) if a gap matters.
+    /// Only `directory_url` is watched, over HTTP, whatever
+    /// [`Transport`](crate::Transport) or fallback directories the agent
+    /// has.
+    ///
+    /// ```rust,no_run
+    /// use a2a::{A2AAgent, CapabilityQuery};
+    /// use futures::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let agent = A2AAgent::new("dashboard", "Dashboard", vec![]);
+    /// let mut events = agent.watch_agents(CapabilityQuery::All(vec![]), "http://localhost:8080");
+    /// while let Some(event) = events.next().await {
+    ///     println!("{}: {}", event.kind.as_str(), event.agent.agent_id);
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_agents(&self, filter: CapabilityQuery, directory_url: &str) -> DirectoryEventStream {
+        let state = Watch {
+            agent: self.clone(),
+            url: self.directory_path(directory_url.trim_end_matches('/'), "watch"),
+            filter,
+            body: None,
+            decoder: SseDecoder::default(),
+            pending: VecDeque::new(),
+            delay: None,
+        };
+        Box::pin(futures::stream::unfold(state, |mut state| async move {
+            let event = state.next().await;
+            Some((event, state))
+        }))
+    }
+}
+
+struct Watch {
+    agent: A2AAgent,
+    url: String,
+    filter: CapabilityQuery,
+    body: Option<Body>,
+    decoder: SseDecoder,
+    pending: VecDeque<SseEvent>,
+    /// How long to wait before connecting; `None` for the first attempt.
+    delay: Option<Duration>,
+}
+
+impl Watch {
+    async fn next(&mut self) -> DirectoryEvent {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                match self.decode(event) {
+                    Some(event) if self.filter.matches(&event.agent.capabilities) => return event,
+                    _ => continue,
+                }
+            }
+
+            let Some(body) = self.body.as_mut() else {
+                if let Some(delay) = self.delay {
+                    time::sleep(delay).await;
+                }
+                match connect(&self.agent, &self.url).await {
+                    Ok(body) => {
+                        debug!(url = %self.url, "watching directory");
+                        self.body = Some(body);
+                        self.decoder = SseDecoder::default();
+                    }
+                    Err(e) => {
+                        warn!(url = %self.url, error = %e, "watching directory failed, reconnecting");
+                        self.back_off();
+                    }
+                }
+                continue;
+            };
+
+            match time::timeout(IDLE_TIMEOUT, body.next()).await {
+                Some(Some(Ok(bytes))) => {
+                    // The connection works, so a drop is worth retrying
+                    // soon.
+                    self.delay = None;
+                    let events = self.decoder.push(&bytes);
+                    self.pending.extend(events);
+                }
+                Some(Some(Err(e))) => {
+                    warn!(url = %self.url, error = %e, "directory watch dropped, reconnecting");
+                    self.disconnect();
+                }
+                Some(None) => {
+                    debug!(url = %self.url, "directory closed the watch, reconnecting");
+                    self.disconnect();
+                }
+                None => {
+                    warn!(url = %self.url, "directory watch went quiet, reconnecting");
+                    self.disconnect();
+                }
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.body = None;
+        self.back_off();
+    }
+
+    fn back_off(&mut self) {
+        self.delay = Some(match self.delay {
+            Some(delay) => (delay * 2).min(MAX_RECONNECT_DELAY),
+            None => RECONNECT_DELAY,
+        });
+    }
+
+    /// The change an SSE event reports, skipping kinds this client does
+    /// not know.
+    fn decode(&self, event: SseEvent) -> Option<DirectoryEvent> {
+        match serde_json::from_str(&event.data) {
+            Ok(change) => Some(change),
+            Err(e) => {
+                debug!(event = %event.event, error = %e, "skipping unreadable directory event");
+                None
+            }
+        }
+    }
+}
+
+/// Open the event stream at `url`. Takes no `Watch`, whose body is not
+/// `Sync`, so the stream stays `Send`.
+async fn connect(agent: &A2AAgent, url: &str) -> Result<Body, A2AError> {
+    let request = agent.http.client.get(url).header(reqwest::header::ACCEPT, "text/event-stream");
+    #[cfg(not(target_arch = "wasm32"))]
+    let request = request.timeout(STREAM_TIMEOUT);
+    let response = agent.http.send(request, "a2a/watch").await?;
+    let response = transport::check_status(response).await?;
+    Ok(Box::pin(response.bytes_stream().map(|bytes| bytes.map(Vec::from))))
+}
@@ -0,0 +1,456 @@
+//! WebSocket transport, behind the `ws` feature.
+//!
+//! [`A2AServer`](crate::A2AServer) accepts persistent connections on
+//! `GET /ws`. Every text frame carries one JSON-RPC message, so one
+//! connection carries many calls at once, answered in any order and
+//! matched by `id`, and the server can push messages of its own: over a
+//! WebSocket, `a2a/task/stream` is answered with one `a2a/task/chunk`
+//! notification per [`TaskChunk`](crate::TaskChunk), then a response
+//! carrying the final [`TaskResult`]. Batches are not accepted; send each
+//! call as its own frame.
+//!
+//! Both ends ping every 20 seconds and drop a connection that stays
+//! silent for a minute. Open a session with
+//! [`A2AAgent::connect_ws`](crate::A2AAgent::connect_ws).
+
+use axum::extract::ws::{self as server_ws, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::{self, Message as ClientMessage};
+
+use crate::{A2AAgent, A2AError, JSONRPCRequest, JSONRPCResponse, TaskResult, DEFAULT_TIMEOUT, JSONRPC_VERSION};
+
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+/// How long a connection may stay silent, pongs included, before it is
+/// dropped.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// A notification pushed by the server, such as an `a2a/task/chunk`
+/// whose `params` is one [`TaskChunk`](crate::TaskChunk).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsNotification {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// What the writer task sends. Pongs are left to the WebSocket library,
+/// which answers every ping.
+enum Message {
+    Text(String),
+    Ping,
+    Close,
+}
+
+/// What the read loop makes of a received message.
+enum Received {
+    Text(String),
+    Control,
+    Binary,
+    Close,
+}
+
+/// The message type of one side's WebSocket library.
+trait Frame: Send + 'static {
+    fn outgoing(message: Message) -> Self;
+    fn received(self) -> Received;
+}
+
+impl Frame for server_ws::Message {
+    fn outgoing(message: Message) -> Self {
+        match message {
+            Message::Text(text) => Self::Text(text.into()),
+            Message::Ping => Self::Ping(Default::default()),
+            Message::Close => Self::Close(None),
+        }
+    }
+
+    fn received(self) -> Received {
+        match self {
+            Self::Text(text) => Received::Text(text.to_string()),
+            Self::Binary(_) => Received::Binary,
+            Self::Ping(_) | Self::Pong(_) => Received::Control,
+            Self::Close(_) => Received::Close,
+        }
+    }
+}
+
+impl Frame for ClientMessage {
+    fn outgoing(message: Message) -> Self {
+        match message {
+            Message::Text(text) => Self::Text(text.into()),
+            Message::Ping => Self::Ping(Default::default()),
+            Message::Close => Self::Close(None),
+        }
+    }
+
+    fn received(self) -> Received {
+        match self {
+            Self::Text(text) => Received::Text(text.to_string()),
+            Self::Binary(_) => Received::Binary,
+            Self::Ping(_) | Self::Pong(_) | Self::Frame(_) => Received::Control,
+            Self::Close(_) => Received::Close,
+        }
+    }
+}
+
+// ============ Connection plumbing ============
+
+/// Queue of frames for a connection's writer task.
+#[derive(Clone)]
+pub(crate) struct Outbox(mpsc::UnboundedSender<Message>);
+
+impl Outbox {
+    /// Queue `value` as a text frame. Returns `false` once the connection
+    /// is gone.
+    pub(crate) fn send(&self, value: &impl Serialize) -> bool {
+        match serde_json::to_string(value) {
+            Ok(text) => self.0.send(Message::Text(text)).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// Queue a JSON-RPC notification.
+    pub(crate) fn notify(&self, method: &str, params: &impl Serialize) -> bool {
+        self.send(&json!({"jsonrpc": JSONRPC_VERSION, "method": method, "params": params}))
+    }
+
+    fn control(&self, message: Message) -> bool {
+        self.0.send(message).is_ok()
+    }
+}
+
+/// Spawn the task that owns the write half. It stops after sending a
+/// close frame or once every [`Outbox`] is dropped.
+fn spawn_writer<W, M>(mut writer: W) -> Outbox
+where
+    W: Sink<M> + Unpin + Send + 'static,
+    M: Frame,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let closing = matches!(message, Message::Close);
+            if writer.send(M::outgoing(message)).await.is_err() || closing {
+                break;
+            }
+        }
+        let _ = writer.close().await;
+    });
+    Outbox(tx)
+}
+
+fn spawn_keepalive(outbox: Outbox) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PING_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if !outbox.control(Message::Ping) {
+                break;
+            }
+        }
+    })
+}
+
+/// Read messages until the peer closes, fails or goes quiet, and pass
+/// each text message to `on_text`.
+async fn read_loop<R, M, E>(mut reader: R, outbox: &Outbox, mut on_text: impl FnMut(String))
+where
+    R: Stream<Item = Result<M, E>> + Unpin,
+    M: Frame,
+    E: Display,
+{
+    let keepalive = spawn_keepalive(outbox.clone());
+
+    loop {
+        let message = match tokio::time::timeout(IDLE_TIMEOUT, reader.next()).await {
+            Ok(Some(Ok(message))) => message,
+            Ok(Some(Err(e))) => {
+                debug!(error = %e, "websocket connection failed");
+                break;
+            }
+            Ok(None) => break,
+            Err(_) => {
+                debug!("websocket peer went quiet");
+                break;
+            }
+        };
+
+        match message.received() {
+            Received::Text(text) => on_text(text),
+            Received::Control => {}
+            Received::Binary => {
+                debug!("binary websocket frames are not supported");
+                break;
+            }
+            Received::Close => break,
+        }
+    }
+
+    keepalive.abort();
+    outbox.control(Message::Close);
+}
+
+// ============ Server side ============
+
+/// Accept an upgrade, then run `handle` on every text message of the
+/// connection concurrently, with an [`Outbox`] to reply through.
+pub(crate) fn accept<F, Fut>(upgrade: WebSocketUpgrade, handle: F) -> Response
+where
+    F: Fn(String, Outbox) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    upgrade
+        .max_message_size(MAX_MESSAGE_BYTES)
+        .on_upgrade(move |socket| serve(socket, handle))
+}
+
+async fn serve<F, Fut>(socket: WebSocket, handle: F)
+where
+    F: Fn(String, Outbox) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    debug!("websocket connected");
+    let (writer, reader) = socket.split();
+    let outbox = spawn_writer(writer);
+    read_loop(reader, &outbox, |text| {
+        tokio::spawn(handle(text, outbox.clone()));
+    })
+    .await;
+}
+
+// ============ Client side ============
+
+/// Response waiters by request id; `None` once the connection is closed.
+type Pending = Arc<Mutex<Option<HashMap<String, oneshot::Sender<JSONRPCResponse>>>>>;
+
+struct Connection {
+    outbox: Outbox,
+    pending: Pending,
+    closed: watch::Receiver<bool>,
+}
+
+impl Connection {
+    fn is_open(&self) -> bool {
+        !*self.closed.borrow()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.outbox.control(Message::Close);
+    }
+}
+
+/// A persistent WebSocket connection to one agent, opened with
+/// [`A2AAgent::connect_ws`]. When the connection drops, the next call
+/// reconnects.
+pub struct WsSession {
+    url: reqwest::Url,
+    agent: A2AAgent,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    notify_tx: mpsc::UnboundedSender<WsNotification>,
+    notifications: tokio::sync::Mutex<mpsc::UnboundedReceiver<WsNotification>>,
+}
+
+impl WsSession {
+    pub(crate) async fn connect(agent: A2AAgent, endpoint: &str) -> Result<Self, A2AError> {
+        let url = reqwest::Url::parse(endpoint)
+            .map_err(|e| A2AError::WebSocket(format!("invalid endpoint {}: {}", endpoint, e)))?;
+        if !matches!(url.scheme(), "ws" | "wss") {
+            return Err(A2AError::WebSocket(format!("expected a ws:// or wss:// endpoint: {}", endpoint)));
+        }
+
+        let (notify_tx, notifications) = mpsc::unbounded_channel();
+        let session = Self {
+            url,
+            agent,
+            connection: tokio::sync::Mutex::new(None),
+            notify_tx,
+            notifications: tokio::sync::Mutex::new(notifications),
+        };
+        session.connected().await?;
+        Ok(session)
+    }
+
+    /// Send a task over the connection and wait for its result.
+    pub async fn send_task(&self, action: &str, input: Value) -> Result<TaskResult, A2AError> {
+        let result = self.call(&self.agent.method("task"), self.task_params(action, input)?, Some(DEFAULT_TIMEOUT)).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Run a streaming task. Its chunks arrive through [`recv`](Self::recv)
+    /// as `a2a/task/chunk` notifications while this waits for the final
+    /// result, so poll both at once.
+    pub async fn stream_task(&self, action: &str, input: Value) -> Result<TaskResult, A2AError> {
+        let result = self.call(&self.agent.method("task/stream"), self.task_params(action, input)?, None).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Wait for the next notification pushed by the server. Fails if the
+    /// connection drops first.
+    pub async fn recv(&self) -> Result<WsNotification, A2AError> {
+        let mut closed = self.connected().await?;
+        let mut notifications = self.notifications.lock().await;
+        tokio::select! {
+            biased;
+            Some(notification) = notifications.recv() => Ok(notification),
+            _ = closed.wait_for(|closed| *closed) => Err(connection_closed()),
+        }
+    }
+
+    /// Whether the connection is currently open.
+    pub async fn is_connected(&self) -> bool {
+        self.connection.lock().await.as_ref().is_some_and(Connection::is_open)
+    }
+
+    /// Close the connection. A later call opens a new one.
+    pub async fn close(&self) {
+        self.connection.lock().await.take();
+    }
+
+    fn task_params(&self, action: &str, input: Value) -> Result<Value, A2AError> {
+        let params = self.agent.task_params(action, input);
+        Ok(serde_json::to_value(params)?)
+    }
+
+    async fn call(&self, method: &str, params: Value, timeout: Option<Duration>) -> Result<Value, A2AError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+
+        let pending = {
+            let mut connection = self.connection.lock().await;
+            let connection = self.open(&mut connection).await?;
+            match connection.pending.lock().unwrap().as_mut() {
+                Some(pending) => pending.insert(id.clone(), tx),
+                None => return Err(connection_closed()),
+            };
+
+            let request = JSONRPCRequest {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id: Value::String(id.clone()),
+                method: method.to_string(),
+                params: Some(params),
+            };
+            if !connection.outbox.send(&request) {
+                return Err(connection_closed());
+            }
+            connection.pending.clone()
+        };
+        debug!(method, "sent over websocket");
+
+        let response = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, rx).await.map_err(|_| {
+                if let Some(pending) = pending.lock().unwrap().as_mut() {
+                    pending.remove(&id);
+                }
+                A2AError::WebSocket(format!("no response to {} within {:?}", method, timeout))
+            })?,
+            None => rx.await,
+        };
+        response.map_err(|_| connection_closed())?.into_result()
+    }
+
+    /// The open connection's close signal, reconnecting first if needed.
+    async fn connected(&self) -> Result<watch::Receiver<bool>, A2AError> {
+        let mut connection = self.connection.lock().await;
+        Ok(self.open(&mut connection).await?.closed.clone())
+    }
+
+    async fn open<'a>(&self, connection: &'a mut Option<Connection>) -> Result<&'a Connection, A2AError> {
+        if !connection.as_ref().is_some_and(Connection::is_open) {
+            *connection = Some(self.dial().await?);
+        }
+        Ok(connection.as_ref().expect("connection was just opened"))
+    }
+
+    async fn dial(&self) -> Result<Connection, A2AError> {
+        info!(url = %self.url, "opening websocket");
+        // Reuse the agent's credentials for the upgrade request.
+        let authorized = self.agent.http.authorize(self.agent.http.client.get(self.url.clone())).await?.build()?;
+        let mut request = self.url.as_str().into_client_request().map_err(websocket_error)?;
+        request.headers_mut().extend(authorized.headers().clone());
+
+        let config = WebSocketConfig::default().max_message_size(Some(MAX_MESSAGE_BYTES));
+        let (stream, _) = tokio_tungstenite::connect_async_with_config(request, Some(config), false)
+            .await
+            .map_err(websocket_error)?;
+
+        let (writer, reader) = stream.split();
+        let outbox = spawn_writer(writer);
+        let pending: Pending = Arc::new(Mutex::new(Some(HashMap::new())));
+        let (closed_tx, closed) = watch::channel(false);
+
+        let notifications = self.notify_tx.clone();
+        let (reader_outbox, reader_pending) = (outbox.clone(), pending.clone());
+        tokio::spawn(async move {
+            read_loop(reader, &reader_outbox, |text| route(&text, &reader_pending, &notifications)).await;
+            // Dropping the waiters makes their calls fail.
+            reader_pending.lock().unwrap().take();
+            closed_tx.send_replace(true);
+        });
+
+        Ok(Connection { outbox, pending, closed })
+    }
+}
+
+/// Hand a message from the server to whoever is waiting for it.
+fn route(text: &str, pending: &Pending, notifications: &mpsc::UnboundedSender<WsNotification>) {
+    let raw: Value = match serde_json::from_str(text) {
+        Ok(raw) => raw,
+        Err(e) => {
+            warn!(error = %e, "ignoring malformed websocket message");
+            return;
+        }
+    };
+
+    if raw.get("method").is_some() {
+        match serde_json::from_value(raw) {
+            Ok(notification) => {
+                let _ = notifications.send(notification);
+            }
+            Err(e) => warn!(error = %e, "ignoring malformed notification"),
+        }
+        return;
+    }
+
+    let response: JSONRPCResponse = match serde_json::from_value(raw) {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(error = %e, "ignoring malformed response");
+            return;
+        }
+    };
+    let waiter = response
+        .id
+        .as_str()
+        .and_then(|id| pending.lock().unwrap().as_mut()?.remove(id));
+    if let Some(waiter) = waiter {
+        let _ = waiter.send(response);
+    }
+}
+
+fn connection_closed() -> A2AError {
+    A2AError::WebSocket("connection closed".to_string())
+}
+
+fn websocket_error(e: tungstenite::Error) -> A2AError {
+    match e {
+        tungstenite::Error::Io(e) => A2AError::Io(e),
+        tungstenite::Error::Http(response) => A2AError::WebSocket(format!("upgrade rejected: {}", response.status())),
+        e => A2AError::WebSocket(e.to_string()),
+    }
+}
@@ -0,0 +1,185 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, A2AError, AgentInfo, AgentListExt};
+use chrono::{DateTime, TimeZone, Utc};
+use std::time::SystemTime;
+use serde_json::json;
+
+fn agent(registered_at: serde_json::Value) -> AgentInfo {
+    serde_json::from_value(json!({
+        "agentId": "calc",
+        "name": "Calc",
+        "capabilities": [],
+        "endpoint": "http://localhost:9001",
+        "registeredAt": registered_at,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn registered_at_parses_rfc3339() {
+    let expected = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap() + chrono::Duration::milliseconds(500);
+    assert_eq!(agent(json!("2024-05-01T10:00:00.500Z")).registered_at_time(), Some(expected));
+    assert_eq!(agent(json!("2024-05-01T12:00:00.5+02:00")).registered_at_time(), Some(expected));
+}
+
+#[test]
+fn malformed_registered_at_is_none() {
+    for text in ["2024-05-01 10:00", "2024-13-01T10:00:00Z", "2024-05-01T10:00:00", "soon"] {
+        assert_eq!(agent(json!(text)).registered_at_time(), None, "{:?}", text);
+    }
+}
+
+#[test]
+fn missing_registered_at_is_none() {
+    assert_eq!(agent(json!(null)).registered_at_time(), None);
+    let bare: AgentInfo =
+        serde_json::from_value(json!({"agentId": "calc", "name": "Calc", "capabilities": [], "endpoint": "http://localhost:9001"})).unwrap();
+    assert_eq!(bare.registered_at_time(), None);
+}
+
+#[tokio::test]
+async fn the_directory_stamps_registrations() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let before = DateTime::<Utc>::from(SystemTime::now());
+    let mut calc = A2AAgent::new("calc", "Calc", vec![]);
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+
+    let listed = A2AAgent::new("client", "Client", vec![]).discover_all(vec![], &directory).await.unwrap();
+    let registered = listed[0].registered_at_time().unwrap();
+    // Stamped to the millisecond, so up to one before `before`.
+    assert!(registered > before - chrono::Duration::milliseconds(1));
+    assert!(registered <= DateTime::<Utc>::from(SystemTime::now()));
+}
+
+#[tokio::test]
+async fn metadata_round_trips_through_the_directory() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()])
+        .with_version("1.2.0")
+        .with_description("Adds numbers")
+        .with_tag("region", "eu")
+        .with_tag("tier", "gold");
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+
+    let listed = A2AAgent::new("client", "Client", vec![]).discover_all(vec![], &directory).await.unwrap();
+    assert_eq!(listed[0].version.as_deref(), Some("1.2.0"));
+    assert_eq!(listed[0].description.as_deref(), Some("Adds numbers"));
+    assert_eq!(listed[0].tags.len(), 2);
+    assert_eq!(listed[0].tags["region"], "eu");
+    assert_eq!(listed[0].tags["tier"], "gold");
+
+    // Listings from older directories leave the metadata empty.
+    let bare = agent(json!(null));
+    assert_eq!((bare.version, bare.description), (None, None));
+    assert!(bare.tags.is_empty());
+}
+
+fn record(agent_id: &str, endpoint: &str, registered_at: serde_json::Value) -> AgentInfo {
+    let mut record = agent(registered_at);
+    record.agent_id = agent_id.to_string();
+    record.endpoint = endpoint.to_string();
+    record
+}
+
+#[test]
+fn records_are_equal_by_agent_id() {
+    let old = record("calc", "http://10.0.0.1:9001", json!("2024-05-01T10:00:00Z"));
+    let mut moved = record("calc", "http://10.0.0.2:9001", json!("2024-05-02T10:00:00Z"));
+    moved.name = "Calculator".to_string();
+    assert_eq!(old, moved);
+    assert_ne!(old, record("search", "http://10.0.0.1:9001", json!("2024-05-01T10:00:00Z")));
+    assert_eq!(std::collections::HashSet::from([old, moved]).len(), 1);
+}
+
+#[test]
+fn dedup_keeps_the_latest_record_of_each_agent() {
+    let mut agents = vec![
+        record("calc", "http://old-calc", json!("2024-05-01T10:00:00Z")),
+        record("search", "http://search", json!(null)),
+        record("calc", "http://new-calc", json!("2024-05-02T10:00:00Z")),
+        record("calc", "http://stale-calc", json!("2024-04-01T10:00:00Z")),
+        record("search", "http://timed-search", json!("2024-05-01T10:00:00Z")),
+    ];
+    agents.dedup_agents();
+
+    let kept: Vec<(&str, &str)> = agents.iter().map(|a| (a.agent_id.as_str(), a.endpoint.as_str())).collect();
+    assert_eq!(kept, [("calc", "http://new-calc"), ("search", "http://timed-search")]);
+}
+
+#[test]
+fn merge_takes_the_later_details() {
+    let mut known = record("calc", "http://10.0.0.1:9001", json!("2024-05-01T10:00:00Z"));
+    known.version = Some("1.0.0".to_string());
+    let mut later = record("other", "http://10.0.0.2:9001", json!(null));
+    later.name = "Calculator".to_string();
+    known.merge(later);
+
+    assert_eq!(known.agent_id, "calc");
+    assert_eq!(known.name, "Calculator");
+    assert_eq!(known.endpoint, "http://10.0.0.2:9001");
+    // Missing in the later record, so kept.
+    assert_eq!(known.version.as_deref(), Some("1.0.0"));
+    assert_eq!(known.registered_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+}
+
+#[test]
+fn camel_and_snake_case_records_read_the_same() {
+    let schema = json!({"type": "object"});
+    let camel: AgentInfo = serde_json::from_value(json!({
+        "agentId": "calc",
+        "name": "Calc",
+        "capabilities": [{"name": "add", "inputSchema": schema, "outputSchema": schema}],
+        "endpoint": "http://localhost:9001",
+        "registeredAt": "2024-05-01T10:00:00Z",
+    }))
+    .unwrap();
+    let snake: AgentInfo = serde_json::from_value(json!({
+        "agent_id": "calc",
+        "name": "Calc",
+        "capabilities": [{"name": "add", "input_schema": schema, "output_schema": schema}],
+        "endpoint": "http://localhost:9001",
+        "registered_at": "2024-05-01T10:00:00Z",
+    }))
+    .unwrap();
+
+    for agent in [&camel, &snake] {
+        assert_eq!(agent.agent_id, "calc");
+        assert_eq!(agent.registered_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+        assert_eq!(agent.capabilities[0].input_schema, Some(schema.clone()));
+        assert_eq!(agent.capabilities[0].output_schema, Some(schema.clone()));
+    }
+
+    // Either way it is written back in camelCase.
+    let written = serde_json::to_value(&snake).unwrap();
+    assert_eq!(written, serde_json::to_value(&camel).unwrap());
+    assert_eq!(written["agentId"], "calc");
+    assert_eq!(written["registeredAt"], "2024-05-01T10:00:00Z");
+    assert_eq!(written["capabilities"][0]["inputSchema"], schema);
+    for snake_key in ["agent_id", "registered_at"] {
+        assert!(written.get(snake_key).is_none(), "{}", snake_key);
+    }
+}
+
+#[test]
+fn new_records_need_an_absolute_http_endpoint() {
+    for endpoint in ["http://10.0.0.5:9001", "https://calc.example.com/a2a", "http://localhost:9001/"] {
+        let agent = AgentInfo::new("calc", "Calc", vec!["add".to_string()], endpoint).unwrap();
+        assert_eq!(agent.endpoint, endpoint);
+        assert_eq!(agent.capabilities[0].name, "add");
+        assert_eq!((agent.registered_at, agent.version), (None, None));
+    }
+
+    for endpoint in ["localhost:9001", "/a2a", "ftp://10.0.0.5", "ws://10.0.0.5:9001", "http://", "http//calc", ""] {
+        let refused = AgentInfo::new("calc", "Calc", vec![], endpoint);
+        assert!(matches!(refused, Err(A2AError::InvalidEndpoint(_))), "{:?}: {:?}", endpoint, refused);
+    }
+
+    let mut agent = AgentInfo::new("calc", "Calc", vec![], "http://10.0.0.1:9001").unwrap();
+    agent.set_endpoint("https://10.0.0.2:9001").unwrap();
+    assert_eq!(agent.endpoint, "https://10.0.0.2:9001");
+    assert!(matches!(agent.set_endpoint("10.0.0.3:9001"), Err(A2AError::InvalidEndpoint(_))));
+    assert_eq!(agent.endpoint, "https://10.0.0.2:9001");
+}
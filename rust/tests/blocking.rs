@@ -0,0 +1,47 @@
+#![cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::blocking::A2AAgent;
+use a2a::{A2ADirectory, A2AError, A2AServer};
+use serde_json::json;
+use std::sync::mpsc;
+
+/// Serve a directory and an adder from a runtime on another thread, as a
+/// synchronous program would find them, returning their base URLs.
+fn serve_in_background() -> (String, String) {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let mut server = A2AServer::new("calc", "Calc", vec!["add".to_string()], 0);
+            server.handle_task(|_ctx, input| json!(input["a"].as_i64().unwrap() + input["b"].as_i64().unwrap()));
+            let endpoint = common::serve(&server).await;
+            let directory = common::directory(A2ADirectory::new(0)).await;
+            sender.send((endpoint, directory)).unwrap();
+            std::future::pending::<()>().await
+        })
+    });
+    receiver.recv().unwrap()
+}
+
+#[test]
+fn blocking_agents_register_discover_and_send() {
+    let (endpoint, directory) = serve_in_background();
+
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()]).unwrap();
+    calc.register(&endpoint, &directory).unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]).unwrap();
+    client.ping(&endpoint).unwrap();
+
+    let found = client.discover(vec!["add".to_string()], &directory).unwrap();
+    assert_eq!(found.map(|agent| agent.agent_id), Some("calc".to_string()));
+    let result = client.send_task("calc", "add", json!({"a": 2, "b": 3}), &directory).unwrap();
+    assert_eq!(result.output, Some(json!(5)));
+    let sum: i64 = client.send_task_typed("calc", "add", json!({"a": 4, "b": 5}), &directory).unwrap();
+    assert_eq!(sum, 9);
+
+    calc.deregister(&directory).unwrap();
+    let gone = client.send_task("calc", "add", json!({"a": 2, "b": 3}), &directory);
+    assert!(matches!(gone, Err(A2AError::AgentNotFound(_))), "{:?}", gone);
+}
@@ -0,0 +1,84 @@
+#![cfg(all(feature = "tracing", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2AAgent, AuthConfig};
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Collects every event's fields as text, one line per event.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<String>>);
+
+impl Capture {
+    fn text(&self) -> String {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Visit for Capture {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = write!(self.0.lock().unwrap(), "{}={:?} ", field.name(), value);
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        event.record(&mut self.clone());
+        self.0.lock().unwrap().push('\n');
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[tokio::test]
+async fn redacted_fields_and_credentials_stay_out_of_the_log() {
+    let (directory, _calls) = common::recording(json!({"agents": [], "apiKey": "response-secret"})).await;
+    let capture = Capture::default();
+    let _logging = tracing::subscriber::set_default(capture.clone());
+
+    let client = A2AAgent::new("client", "Client", vec![])
+        .with_auth(AuthConfig::Bearer("bearer-secret".to_string()))
+        .with_body_logging(vec!["params.tags.password".to_string(), "result.apiKey".to_string()]);
+    let tags = std::collections::HashMap::from([
+        ("password".to_string(), "request-secret".to_string()),
+        ("region".to_string(), "eu".to_string()),
+    ]);
+    let query = a2a::CapabilityQuery::All(vec!["add".to_string()]);
+    client.discover_filtered(query, tags, &directory).await.unwrap();
+
+    let log = capture.text();
+    assert!(log.contains("request body") && log.contains("response body"), "{}", log);
+    assert!(log.contains("eu") && log.contains("***"), "{}", log);
+    for secret in ["request-secret", "response-secret", "bearer-secret"] {
+        assert!(!log.contains(secret), "{} logged: {}", secret, log);
+    }
+}
+
+#[tokio::test]
+async fn bodies_are_not_logged_by_default() {
+    let (directory, _calls) = common::recording(json!({"agents": []})).await;
+    let capture = Capture::default();
+    let _logging = tracing::subscriber::set_default(capture.clone());
+
+    A2AAgent::new("client", "Client", vec![]).discover(vec![], &directory).await.unwrap();
+    assert!(!capture.text().contains("request body"), "{}", capture.text());
+}
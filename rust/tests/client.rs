@@ -0,0 +1,1049 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{
+    A2AAgent, A2ADirectory, A2AError, A2AServer, AuthConfig, Capability, CapabilityQuery, CircuitBreaker, Outbox,
+    PendingTask, RateLimit, RetryPolicy, SelectionStrategy, TaskBatcher, TaskError, TaskStatus, TokenProvider, Version,
+};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn requests_give_up_after_the_timeout() {
+    let router = Router::new().route(
+        "/a2a/agents/{id}",
+        get(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            "too late"
+        }),
+    );
+    let directory = common::mock(router).await;
+
+    let agent = A2AAgent::with_timeout("client", "Client", vec![], Duration::from_millis(200))
+        .with_retry_policy(RetryPolicy::none());
+    let started = Instant::now();
+    let failed = agent.send_task("slow", "wait", json!({}), &directory).await;
+    assert!(matches!(failed, Err(A2AError::Timeout(_))), "{:?}", failed);
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+/// A directory whose first `failures` register calls fail with `status`.
+async fn flaky_directory(failures: usize, status: StatusCode) -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let router = Router::new().route(
+        "/a2a/register",
+        post(move |Json(call): Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    return Err(status);
+                }
+                Ok(Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"status": "registered"}})))
+            }
+        }),
+    );
+    (common::mock(router).await, calls)
+}
+
+#[tokio::test]
+async fn failed_calls_are_retried_with_backoff() {
+    let (directory, calls) = flaky_directory(2, StatusCode::BAD_GATEWAY).await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::new(3, Duration::from_millis(20)));
+
+    let started = Instant::now();
+    agent.register("http://calc.invalid/", &directory).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    // Waits of 20ms then 40ms, jittered down to half at most.
+    assert!(started.elapsed() >= Duration::from_millis(30));
+}
+
+#[tokio::test]
+async fn retries_stop_at_the_limit_and_skip_client_errors() {
+    let (directory, calls) = flaky_directory(usize::MAX, StatusCode::SERVICE_UNAVAILABLE).await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1)));
+    let failed = agent.register("http://calc.invalid/", &directory).await;
+    assert!(matches!(failed, Err(A2AError::HttpStatus { .. })), "{:?}", failed);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let (directory, calls) = flaky_directory(usize::MAX, StatusCode::BAD_REQUEST).await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1)));
+    assert!(agent.register("http://calc.invalid/", &directory).await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+/// A directory whose first `failures` register calls answer 429 with
+/// `Retry-After: retry_after`.
+async fn throttling_directory(failures: usize, retry_after: &'static str) -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let router = Router::new().route(
+        "/a2a/register",
+        post(move |Json(call): Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    return Err((StatusCode::TOO_MANY_REQUESTS, [("retry-after", retry_after)]));
+                }
+                Ok(Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"status": "registered"}})))
+            }
+        }),
+    );
+    (common::mock(router).await, calls)
+}
+
+#[tokio::test]
+async fn throttled_calls_wait_for_the_servers_retry_after() {
+    let (directory, calls) = throttling_directory(1, "1").await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1)));
+    let started = Instant::now();
+    agent.register("http://calc.invalid/", &directory).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+
+    // Without retries, or asked to wait longer than the policy allows, the
+    // server's delay is surfaced instead.
+    let (directory, calls) = throttling_directory(1, "1").await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::none());
+    match agent.register("http://calc.invalid/", &directory).await {
+        Err(A2AError::RateLimited { retry_after, .. }) => assert_eq!(retry_after, Some(Duration::from_secs(1))),
+        other => panic!("expected RateLimited, got {:?}", other),
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (directory, calls) = throttling_directory(1, "120").await;
+    let policy = RetryPolicy::new(2, Duration::from_millis(1)).with_max_retry_after(Duration::from_secs(5));
+    let mut agent = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(policy);
+    let started = Instant::now();
+    match agent.register("http://calc.invalid/", &directory).await {
+        Err(A2AError::RateLimited { retry_after, .. }) => assert_eq!(retry_after, Some(Duration::from_secs(120))),
+        other => panic!("expected RateLimited, got {:?}", other),
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn inputs_are_checked_against_the_capability_schema() {
+    let mut server = A2AServer::new("calc", "Calc", vec![], 0);
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    server.handle_task(move |_ctx, input| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        input
+    });
+    let schema = json!({"type": "object", "required": ["a", "b"], "properties": {"a": {"type": "number"}}});
+    let directory = common::lookup(&common::serve(&server).await, json!([{"name": "add", "inputSchema": schema}])).await;
+    let agent = A2AAgent::new("client", "Client", vec![]).with_input_validation(true);
+
+    agent.send_task("calc", "add", json!({"a": 1, "b": 2}), &directory).await.unwrap();
+    for input in [json!({"a": 1}), json!({"a": "one", "b": 2})] {
+        let refused = agent.send_task("calc", "add", input, &directory).await;
+        assert!(matches!(refused, Err(A2AError::InvalidInput { .. })), "{:?}", refused);
+    }
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    // Off by default.
+    A2AAgent::new("client", "Client", vec![]).send_task("calc", "add", json!({}), &directory).await.unwrap();
+}
+
+#[tokio::test]
+async fn agents_send_through_the_client_they_are_given() {
+    let (directory, calls) = common::recording(json!({"agents": []})).await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-pool", "shared".parse().unwrap());
+    let client = reqwest::Client::builder().default_headers(headers).build().unwrap();
+
+    for agent_id in ["one", "two"] {
+        let agent = A2AAgent::with_client(agent_id, agent_id, vec![], client.clone());
+        agent.discover(vec!["add".to_string()], &directory).await.unwrap();
+    }
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    for (_, headers, _) in calls.iter() {
+        assert_eq!(headers["x-pool"], "shared");
+    }
+}
+
+#[tokio::test]
+async fn bearer_tokens_go_to_the_directory_and_the_peer() {
+    let (directory, calls) = common::directory_and_peer().await;
+    let agent = A2AAgent::new("client", "Client", vec![]).with_auth(AuthConfig::Bearer("s3cret".to_string()));
+    agent.send_task("calc", "add", json!({}), &directory).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    let paths: Vec<_> = calls.iter().map(|(path, _, _)| path.as_str()).collect();
+    assert_eq!(paths, ["/a2a/agents/calc", "/rpc"]);
+    for (_, headers, _) in calls.iter() {
+        assert_eq!(headers["authorization"], "Bearer s3cret");
+    }
+}
+
+/// Hands out `token-0`, `token-1`, ... as if rotating on every call.
+struct Rotating(AtomicUsize);
+
+#[async_trait::async_trait]
+impl TokenProvider for Rotating {
+    async fn token(&self) -> Result<String, A2AError> {
+        Ok(format!("token-{}", self.0.fetch_add(1, Ordering::SeqCst)))
+    }
+}
+
+#[tokio::test]
+async fn token_providers_are_asked_on_every_request() {
+    let (directory, calls) = common::directory_and_peer().await;
+    let agent = A2AAgent::new("client", "Client", vec![]).with_token_provider(Arc::new(Rotating(AtomicUsize::new(0))));
+    agent.send_task("calc", "add", json!({}), &directory).await.unwrap();
+    agent.send_task("calc", "add", json!({}), &directory).await.unwrap();
+
+    let tokens: Vec<_> = calls.lock().unwrap().iter().map(|(_, headers, _)| headers["authorization"].clone()).collect();
+    assert_eq!(tokens, ["Bearer token-0", "Bearer token-1", "Bearer token-2", "Bearer token-3"]);
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct AddResult {
+    result: i64,
+}
+
+/// A calculator whose `add` answers with an [`AddResult`].
+fn calculator() -> A2AServer {
+    let mut server = A2AServer::new("calc", "Calculator", vec!["add".to_string()], 0);
+    server.handle_task(|_ctx, input| json!({"result": input["a"].as_i64().unwrap() + input["b"].as_i64().unwrap()}));
+    server
+}
+
+#[tokio::test]
+async fn outputs_deserialize_into_the_callers_type() {
+    let directory = common::listed(&calculator(), "calc").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let sum: AddResult = client
+        .send_task_typed("calc", "add", json!({"a": 10, "b": 20}), &directory)
+        .await
+        .unwrap();
+    assert_eq!(sum, AddResult { result: 30 });
+
+    let mismatch = client
+        .send_task_typed::<Vec<String>>("calc", "add", json!({"a": 1, "b": 2}), &directory)
+        .await;
+    assert!(matches!(mismatch, Err(A2AError::Serde(_))), "{:?}", mismatch);
+}
+
+#[derive(serde::Serialize)]
+struct AddRequest {
+    a: i64,
+    b: i64,
+}
+
+#[tokio::test]
+async fn inputs_serialize_from_the_callers_type() {
+    let directory = common::listed(&calculator(), "calc").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let result = client
+        .send_task_with("calc", "add", &AddRequest { a: 2, b: 3 }, &directory)
+        .await
+        .unwrap();
+    assert_eq!(result.output, Some(json!({"result": 5})));
+
+    let keyed_by_pairs = std::collections::HashMap::from([((1, 2), 3)]);
+    let refused = client.send_task_with("calc", "add", &keyed_by_pairs, &directory).await;
+    assert!(matches!(refused, Err(A2AError::Serde(_))), "{:?}", refused);
+}
+
+#[tokio::test]
+async fn each_target_is_throttled_past_its_burst() {
+    let (directory, _calls) = common::directory_and_peer().await;
+    let client = A2AAgent::new("client", "Client", vec![]).with_rate_limit(RateLimit::new(1.0, 2).fail_fast());
+
+    for _ in 0..2 {
+        client.send_task("calc", "add", json!({}), &directory).await.unwrap();
+    }
+    match client.send_task("calc", "add", json!({}), &directory).await {
+        Err(A2AError::RateLimited { target, retry_after }) => {
+            assert_eq!(target, "calc");
+            assert!(retry_after.is_some_and(|wait| wait <= Duration::from_secs(1)));
+        }
+        other => panic!("expected RateLimited, got {:?}", other),
+    }
+    // Another target has a bucket of its own.
+    client.send_task("search", "find", json!({}), &directory).await.unwrap();
+}
+
+#[tokio::test]
+async fn throttled_sends_wait_for_a_token() {
+    let (directory, calls) = common::directory_and_peer().await;
+    let client = A2AAgent::new("client", "Client", vec![]).with_rate_limit(RateLimit::new(20.0, 1));
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        client.send_task("calc", "add", json!({}), &directory).await.unwrap();
+    }
+    // The first send takes the burst; the next two wait 50ms each.
+    assert!(started.elapsed() >= Duration::from_millis(90), "{:?}", started.elapsed());
+    let tasks = calls.lock().unwrap().iter().filter(|(path, _, _)| path == "/rpc").count();
+    assert_eq!(tasks, 3);
+}
+
+#[tokio::test]
+async fn actions_missing_from_the_capabilities_fail_before_sending() {
+    let (peer, calls) = common::directory_and_peer().await;
+    let directory = common::lookup(&format!("{}/rpc", peer), json!(["add"])).await;
+    let checking = A2AAgent::new("client", "Client", vec![]).with_action_check(true);
+
+    checking.send_task("calc", "add", json!({}), &directory).await.unwrap();
+    match checking.send_task("calc", "sub", json!({}), &directory).await {
+        Err(A2AError::UnsupportedAction { agent_id, action }) => {
+            assert_eq!((agent_id.as_str(), action.as_str()), ("calc", "sub"));
+        }
+        other => panic!("expected UnsupportedAction, got {:?}", other),
+    }
+    assert_eq!(calls.lock().unwrap().len(), 1);
+
+    // Without the check the peer decides.
+    let trusting = A2AAgent::new("client", "Client", vec![]);
+    trusting.send_task("calc", "sub", json!({}), &directory).await.unwrap();
+    assert_eq!(calls.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn default_and_per_call_headers_reach_the_directory_and_the_peer() {
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    let (directory, calls) = common::directory_and_peer().await;
+    let mut defaults = HeaderMap::new();
+    defaults.insert("x-tenant-id", HeaderValue::from_static("acme"));
+    defaults.insert("x-route", HeaderValue::from_static("default"));
+    let client = A2AAgent::new("client", "Client", vec![]).with_default_headers(defaults);
+
+    let mut per_call = HeaderMap::new();
+    per_call.insert("x-route", HeaderValue::from_static("canary"));
+    a2a::with_headers(per_call, client.send_task("calc", "add", json!({}), &directory))
+        .await
+        .unwrap();
+    client.send_task("calc", "add", json!({}), &directory).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    let paths: Vec<&str> = calls.iter().map(|(path, _, _)| path.as_str()).collect();
+    assert_eq!(paths, ["/a2a/agents/calc", "/rpc", "/a2a/agents/calc", "/rpc"]);
+    for (index, (_, headers, _)) in calls.iter().enumerate() {
+        assert_eq!(headers["x-tenant-id"], "acme");
+        assert_eq!(headers["x-route"], if index < 2 { "canary" } else { "default" });
+    }
+}
+
+#[tokio::test]
+async fn requests_go_through_the_configured_proxy() {
+    // Plain HTTP through a proxy is sent to the proxy in absolute form, so
+    // a recording server stands in for it.
+    let (proxy, calls) = common::recording(json!({"agents": []})).await;
+    let client = A2AAgent::builder("client", "Client")
+        .proxy(&proxy, Some(("user", "secret")))
+        .unwrap()
+        .max_retries(0)
+        .build().unwrap();
+
+    client.discover(vec!["add".to_string()], "http://directory.invalid").await.unwrap();
+    let calls = calls.lock().unwrap();
+    let (path, headers, _) = &calls[0];
+    assert_eq!(path, "/a2a/discover");
+    assert_eq!(headers["host"], "directory.invalid");
+    // "user:secret" in Base64.
+    assert_eq!(headers["proxy-authorization"], "Basic dXNlcjpzZWNyZXQ=");
+
+    assert!(A2AAgent::builder("client", "Client").proxy("not a url", None).is_err());
+}
+
+#[tokio::test]
+async fn answers_with_another_id_are_refused() {
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(|| async { Json(json!({"jsonrpc": "2.0", "id": "spoofed", "result": {"agents": []}})) }),
+    );
+    let directory = common::mock(router).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    match client.discover(vec![], &directory).await {
+        Err(A2AError::IdMismatch { expected, got }) => {
+            assert!(expected.is_string());
+            assert_ne!(expected, got);
+            assert_eq!(got, "spoofed");
+        }
+        other => panic!("expected IdMismatch, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn batch_answers_are_matched_by_id() {
+    // Answers in reverse order, with the id of the `"spoof"` task replaced.
+    let router = Router::new().route(
+        "/rpc",
+        post(|Json(calls): Json<Vec<Value>>| async move {
+            let answers: Vec<Value> = calls
+                .iter()
+                .rev()
+                .map(|call| {
+                    let params = &call["params"];
+                    let id = if params["input"] == "spoof" { json!("spoofed") } else { call["id"].clone() };
+                    let result = json!({"taskId": params["taskId"], "status": "completed", "output": params["input"]});
+                    json!({"jsonrpc": "2.0", "id": id, "result": result})
+                })
+                .collect();
+            Json(answers)
+        }),
+    );
+    let peer = common::mock(router).await;
+    let directory = common::lookup(&format!("{}/rpc", peer), json!([])).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let tasks = ["one", "spoof", "three"]
+        .into_iter()
+        .map(|input| ("echo".to_string(), "echo".to_string(), json!(input)))
+        .collect();
+    let results = client.send_tasks_batch(tasks, &directory).await.unwrap();
+    assert_eq!(results[0].as_ref().unwrap().output, Some(json!("one")));
+    assert!(matches!(results[1], Err(A2AError::Protocol(_))), "{:?}", results[1]);
+    assert_eq!(results[2].as_ref().unwrap().output, Some(json!("three")));
+}
+
+/// A directory answering discoveries with `jsonrpc` set to `version`, or
+/// without it for `None`.
+async fn versioned_directory(version: Option<&'static str>) -> String {
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(move |Json(call): Json<Value>| async move {
+            let mut answer = json!({"id": call["id"], "result": {"agents": []}});
+            if let Some(version) = version {
+                answer["jsonrpc"] = json!(version);
+            }
+            Json(answer)
+        }),
+    );
+    common::mock(router).await
+}
+
+#[tokio::test]
+async fn answers_must_declare_json_rpc_2() {
+    let client = A2AAgent::new("client", "Client", vec![]);
+    client.discover(vec![], &versioned_directory(Some("2.0")).await).await.unwrap();
+
+    for version in [Some("1.0"), None] {
+        let refused = client.discover(vec![], &versioned_directory(version).await).await;
+        assert!(matches!(refused, Err(A2AError::Protocol(_))), "{:?}: {:?}", version, refused);
+    }
+}
+
+/// A peer, which also answers discoveries, replying to every call with
+/// `envelope` for the call's `id`; returns a directory listing it, and
+/// the peer's own URL.
+async fn answering(envelope: fn(Value) -> Value) -> (String, String) {
+    let answer = move |Json(call): Json<Value>| async move { Json(envelope(call["id"].clone())) };
+    let router = Router::new().route("/a2a/discover", post(answer)).route("/", post(answer));
+    let peer = common::mock(router).await;
+    (common::lookup(&peer, json!([])).await, peer)
+}
+
+#[tokio::test]
+async fn answers_need_exactly_one_of_result_and_error() {
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let (directory, peer) = answering(|id| json!({"jsonrpc": "2.0", "id": id})).await;
+    let empty = client.discover(vec![], &peer).await;
+    assert!(matches!(empty, Err(A2AError::EmptyResponse)), "{:?}", empty);
+    let empty = client.send_task("calc", "add", json!({}), &directory).await;
+    assert!(matches!(empty, Err(A2AError::EmptyResponse)), "{:?}", empty);
+
+    let both = |id| {
+        let error = json!({"code": -32000, "message": "both"});
+        json!({"jsonrpc": "2.0", "id": id, "result": {"agents": []}, "error": error})
+    };
+    let (directory, peer) = answering(both).await;
+    let refused = client.discover(vec![], &peer).await;
+    assert!(matches!(refused, Err(A2AError::Protocol(_))), "{:?}", refused);
+    let refused = client.send_task("calc", "add", json!({}), &directory).await;
+    assert!(matches!(refused, Err(A2AError::Protocol(_))), "{:?}", refused);
+}
+
+#[tokio::test]
+async fn oversized_answers_are_cut_off() {
+    let padding = "x".repeat(64 * 1024);
+    let router = Router::new()
+        .route(
+            "/a2a/discover",
+            post({
+                let padding = padding.clone();
+                move |Json(call): Json<Value>| async move {
+                    Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"agents": [], "padding": padding}}))
+                }
+            }),
+        )
+        .route(
+            "/a2a/agents/{id}",
+            get(move || async move {
+                Json(json!({"agent_id": "calc", "name": padding, "capabilities": [], "endpoint": "http://calc.invalid"}))
+            }),
+        );
+    let directory = common::mock(router).await;
+    let client = A2AAgent::new("client", "Client", vec![]).with_max_response_bytes(1024);
+
+    let refused = client.discover(vec![], &directory).await;
+    assert!(matches!(refused, Err(A2AError::ResponseTooLarge { limit: 1024 })), "{:?}", refused);
+    let refused = client.send_task("calc", "add", json!({}), &directory).await;
+    assert!(matches!(refused, Err(A2AError::ResponseTooLarge { limit: 1024 })), "{:?}", refused);
+
+    let roomy = A2AAgent::new("client", "Client", vec![]).with_max_response_bytes(1024 * 1024);
+    roomy.discover(vec![], &directory).await.unwrap();
+}
+
+#[tokio::test]
+async fn gateway_error_pages_surface_their_status_and_body() {
+    let page = format!("<html><body><h1>502 Bad Gateway</h1>{}</body></html>", "<p>nginx</p>".repeat(200));
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(move || async move { (StatusCode::BAD_GATEWAY, [("content-type", "text/html")], page) }),
+    );
+    let client = A2AAgent::new("client", "Client", vec![]).with_retry_policy(RetryPolicy::none());
+
+    match client.discover(vec![], &common::mock(router).await).await {
+        Err(A2AError::HttpStatus { status, body }) => {
+            assert_eq!(status, StatusCode::BAD_GATEWAY);
+            assert!(body.starts_with("<html><body><h1>502 Bad Gateway</h1>"), "{}", body);
+            assert!(body.len() < 1024 && body.ends_with("..."), "{}", body);
+        }
+        other => panic!("expected HttpStatus, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn successful_non_json_answers_name_their_content_type() {
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(|| async { ([("content-type", "text/html")], "<html>maintenance</html>") }),
+    );
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    match client.discover(vec![], &common::mock(router).await).await {
+        Err(A2AError::UnexpectedContentType { content_type, body }) => {
+            assert_eq!(content_type, "text/html");
+            assert_eq!(body, "<html>maintenance</html>");
+        }
+        other => panic!("expected UnexpectedContentType, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn the_breaker_opens_half_opens_and_closes() {
+    let (healthy, hits) = (Arc::new(AtomicBool::new(false)), Arc::new(AtomicUsize::new(0)));
+    let router = Router::new().route(
+        "/rpc",
+        post({
+            let (healthy, hits) = (healthy.clone(), hits.clone());
+            move |Json(call): Json<Value>| async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                if !healthy.load(Ordering::SeqCst) {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                let result = json!({"taskId": call["params"]["taskId"], "status": "completed", "output": null});
+                Ok(Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": result})))
+            }
+        }),
+    );
+    let peer = common::mock(router).await;
+    let directory = common::lookup(&format!("{}/rpc", peer), json!([])).await;
+    let client = A2AAgent::new("client", "Client", vec![])
+        .with_retry_policy(RetryPolicy::none())
+        .with_circuit_breaker(CircuitBreaker::new(2, Duration::from_millis(200)));
+    let send = |agent_id: &'static str| client.send_task(agent_id, "add", json!({}), &directory);
+    let is_open = |result: Result<_, A2AError>| matches!(result, Err(A2AError::CircuitOpen(agent_id)) if agent_id == "calc");
+
+    // Closed: failures reach the peer until the threshold.
+    for _ in 0..2 {
+        assert!(matches!(send("calc").await, Err(A2AError::HttpStatus { .. })));
+    }
+    // Open: calls fail without reaching the peer, for this target only.
+    assert!(is_open(send("calc").await));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert!(matches!(send("search").await, Err(A2AError::HttpStatus { .. })));
+
+    // Half-open: one probe goes through; its failure opens the circuit again.
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(matches!(send("calc").await, Err(A2AError::HttpStatus { .. })));
+    assert!(is_open(send("calc").await));
+
+    // A successful probe closes it.
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    send("calc").await.unwrap();
+    send("calc").await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+async fn dry_runs_never_reach_the_peer() {
+    let (directory, calls) = common::directory_and_peer().await;
+    let client = A2AAgent::new("client", "Client", vec![]).with_dry_run(true);
+    let peer_calls = || calls.lock().unwrap().iter().filter(|(path, _, _)| path == "/rpc").count();
+
+    let result = client.send_task("calc", "add", json!({"a": 1}), &directory).await.unwrap();
+    assert_eq!(result.status, TaskStatus::DryRun);
+    assert_eq!(result.output, Some(json!({"a": 1})));
+
+    let built = client.build_task_request("calc", "add", json!({"a": 2}), &directory).await.unwrap();
+    assert!(built.endpoint.ends_with("/rpc"), "{}", built.endpoint);
+    assert_eq!(built.request.method, "a2a/task");
+    let params = built.request.params.unwrap();
+    assert_eq!(params["action"], "add");
+    assert_eq!(params["input"], json!({"a": 2}));
+    assert_eq!(params["sender"], "client");
+
+    // The directory was asked, the peer never was.
+    assert_eq!(calls.lock().unwrap().len(), 2);
+    assert_eq!(peer_calls(), 0);
+}
+
+#[tokio::test]
+async fn send_task_any_moves_on_to_the_next_candidate() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    for (agent_id, works) in [("calc-1", false), ("calc-2", true), ("calc-3", false)] {
+        let mut server = A2AServer::new(agent_id, agent_id, vec![], 0);
+        server.handle_task(move |_ctx, _input| match works {
+            true => Ok(json!(agent_id)),
+            false => Err(TaskError::new(-32050, "out of order")),
+        });
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec!["add".to_string()]);
+        agent.register(&common::serve(&server).await, &directory).await.unwrap();
+    }
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let add = || CapabilityQuery::All(vec!["add".to_string()]);
+
+    let result = client.send_task_any(add(), SelectionStrategy::First, "add", json!({}), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("calc-2")));
+
+    let nobody = CapabilityQuery::All(vec!["mul".to_string()]);
+    match client.send_task_any(nobody, SelectionStrategy::First, "mul", json!({}), &directory).await {
+        Err(A2AError::AllCandidatesFailed(failures)) => assert!(failures.is_empty()),
+        other => panic!("expected AllCandidatesFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn send_task_any_reports_every_failure_in_order() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    for agent_id in ["calc-1", "calc-2"] {
+        let mut server = A2AServer::new(agent_id, agent_id, vec![], 0);
+        server.handle_task(|_ctx, _input| Err::<Value, _>(TaskError::new(-32050, "out of order")));
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec!["add".to_string()]);
+        agent.register(&common::serve(&server).await, &directory).await.unwrap();
+    }
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let add = CapabilityQuery::All(vec!["add".to_string()]);
+    match client.send_task_any(add, SelectionStrategy::First, "add", json!({}), &directory).await {
+        Err(A2AError::AllCandidatesFailed(failures)) => {
+            let tried: Vec<&str> = failures.iter().map(|(agent_id, _)| agent_id.as_str()).collect();
+            assert_eq!(tried, ["calc-1", "calc-2"]);
+            assert!(failures.iter().all(|(_, e)| matches!(e, A2AError::Rpc { code: -32050, .. })));
+        }
+        other => panic!("expected AllCandidatesFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn clones_send_tasks_and_share_the_round_robin_turn() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    for agent_id in ["calc-1", "calc-2"] {
+        let mut server = A2AServer::new(agent_id, agent_id, vec![], 0);
+        server.handle_task(move |_ctx, _input| Ok::<_, TaskError>(json!(agent_id)));
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec!["add".to_string()]);
+        agent.register(&common::serve(&server).await, &directory).await.unwrap();
+    }
+    let client = A2AAgent::default();
+    assert!(!client.agent_id.is_empty());
+    assert_eq!(client.name, client.agent_id);
+    assert!(client.capabilities.is_empty());
+    let mut clone = client.clone();
+    clone.capabilities.push("mul".into());
+
+    let mut answers = Vec::new();
+    for agent in [&client, &clone, &client, &clone] {
+        let add = CapabilityQuery::All(vec!["add".to_string()]);
+        let result = agent.send_task_any(add, SelectionStrategy::RoundRobin, "add", json!({}), &directory).await;
+        answers.push(result.unwrap().output.unwrap());
+    }
+    assert_eq!(answers, [json!("calc-1"), json!("calc-2"), json!("calc-1"), json!("calc-2")]);
+
+    // Clones also work from separate tasks; their own settings stay apart.
+    let sent = tokio::spawn(async move { clone.send_task("calc-1", "add", json!({}), &directory).await });
+    assert_eq!(sent.await.unwrap().unwrap().output, Some(json!("calc-1")));
+    assert!(client.capabilities.is_empty());
+}
+
+#[tokio::test]
+async fn version_constraints_pick_a_declared_version_or_fail() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let capabilities = || {
+        ["1.4.0", "2.1.0"].map(|version| Capability::new("add").with_version(version.parse().unwrap())).to_vec()
+    };
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let mut server = A2AServer::new("calc", "Calc", vec![], 0).with_capabilities(capabilities());
+    server.handle_task(move |ctx, _input| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        json!(ctx.version)
+    });
+    let mut calc = A2AAgent::new("calc", "Calc", vec![]).with_capabilities(capabilities());
+    calc.register(&common::serve(&server).await, &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    for (requirement, chosen) in [("^2.0", "2.1.0"), (">=1.0, <2", "1.4.0"), ("*", "2.1.0")] {
+        let requirement = requirement.parse().unwrap();
+        let result = client.send_task_versioned("calc", "add", &requirement, json!({}), &directory).await.unwrap();
+        assert_eq!(result.output, Some(json!(chosen)));
+    }
+
+    match client.send_task_versioned("calc", "add", &"^3.0".parse().unwrap(), json!({}), &directory).await {
+        Err(A2AError::VersionMismatch { agent_id, action, required, available }) => {
+            assert_eq!((agent_id.as_str(), action.as_str(), required.as_str()), ("calc", "add", "^3.0"));
+            assert_eq!(available, [Version::new(1, 4, 0), Version::new(2, 1, 0)]);
+        }
+        other => panic!("expected VersionMismatch, got {:?}", other),
+    }
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    // The directory matches `name@requirement` against declared versions.
+    let found = client.discover_all(vec!["add@^2.0".to_string()], &directory).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(client.discover_all(vec!["add@^3.0".to_string()], &directory).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn broadcasts_collect_every_agents_outcome() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let (in_flight, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    for agent_id in ["cache-1", "cache-2", "cache-3"] {
+        let mut server = A2AServer::new(agent_id, agent_id, vec![], 0);
+        let (in_flight, most) = (in_flight.clone(), most.clone());
+        server.handle_task_async(move |_ctx, _input| {
+            let (in_flight, most) = (in_flight.clone(), most.clone());
+            async move {
+                most.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                match agent_id {
+                    "cache-2" => Err(TaskError::new(-32050, "cold storage")),
+                    _ => Ok(json!(agent_id)),
+                }
+            }
+        });
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec!["cache".to_string()]);
+        agent.register(&common::serve(&server).await, &directory).await.unwrap();
+    }
+    let client = A2AAgent::new("client", "Client", vec![]).with_broadcast_concurrency(2);
+
+    let query = CapabilityQuery::All(vec!["cache".to_string()]);
+    let outcomes = client.broadcast_task(query, "warm", json!({}), &directory).await.unwrap();
+    let outcomes: Vec<(&str, Result<Option<Value>, i32>)> = outcomes
+        .iter()
+        .map(|(peer, outcome)| {
+            let outcome = match outcome {
+                Ok(result) => Ok(result.output.clone()),
+                Err(A2AError::Rpc { code, .. }) => Err(*code),
+                Err(e) => panic!("unexpected failure: {:?}", e),
+            };
+            (peer.agent_id.as_str(), outcome)
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        [("cache-1", Ok(Some(json!("cache-1")))), ("cache-2", Err(-32050)), ("cache-3", Ok(Some(json!("cache-3"))))]
+    );
+    assert_eq!(most.load(Ordering::SeqCst), 2);
+
+    let nobody = CapabilityQuery::All(vec!["mul".to_string()]);
+    assert!(client.broadcast_task(nobody, "warm", json!({}), &directory).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn raw_sends_return_the_envelope_as_answered() {
+    let (peer, calls) = common::recording(json!({"sum": 3, "note": "not a TaskResult"})).await;
+    let directory = common::lookup(&peer, json!(["add"])).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let response = client.send_task_raw("calc", "add", json!({"a": 1, "b": 2}), &directory).await.unwrap();
+    let sent_id = calls.lock().unwrap()[0].2["id"].clone();
+    assert!(!sent_id.is_null());
+    assert_eq!(response["id"], sent_id);
+    assert_eq!(response["jsonrpc"], "2.0");
+    assert_eq!(response["result"], json!({"sum": 3, "note": "not a TaskResult"}));
+
+    // An edited request keeps its own id, and errors come back as answered.
+    let mut task = client.build_task_request("calc", "add", json!({}), &directory).await.unwrap();
+    task.request.id = json!(42);
+    assert_eq!(client.send_request_raw(&task).await.unwrap()["id"], 42);
+
+    let router = Router::new().route(
+        "/",
+        post(|| async { Json(json!({"jsonrpc": "2.0", "id": "other", "error": {"code": -32050, "message": "no"}})) }),
+    );
+    let directory = common::lookup(&common::mock(router).await, json!(["add"])).await;
+    let response = client.send_task_raw("calc", "add", json!({}), &directory).await.unwrap();
+    assert_eq!(response["id"], "other");
+    assert_eq!(response["error"]["code"], -32050);
+}
+
+/// A peer at `/rpc` that echoes each batch, recording every batch's size.
+async fn batch_echo() -> (String, Arc<std::sync::Mutex<Vec<usize>>>) {
+    let sizes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = sizes.clone();
+    let router = Router::new().route(
+        "/rpc",
+        post(move |Json(calls): Json<Vec<Value>>| {
+            seen.lock().unwrap().push(calls.len());
+            let answers: Vec<Value> = calls
+                .iter()
+                .map(|call| {
+                    let params = &call["params"];
+                    let result = json!({"taskId": params["taskId"], "status": "completed", "output": params["input"]});
+                    json!({"jsonrpc": "2.0", "id": call["id"], "result": result})
+                })
+                .collect();
+            async move { Json(answers) }
+        }),
+    );
+    let peer = common::mock(router).await;
+    (common::lookup(&format!("{}/rpc", peer), json!([])).await, sizes)
+}
+
+#[tokio::test]
+async fn batchers_send_enqueued_tasks_together() {
+    let (directory, sizes) = batch_echo().await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let batcher = TaskBatcher::new(client.clone(), &directory, 10, Duration::from_millis(50));
+    let pending: Vec<_> = (0..5).map(|n| batcher.enqueue("echo", "echo", json!(n))).collect();
+    for (n, result) in futures::future::join_all(pending).await.into_iter().enumerate() {
+        assert_eq!(result.unwrap().output, Some(json!(n)));
+    }
+    assert_eq!(*sizes.lock().unwrap(), [5]);
+    batcher.close().await;
+
+    // Full batches go at once; the rest goes on close, or on drop.
+    let (directory, sizes) = batch_echo().await;
+    let batcher = TaskBatcher::new(client.clone(), &directory, 2, Duration::from_secs(60));
+    let pending: Vec<_> = (0..5).map(|n| batcher.enqueue("echo", "echo", json!(n))).collect();
+    batcher.close().await;
+    assert!(futures::future::join_all(pending).await.iter().all(Result::is_ok));
+    assert_eq!(*sizes.lock().unwrap(), [2, 2, 1]);
+
+    let (directory, sizes) = batch_echo().await;
+    let batcher = TaskBatcher::new(client, &directory, 10, Duration::from_secs(60));
+    let last = batcher.enqueue("echo", "echo", json!("last"));
+    drop(batcher);
+    assert_eq!(last.await.unwrap().output, Some(json!("last")));
+    assert_eq!(*sizes.lock().unwrap(), [1]);
+}
+
+#[tokio::test]
+async fn submits_resolve_on_the_ack_before_the_task_runs() {
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+    let released = Arc::new(tokio::sync::Mutex::new(Some(released)));
+    let mut server = A2AServer::new("slow", "Slow", vec![], 0);
+    server.handle_task_async(move |_ctx, input| {
+        let released = released.clone();
+        async move {
+            if let Some(released) = released.lock().await.take() {
+                let _ = released.await;
+            }
+            input
+        }
+    });
+    let directory = common::listed(&server, "slow").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let acked = client.submit_task("slow", "work", json!("done"), &directory).await.unwrap();
+    assert_eq!(acked.status, TaskStatus::Accepted);
+    assert!(!acked.task_id.is_empty());
+    let status = client.get_task_status("slow", &acked.task_id, &directory).await.unwrap();
+    assert!(!status.status.is_terminal(), "{:?}", status.status);
+
+    release.send(()).unwrap();
+    let finished = client
+        .wait_for_task("slow", &acked.task_id, &directory, Duration::from_millis(20), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!((finished.status, finished.output), (TaskStatus::Completed, Some(json!("done"))));
+}
+
+/// Keeps pending tasks in memory, as if on disk across a restart.
+#[derive(Default)]
+struct MemoryOutbox(std::sync::Mutex<Vec<PendingTask>>);
+
+#[async_trait::async_trait]
+impl Outbox for MemoryOutbox {
+    async fn save(&self, task: &PendingTask) -> Result<(), A2AError> {
+        let mut tasks = self.0.lock().unwrap();
+        tasks.retain(|saved| saved.task_id != task.task_id);
+        tasks.push(task.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, task_id: &str) -> Result<(), A2AError> {
+        self.0.lock().unwrap().retain(|saved| saved.task_id != task_id);
+        Ok(())
+    }
+
+    async fn pending(&self) -> Result<Vec<PendingTask>, A2AError> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+#[tokio::test]
+async fn unacknowledged_submits_stay_in_the_outbox_until_resent() {
+    // The first submit is lost to a gateway error, the second acknowledged.
+    let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = sent.clone();
+    let router = Router::new().route(
+        "/",
+        post(move |Json(call): Json<Value>| {
+            let task_id = call["params"]["taskId"].clone();
+            let first = {
+                let mut seen = seen.lock().unwrap();
+                seen.push(task_id.clone());
+                seen.len() == 1
+            };
+            async move {
+                if first {
+                    return Err(StatusCode::BAD_GATEWAY);
+                }
+                let ack = json!({"taskId": task_id, "status": "accepted"});
+                Ok(Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": ack})))
+            }
+        }),
+    );
+    let directory = common::lookup(&common::mock(router).await, json!([])).await;
+    let outbox = Arc::new(MemoryOutbox::default());
+    let client = A2AAgent::new("client", "Client", vec![])
+        .with_retry_policy(RetryPolicy::none())
+        .with_outbox(outbox.clone());
+
+    assert!(client.submit_task("billing", "charge", json!({"cents": 100}), &directory).await.is_err());
+    let pending = outbox.pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!((pending[0].target_agent_id.as_str(), pending[0].action.as_str()), ("billing", "charge"));
+
+    let resent = client.resubmit_pending().await.unwrap();
+    assert_eq!(resent.len(), 1);
+    let (task_id, outcome) = &resent[0];
+    assert_eq!(*task_id, pending[0].task_id);
+    assert_eq!(outcome.as_ref().unwrap().status, TaskStatus::Accepted);
+    assert!(outbox.pending().await.unwrap().is_empty());
+    // Both attempts carried the same task ID, so the target can drop copies.
+    assert_eq!(*sent.lock().unwrap(), [json!(task_id), json!(task_id)]);
+}
+
+/// Sets its flag when dropped, i.e. when the handler holding it stops.
+struct Stopped(Arc<AtomicBool>);
+
+impl Drop for Stopped {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn dropped_sends_stop_the_task_on_the_peer() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let (stopped, finished) = (Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)));
+    let mut server = A2AServer::new("slow", "Slow", vec![], 0);
+    let flags = (started.clone(), stopped.clone(), finished.clone());
+    server.handle_task_async(move |ctx, _input| {
+        let (started, stopped, finished) = flags.clone();
+        async move {
+            let _stopped = Stopped(stopped);
+            started.fetch_add(1, Ordering::SeqCst);
+            tokio::select! {
+                _ = ctx.cancellation.cancelled() => {}
+                _ = tokio::time::sleep(Duration::from_secs(30)) => finished.store(true, Ordering::SeqCst),
+            }
+            json!("ran")
+        }
+    });
+    let directory = common::listed(&server, "slow").await;
+    let client = A2AAgent::new("client", "Client", vec![]).with_cancel_on_drop(true);
+
+    // The select! losing the race drops the send mid-flight.
+    let started_at = Instant::now();
+    tokio::select! {
+        _ = client.send_task("slow", "work", json!({}), &directory) => panic!("the task should still be running"),
+        _ = tokio::time::sleep(Duration::from_millis(200)) => {}
+    }
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+    assert_eq!(started.load(Ordering::SeqCst), 1);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !stopped.load(Ordering::SeqCst) {
+        assert!(Instant::now() < deadline, "the handler kept running");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(!finished.load(Ordering::SeqCst));
+
+    // Peers that keep going are also asked to cancel the task.
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = calls.clone();
+    let router = Router::new().route(
+        "/",
+        post(move |Json(call): Json<Value>| {
+            seen.lock().unwrap().push((call["method"].clone(), call["params"]["taskId"].clone()));
+            async move {
+                if call["method"] == "a2a/task" {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }
+                Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"taskId": "t", "status": "cancelled"}}))
+            }
+        }),
+    );
+    let directory = common::lookup(&common::mock(router).await, json!([])).await;
+    let sent = client.send_task("slow", "work", json!({}), &directory);
+    assert!(tokio::time::timeout(Duration::from_millis(200), sent).await.is_err());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while calls.lock().unwrap().len() < 2 {
+        assert!(Instant::now() < deadline, "no cancel was sent");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let calls = calls.lock().unwrap();
+    assert_eq!((calls[0].0.as_str(), calls[1].0.as_str()), (Some("a2a/task"), Some("a2a/task/cancel")));
+    assert_eq!(calls[0].1, calls[1].1);
+}
+
+#[tokio::test]
+async fn every_request_names_the_client_in_its_user_agent() {
+    let (peer, calls) = common::recording(json!({"agents": []})).await;
+    let user_agents = |calls: &common::Calls| -> Vec<(String, String)> {
+        let calls = calls.lock().unwrap();
+        let user_agent = |headers: &axum::http::HeaderMap| headers["user-agent"].to_str().unwrap().to_string();
+        calls.iter().map(|(path, headers, _)| (path.clone(), user_agent(headers))).collect()
+    };
+
+    let client = A2AAgent::new("client", "Client", vec![]);
+    client.discover_all(vec![], &peer).await.unwrap();
+    // Agent lookups and cards are plain GETs, answered here with nonsense.
+    let _ = client.send_task("calc", "add", json!({}), &peer).await;
+    let _ = client.fetch_card(&peer).await;
+    let default = format!("a2a-rust/{}", env!("CARGO_PKG_VERSION"));
+    let sent = user_agents(&calls);
+    let paths: Vec<&str> = sent.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["/a2a/discover", "/a2a/agents/calc", "/a2a/card"]);
+    assert!(sent.iter().all(|(_, user_agent)| *user_agent == default), "{:?}", sent);
+
+    calls.lock().unwrap().clear();
+    let renamed = A2AAgent::new("client", "Client", vec![]).with_user_agent("billing-bot/2.1");
+    renamed.discover_all(vec![], &peer).await.unwrap();
+    let built = A2AAgent::builder("client", "Client").user_agent("billing-bot/3.0").build().unwrap();
+    let _ = built.fetch_card(&peer).await;
+    let sent: Vec<String> = user_agents(&calls).into_iter().map(|(_, user_agent)| user_agent).collect();
+    assert_eq!(sent, ["billing-bot/2.1", "billing-bot/3.0"]);
+}
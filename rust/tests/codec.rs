@@ -0,0 +1,49 @@
+#![cfg(all(feature = "msgpack", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, A2AServer, Codec, MsgPackCodec, RetryPolicy};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn msgpack_agents_reach_msgpack_servers() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    let endpoint = common::serve(&server).await;
+
+    let mut echo = A2AAgent::new("echo", "Echo", vec!["echo".to_string()]).with_codec(Arc::new(MsgPackCodec));
+    echo.register(&endpoint, &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![])
+        .with_codec(Arc::new(MsgPackCodec))
+        .with_retry_policy(RetryPolicy::none());
+
+    let input = json!({"n": -7, "x": 2.5, "items": ["a", null, true]});
+    let result = client.send_task("echo", "echo", input.clone(), &directory).await.unwrap();
+    assert_eq!(result.output, Some(input));
+}
+
+#[tokio::test]
+async fn servers_answer_in_the_codec_of_the_call() {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    let url = common::serve(&server).await;
+
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "a2a/task",
+        "params": {"taskId": "t1", "action": "echo", "sender": "client", "input": [1, 2]},
+    });
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("content-type", "application/msgpack")
+        .body(MsgPackCodec.encode(&call).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-type"], "application/msgpack");
+    let answer = MsgPackCodec.decode(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(answer["result"]["output"], json!([1, 2]));
+}
@@ -0,0 +1,107 @@
+//! Loopback servers for the integration tests, each on a port the OS
+//! picks so tests can run in parallel.
+
+#![allow(dead_code)]
+
+use a2a::{A2AAgent, A2ADirectory, A2AServer};
+use axum::body::Bytes;
+use axum::extract::Path;
+use axum::http::{HeaderMap, Uri};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// Serve `router` as a stand-in directory or peer, returning its base URL.
+pub async fn mock(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// Serve `server` with the handlers registered so far, returning its base
+/// URL. Create it on port `0`.
+pub async fn serve(server: &A2AServer) -> String {
+    let (addr, serving) = server.bind().await.unwrap();
+    tokio::spawn(serving);
+    format!("http://127.0.0.1:{}", addr.port())
+}
+
+/// Serve `directory`, returning its base URL. Create it on port `0`.
+pub async fn directory(directory: A2ADirectory) -> String {
+    let (addr, serving) = directory.bind().await.unwrap();
+    tokio::spawn(serving);
+    format!("http://127.0.0.1:{}", addr.port())
+}
+
+/// What a [`recording`] server was sent: each call's path, headers and
+/// JSON body, in arrival order.
+pub type Calls = Arc<Mutex<Vec<(String, HeaderMap, Value)>>>;
+
+/// A stand-in directory or peer that records every call and answers each
+/// with `result`, returning its base URL.
+pub async fn recording(result: Value) -> (String, Calls) {
+    let calls = Calls::default();
+    let seen = calls.clone();
+    let router = Router::new().fallback(move |uri: Uri, headers: HeaderMap, body: Bytes| {
+        let (seen, result) = (seen.clone(), result.clone());
+        async move {
+            let call: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+            let answer = json!({"jsonrpc": "2.0", "id": call["id"], "result": result});
+            seen.lock().unwrap().push((uri.path().to_string(), headers, call));
+            Json(answer)
+        }
+    });
+    (mock(router).await, calls)
+}
+
+/// Serve `server` and a fresh directory listing it as `agent_id`,
+/// returning the directory's base URL.
+pub async fn listed(server: &A2AServer, agent_id: &str) -> String {
+    let endpoint = serve(server).await;
+    let directory = directory(A2ADirectory::new(0)).await;
+    let mut agent = A2AAgent::new(agent_id, agent_id, vec![]);
+    agent.register(&endpoint, &directory).await.unwrap();
+    directory
+}
+
+/// A stand-in directory answering every agent lookup with an agent at
+/// `endpoint` offering `capabilities`, returning its base URL.
+pub async fn lookup(endpoint: &str, capabilities: Value) -> String {
+    let endpoint = endpoint.to_string();
+    let router = Router::new().route(
+        "/a2a/agents/{id}",
+        axum::routing::get(move |Path(agent_id): Path<String>| {
+            let agent = json!({"agent_id": agent_id, "name": agent_id, "capabilities": capabilities, "endpoint": endpoint});
+            async move { Json(agent) }
+        }),
+    );
+    mock(router).await
+}
+
+/// A stand-in directory that lists every agent at its own `/rpc`, where it
+/// completes every task with a `null` output. Records the calls of both
+/// kinds, and returns the base URL.
+pub async fn directory_and_peer() -> (String, Calls) {
+    let calls = Calls::default();
+    let (seen, seen_rpc) = (calls.clone(), calls.clone());
+    let router = Router::new()
+        .route(
+            "/a2a/agents/{id}",
+            axum::routing::get(move |Path(agent_id): Path<String>, uri: Uri, headers: HeaderMap| {
+                let endpoint = format!("http://{}/rpc", headers["host"].to_str().unwrap());
+                seen.lock().unwrap().push((uri.path().to_string(), headers, Value::Null));
+                async move { Json(json!({"agent_id": agent_id, "name": agent_id, "capabilities": [], "endpoint": endpoint})) }
+            }),
+        )
+        .route(
+            "/rpc",
+            axum::routing::post(move |headers: HeaderMap, Json(call): Json<Value>| {
+                let result = json!({"taskId": call["params"]["taskId"], "status": "completed", "output": null});
+                let answer = json!({"jsonrpc": "2.0", "id": call["id"], "result": result});
+                seen_rpc.lock().unwrap().push(("/rpc".to_string(), headers, call));
+                async move { Json(answer) }
+            }),
+        );
+    (mock(router).await, calls)
+}
@@ -0,0 +1,52 @@
+#![cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, A2AServer, Compression, Encoding, RetryPolicy};
+use serde_json::{json, Value};
+
+fn compression(encoding: Encoding) -> Compression {
+    Compression { encoding, min_bytes: 1024 }
+}
+
+#[tokio::test]
+async fn large_tasks_round_trip_compressed() {
+    for encoding in [Encoding::Gzip, Encoding::Deflate] {
+        let directory = common::directory(A2ADirectory::new(0)).await;
+        let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_compression(compression(encoding));
+        server.handle_task(|_ctx, input| input);
+        let endpoint = common::serve(&server).await;
+
+        A2AAgent::new("echo", "Echo", vec!["echo".to_string()]).register(&endpoint, &directory).await.unwrap();
+        let client = A2AAgent::new("client", "Client", vec![])
+            .with_compression(compression(encoding))
+            .with_retry_policy(RetryPolicy::none());
+
+        let input = json!({"text": "compress me ".repeat(50_000)});
+        let result = client.send_task("echo", "echo", input.clone(), &directory).await.unwrap();
+        assert_eq!(result.output, Some(input), "{:?}", encoding);
+    }
+}
+
+#[tokio::test]
+async fn the_server_compresses_only_what_the_caller_accepts() {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_compression(compression(Encoding::Gzip));
+    server.handle_task(|_ctx, input| input);
+    let url = common::serve(&server).await;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "a2a/task",
+        "params": {"taskId": "t1", "action": "echo", "sender": "client", "input": "x".repeat(10_000)},
+    });
+    let http = reqwest::Client::new();
+    let send = |accept: &'static str| http.post(&url).header("accept-encoding", accept).json(&request).send();
+
+    let response = send("deflate").await.unwrap();
+    assert_eq!(response.headers()["content-encoding"], "deflate");
+    let response = send("identity").await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["result"]["output"], json!("x".repeat(10_000)));
+}
@@ -0,0 +1,377 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{
+    A2AAgent, A2ADirectory, A2AError, A2AServer, AgentIdPolicy, AgentInfo, AuthConfig, CapabilityQuery, DirectoryEvent,
+    DirectoryEventKind, ManagedAgent, RegistrationPolicy, RejectionReason, RetryPolicy,
+};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use futures::StreamExt;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn deregister_names_the_agent() {
+    let (directory, calls) = common::recording(json!({"status": "deregistered"})).await;
+    let mut agent = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    agent.deregister(&directory).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    let (path, _, call) = &calls[0];
+    assert_eq!(path, "/a2a/deregister");
+    assert_eq!(call["method"], "a2a/deregister");
+    assert_eq!(call["params"], json!({"agentId": "calc"}));
+}
+
+#[tokio::test]
+async fn heartbeats_re_register_until_stopped() {
+    let (directory, calls) = common::recording(json!({"status": "registered"})).await;
+    let registrations = || calls.lock().unwrap().iter().filter(|(path, _, _)| path == "/a2a/register").count();
+
+    let mut agent = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    agent.register("http://calc.invalid/", &directory).await.unwrap();
+    let heartbeat = agent.start_heartbeat(&directory, Duration::from_millis(50));
+    tokio::time::sleep(Duration::from_millis(275)).await;
+    let beats = registrations() - 1;
+    assert!((3..=6).contains(&beats), "{} heartbeats", beats);
+
+    heartbeat.stop();
+    let stopped_at = registrations();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(registrations(), stopped_at);
+
+    let calls = calls.lock().unwrap();
+    let (_, _, last) = calls.last().unwrap();
+    assert_eq!(last["params"]["endpoint"], "http://calc.invalid/");
+}
+
+#[tokio::test]
+async fn the_directory_registers_discovers_and_looks_up_agents() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string(), "mul".to_string()]);
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    let mut search = A2AAgent::new("search", "Search", vec!["find".to_string()]);
+    search.register("http://localhost:9002", &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let found = client.discover_all(vec!["mul".to_string()], &directory).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].agent_id, "calc");
+    assert_eq!(found[0].endpoint, "http://localhost:9001");
+    assert_eq!(client.discover_all(vec![], &directory).await.unwrap().len(), 2);
+
+    let fetched: serde_json::Value = reqwest::get(format!("{}/a2a/agents/search", directory))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(fetched["endpoint"], "http://localhost:9002");
+    let missing = reqwest::get(format!("{}/a2a/agents/nobody", directory)).await.unwrap();
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn agents_expire_unless_they_register_again() {
+    let directory = common::directory(A2ADirectory::new(0).with_ttl(Duration::from_millis(200))).await;
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]);
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_none());
+    let expired = reqwest::get(format!("{}/a2a/agents/calc", directory)).await.unwrap();
+    assert_eq!(expired.status(), reqwest::StatusCode::NOT_FOUND);
+
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_some());
+}
+
+/// Serve a fresh, empty directory on `port`, returning its base URL and a
+/// sender that shuts it down.
+async fn directory_on(port: u16) -> (String, tokio::sync::oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let (addr, serving) = A2ADirectory::new(port)
+        .bind_with_shutdown(async {
+            stopped.await.ok();
+        })
+        .await
+        .unwrap();
+    let serving = tokio::spawn(async move { serving.await.unwrap() });
+    (format!("http://127.0.0.1:{}", addr.port()), stop, serving)
+}
+
+#[tokio::test]
+async fn managed_agents_re_register_after_a_directory_restart() {
+    let (directory, stop, serving) = directory_on(0).await;
+    let mut server = A2AServer::new("calc", "Calc", vec!["add".to_string()], 0);
+    server.handle_task(|_ctx, _input| json!("done"));
+    let endpoint = common::serve(&server).await;
+
+    let agent = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    let mut managed = ManagedAgent::new(agent, &endpoint, &directory)
+        .with_recovery_policy(RetryPolicy::new(5, Duration::from_millis(20)));
+    managed.register().await.unwrap();
+    assert!(!managed.ensure_registered().await.unwrap());
+
+    // The directory comes back on the same port, having forgotten everyone.
+    stop.send(()).unwrap();
+    serving.await.unwrap();
+    let port = directory.rsplit(':').next().unwrap().parse().unwrap();
+    let (_directory, _stop, _serving) = directory_on(port).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_none());
+
+    // Its own task names it, so the lookup fails, it re-registers, and the
+    // retried call goes through.
+    let result = managed.send_task("calc", "add", json!({})).await.unwrap();
+    assert_eq!(result.output, Some(json!("done")));
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_some());
+    assert!(!managed.ensure_registered().await.unwrap());
+}
+
+#[tokio::test]
+async fn capabilities_are_patched_without_re_registering() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()]).with_tag("region", "eu");
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    calc.add_capability("mul", &directory).await.unwrap();
+    let names: Vec<&str> = calc.capabilities.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["add", "mul"]);
+    let found = client.discover(vec!["mul".to_string()], &directory).await.unwrap().unwrap();
+    assert_eq!(found.agent_id, "calc");
+    // Fields the update does not name are kept.
+    assert_eq!(found.tags["region"], "eu");
+    assert_eq!(found.endpoint, "http://localhost:9001");
+
+    calc.remove_capability("add", &directory).await.unwrap();
+    assert_eq!(calc.capabilities.len(), 1);
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_none());
+    assert!(client.discover(vec!["mul".to_string()], &directory).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn malformed_agent_ids_never_reach_the_directory() {
+    let (recorder, calls) = common::recording(json!({"status": "registered"})).await;
+    let malformed = [
+        ("", "Calc"),
+        ("   ", "Calc"),
+        (" calc", "Calc"),
+        ("calc", ""),
+        ("calc.eu", "Calc"),
+        ("ca/lc", "Calc"),
+    ];
+    for (agent_id, name) in malformed {
+        let mut agent = A2AAgent::new(agent_id, name, vec![]);
+        match agent.register("http://calc.invalid", &recorder).await {
+            Err(A2AError::InvalidAgentId(_)) => {}
+            other => panic!("expected InvalidAgentId for {:?}/{:?}, got {:?}", agent_id, name, other),
+        }
+    }
+    assert!(calls.lock().unwrap().is_empty());
+
+    // A client with a looser policy is still refused by the directory.
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut dotted = A2AAgent::new("calc.eu", "Calc", vec![]).with_agent_id_policy(AgentIdPolicy::permissive());
+    match dotted.register("http://calc.invalid", &directory).await {
+        Err(A2AError::RegistrationRejected { reason: RejectionReason::InvalidAgentId, .. }) => {}
+        other => panic!("expected an InvalidAgentId rejection, got {:?}", other),
+    }
+
+    // Unless the directory was configured with the same charset.
+    let policy = || AgentIdPolicy::new().allowed_chars(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    let directory = common::directory(A2ADirectory::new(0).with_agent_id_policy(policy())).await;
+    let mut dotted = A2AAgent::new("calc.eu", "Calc", vec![]).with_agent_id_policy(policy());
+    dotted.register("http://calc.invalid", &directory).await.unwrap();
+    let mut long = A2AAgent::new(&"c".repeat(129), "Calc", vec![]);
+    assert!(matches!(long.register("http://calc.invalid", &directory).await, Err(A2AError::InvalidAgentId(_))));
+}
+
+/// Lets only callers holding the operator token advertise `admin`.
+struct AdminNeedsToken;
+
+impl RegistrationPolicy for AdminNeedsToken {
+    fn allow(&self, info: &AgentInfo, headers: &HeaderMap) -> bool {
+        let operator = headers.get("authorization").is_some_and(|value| value == "Bearer operator");
+        operator || !info.capabilities.iter().any(|capability| capability.name == "admin")
+    }
+}
+
+#[tokio::test]
+async fn forbidden_capability_claims_are_refused() {
+    let directory = common::directory(A2ADirectory::new(0).with_registration_policy(AdminNeedsToken)).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let forbidden = |result: Result<_, A2AError>| {
+        matches!(result, Err(A2AError::RegistrationRejected { reason: RejectionReason::ForbiddenCapability, .. }))
+    };
+
+    let mut rogue = A2AAgent::new("rogue", "Rogue", vec!["admin".to_string(), "add".to_string()]);
+    assert!(forbidden(rogue.register("http://localhost:9001", &directory).await));
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_none());
+
+    // Allowed registrations pass, but cannot be patched into a forbidden one.
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    calc.register("http://localhost:9002", &directory).await.unwrap();
+    assert!(forbidden(calc.add_capability("admin", &directory).await));
+    assert!(client.discover(vec!["admin".to_string()], &directory).await.unwrap().is_none());
+
+    let operator = AuthConfig::Bearer("operator".to_string());
+    let mut ops = A2AAgent::new("ops", "Ops", vec!["admin".to_string()]).with_auth(operator);
+    ops.register("http://localhost:9003", &directory).await.unwrap();
+    let found = client.discover(vec!["admin".to_string()], &directory).await.unwrap().unwrap();
+    assert_eq!(found.agent_id, "ops");
+}
+
+fn rejected(result: Result<(), A2AError>) -> Option<RejectionReason> {
+    match result {
+        Err(A2AError::RegistrationRejected { reason, .. }) => Some(reason),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn duplicate_ids_and_bad_endpoints_are_rejected_with_a_reason() {
+    let directory = common::directory(A2ADirectory::new(0).with_unique_agent_ids(true)).await;
+    let mut calc = A2AAgent::new("calc", "Calc", vec![]);
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    // Heartbeats from the same endpoint still go through.
+    calc.register("http://localhost:9001/", &directory).await.unwrap();
+
+    let mut impostor = A2AAgent::new("calc", "Impostor", vec![]);
+    let duplicate = rejected(impostor.register("http://localhost:9002", &directory).await);
+    assert_eq!(duplicate, Some(RejectionReason::DuplicateId));
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let listed = client.discover_all(vec![], &directory).await.unwrap();
+    assert_eq!((listed.len(), listed[0].endpoint.trim_end_matches('/')), (1, "http://localhost:9001"));
+
+    let mut misconfigured = A2AAgent::new("mul", "Mul", vec![]);
+    for endpoint in ["localhost:9003", "/a2a", "http://", ""] {
+        let invalid = rejected(misconfigured.register(endpoint, &directory).await);
+        assert_eq!(invalid, Some(RejectionReason::InvalidEndpoint), "{:?}", endpoint);
+    }
+
+    // On the wire, the reason is in the error's `data`.
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "a2a/register",
+        "params": {"agentId": "calc", "name": "Calc", "capabilities": [], "endpoint": "http://localhost:9004"},
+    });
+    let response = reqwest::Client::new().post(format!("{}/a2a/register", directory)).json(&call).send();
+    let answer: serde_json::Value = response.await.unwrap().json().await.unwrap();
+    assert_eq!(answer["error"]["code"], -32602);
+    assert_eq!(answer["error"]["data"], json!({"reason": "duplicate_id"}));
+}
+
+#[tokio::test]
+async fn probing_directories_refuse_endpoints_they_cannot_reach() {
+    let directory = common::directory(A2ADirectory::new(0).with_reachability_probe(Duration::from_millis(300))).await;
+    let server = A2AServer::new("calc", "Calc", vec![], 0);
+    let reachable = common::serve(&server).await;
+    let failing = common::mock(Router::new().route("/health", get(|| async { StatusCode::SERVICE_UNAVAILABLE }))).await;
+    let hanging = common::mock(Router::new().route(
+        "/health",
+        get(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            "ok"
+        }),
+    ))
+    .await;
+
+    let mut calc = A2AAgent::new("calc", "Calc", vec![]).with_retry_policy(RetryPolicy::none());
+    for endpoint in ["http://127.0.0.1:1", &failing, &hanging] {
+        let started = std::time::Instant::now();
+        let unreachable = rejected(calc.register(endpoint, &directory).await);
+        assert_eq!(unreachable, Some(RejectionReason::UnreachableEndpoint), "{}", endpoint);
+        assert!(started.elapsed() < Duration::from_secs(2), "{}", endpoint);
+    }
+    let client = A2AAgent::new("client", "Client", vec![]);
+    assert!(client.discover_all(vec![], &directory).await.unwrap().is_empty());
+
+    calc.register(&reachable, &directory).await.unwrap();
+    assert_eq!(client.discover_all(vec![], &directory).await.unwrap().len(), 1);
+
+    // The probe is opt-in.
+    let trusting = common::directory(A2ADirectory::new(0)).await;
+    calc.register("http://127.0.0.1:1", &trusting).await.unwrap();
+}
+
+async fn next(received: &mut tokio::sync::mpsc::UnboundedReceiver<DirectoryEvent>) -> DirectoryEvent {
+    let event = tokio::time::timeout(Duration::from_secs(5), received.recv());
+    event.await.expect("no event arrived").unwrap()
+}
+
+#[tokio::test]
+async fn watchers_see_registrations_updates_and_deregistrations() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let dashboard = A2AAgent::new("dashboard", "Dashboard", vec![]);
+    let mut events = dashboard.watch_agents(CapabilityQuery::All(vec!["add".to_string()]), &directory);
+    let (forward, mut received) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            if forward.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    // The stream subscribes once polled; give it a moment to connect.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let mut calc = A2AAgent::new("calc", "Calc", vec!["add".to_string()]);
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    let event = next(&mut received).await;
+    assert_eq!((event.kind, event.agent.agent_id.as_str()), (DirectoryEventKind::Registered, "calc"));
+    assert_eq!(event.agent.endpoint, "http://localhost:9001");
+
+    // Agents outside the filter, and heartbeats that change nothing, are quiet.
+    let mut mul = A2AAgent::new("mul", "Mul", vec!["mul".to_string()]);
+    mul.register("http://localhost:9002", &directory).await.unwrap();
+    calc.register("http://localhost:9001", &directory).await.unwrap();
+    calc.add_capability("sub", &directory).await.unwrap();
+    let event = next(&mut received).await;
+    assert_eq!((event.kind, event.agent.agent_id.as_str()), (DirectoryEventKind::Updated, "calc"));
+    assert_eq!(event.agent.capabilities.len(), 2);
+
+    calc.deregister(&directory).await.unwrap();
+    let event = next(&mut received).await;
+    assert_eq!((event.kind, event.agent.agent_id.as_str()), (DirectoryEventKind::Deregistered, "calc"));
+}
+
+#[tokio::test]
+async fn watchers_reconnect_when_the_stream_ends() {
+    // Each connection reports one agent, then closes.
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let router = Router::new().route(
+        "/a2a/watch",
+        get(move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let agent = json!({
+                "agentId": format!("calc-{}", n),
+                "name": "Calc",
+                "capabilities": [],
+                "endpoint": "http://localhost:9001",
+            });
+            let event = json!({"kind": "registered", "agent": agent});
+            let body = format!("event: registered\ndata: {}\n\n", event);
+            async move { ([("content-type", "text/event-stream")], body) }
+        }),
+    );
+    let directory = common::mock(router).await;
+    let dashboard = A2AAgent::new("dashboard", "Dashboard", vec![]);
+    let mut events = dashboard.watch_agents(CapabilityQuery::All(vec![]), &directory);
+
+    for n in 0..2 {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap();
+        assert_eq!(event.agent.agent_id, format!("calc-{}", n));
+    }
+    assert!(connections.load(Ordering::SeqCst) >= 2);
+}
@@ -0,0 +1,272 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{A2AAgent, A2ADirectory, A2AError, AgentInfo, CapabilityQuery, PageOptions, RetryPolicy, SelectionStrategy};
+use axum::routing::post;
+use futures::TryStreamExt;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn listing(agent_id: &str, capabilities: &[&str]) -> Value {
+    json!({
+        "agent_id": agent_id,
+        "name": agent_id,
+        "capabilities": capabilities,
+        "endpoint": format!("http://{}.invalid/", agent_id),
+    })
+}
+
+/// A directory answering every discovery with `agents`.
+async fn directory(agents: Vec<Value>) -> String {
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(move |Json(call): Json<Value>| {
+            let agents = agents.clone();
+            async move { Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"agents": agents}})) }
+        }),
+    );
+    common::mock(router).await
+}
+
+/// A directory filtering `agents` by the query it is sent, reading the
+/// legacy `capabilities` list as `all`.
+async fn filtering_directory(agents: Vec<Value>) -> String {
+    let router = Router::new().route(
+        "/a2a/discover",
+        post(move |Json(call): Json<Value>| {
+            let agents: Vec<AgentInfo> = agents.iter().map(|a| serde_json::from_value(a.clone()).unwrap()).collect();
+            async move {
+                let params = &call["params"];
+                let query: CapabilityQuery = match params.get("query") {
+                    Some(query) => serde_json::from_value(query.clone()).unwrap(),
+                    None => CapabilityQuery::All(serde_json::from_value(params["capabilities"].clone()).unwrap()),
+                };
+                let found: Vec<&AgentInfo> = agents.iter().filter(|agent| agent.matches(&query)).collect();
+                Json(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"agents": found}}))
+            }
+        }),
+    );
+    common::mock(router).await
+}
+
+fn ids(agents: Vec<AgentInfo>) -> Vec<String> {
+    agents.into_iter().map(|agent| agent.agent_id).collect()
+}
+
+#[tokio::test]
+async fn every_reported_agent_is_returned_in_order() {
+    let directory = directory(vec![
+        listing("calc-1", &["add"]),
+        listing("calc-2", &["add", "mul"]),
+        listing("calc-3", &["add"]),
+    ])
+    .await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let agents = client.discover_all(vec!["add".to_string()], &directory).await.unwrap();
+    assert_eq!(ids(agents), ["calc-1", "calc-2", "calc-3"]);
+
+    let first = client.discover(vec!["add".to_string()], &directory).await.unwrap();
+    assert_eq!(first.map(|agent| agent.agent_id), Some("calc-1".to_string()));
+}
+
+#[tokio::test]
+async fn no_matches_is_an_empty_list() {
+    let directory = directory(vec![]).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    assert!(client.discover_all(vec!["add".to_string()], &directory).await.unwrap().is_empty());
+    assert!(client.discover(vec!["add".to_string()], &directory).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn cached_discoveries_skip_the_directory_until_they_expire() {
+    let (directory, calls) = common::recording(json!({"agents": [listing("calc", &["add", "mul"])]})).await;
+    let discoveries = || calls.lock().unwrap().len();
+    let client = A2AAgent::new("client", "Client", vec![]).with_discovery_cache(Duration::from_millis(300));
+
+    let wanted = || vec!["add".to_string(), "mul".to_string()];
+    assert_eq!(ids(client.discover_all(wanted(), &directory).await.unwrap()), ["calc"]);
+    // The same capabilities in another order hit the same entry.
+    client.discover_all(vec!["mul".to_string(), "add".to_string()], &directory).await.unwrap();
+    assert_eq!(discoveries(), 1);
+
+    client.discover_all(vec!["add".to_string()], &directory).await.unwrap();
+    assert_eq!(discoveries(), 2);
+
+    client.clear_discovery_cache();
+    client.discover_all(wanted(), &directory).await.unwrap();
+    assert_eq!(discoveries(), 3);
+
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    client.discover_all(wanted(), &directory).await.unwrap();
+    assert_eq!(discoveries(), 4);
+}
+
+#[tokio::test]
+async fn unreachable_directories_fail_over_to_the_next() {
+    let down = "http://127.0.0.1:1".to_string();
+    let (up, calls) = common::recording(json!({"agents": [listing("calc", &["add"])]})).await;
+    let mut client = A2AAgent::new("client", "Client", vec![])
+        .with_retry_policy(RetryPolicy::none())
+        .with_fallback_directories(vec![up.clone()]);
+
+    let agents = client.discover_all(vec!["add".to_string()], &down).await.unwrap();
+    assert_eq!(ids(agents), ["calc"]);
+    client.register("http://127.0.0.1:9/", &down).await.unwrap();
+    let paths: Vec<String> = calls.lock().unwrap().iter().map(|(path, _, _)| path.clone()).collect();
+    assert_eq!(paths, ["/a2a/discover", "/a2a/register"]);
+
+    let stranded = A2AAgent::new("client", "Client", vec![])
+        .with_retry_policy(RetryPolicy::none())
+        .with_fallback_directories(vec!["http://127.0.0.1:2".to_string()]);
+    match stranded.discover_all(vec!["add".to_string()], &down).await {
+        Err(A2AError::AllDirectoriesFailed(failures)) => {
+            let urls: Vec<&str> = failures.iter().map(|(url, _)| url.as_str()).collect();
+            assert_eq!(urls, ["http://127.0.0.1:1", "http://127.0.0.1:2"]);
+        }
+        other => panic!("expected AllDirectoriesFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn and_or_queries_reach_the_directory() {
+    let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+    let directory = filtering_directory(vec![
+        listing("searcher", &["search"]),
+        listing("summarizer", &["search", "summarize"]),
+        listing("translator", &["translate"]),
+    ])
+    .await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let both = CapabilityQuery::All(names(&["search", "summarize"]));
+    assert_eq!(ids(client.discover_matching(both, &directory).await.unwrap()), ["summarizer"]);
+
+    let either = CapabilityQuery::Any(names(&["search", "translate"]));
+    let found = client.discover_matching(either, &directory).await.unwrap();
+    assert_eq!(ids(found), ["searcher", "summarizer", "translator"]);
+
+    let nested = CapabilityQuery::AnyOf(vec![
+        CapabilityQuery::All(names(&["search", "summarize"])),
+        CapabilityQuery::Any(names(&["translate"])),
+    ]);
+    assert_eq!(ids(client.discover_matching(nested, &directory).await.unwrap()), ["summarizer", "translator"]);
+}
+
+#[tokio::test]
+async fn queries_are_sent_tagged_and_plain_all_as_a_list() {
+    let (directory, calls) = common::recording(json!({"agents": []})).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    client.discover_all(vec!["search".to_string()], &directory).await.unwrap();
+    let any = CapabilityQuery::Any(vec!["search".to_string(), "translate".to_string()]);
+    client.discover_matching(any, &directory).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls[0].2["params"]["capabilities"], json!(["search"]));
+    assert!(calls[0].2["params"].get("query").is_none());
+    assert_eq!(calls[1].2["params"]["query"], json!({"any": ["search", "translate"]}));
+}
+
+fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+}
+
+#[tokio::test]
+async fn only_agents_with_every_tag_are_returned() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    for (agent_id, region, tier) in [("eu-gold", "eu", "gold"), ("eu-free", "eu", "free"), ("us-gold", "us", "gold")] {
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec!["add".to_string()])
+            .with_tag("region", region)
+            .with_tag("tier", tier);
+        agent.register(&format!("http://{}.invalid", agent_id), &directory).await.unwrap();
+    }
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let add = || CapabilityQuery::All(vec!["add".to_string()]);
+
+    let found = client.discover_filtered(add(), tags(&[("region", "eu")]), &directory).await.unwrap();
+    assert_eq!(ids(found), ["eu-free", "eu-gold"]);
+    let found = client
+        .discover_filtered(add(), tags(&[("region", "eu"), ("tier", "gold")]), &directory)
+        .await
+        .unwrap();
+    assert_eq!(ids(found), ["eu-gold"]);
+    let found = client.discover_filtered(add(), tags(&[("region", "ap")]), &directory).await.unwrap();
+    assert!(found.is_empty());
+    let found = client.discover_filtered(add(), HashMap::new(), &directory).await.unwrap();
+    assert_eq!(found.len(), 3);
+}
+
+#[tokio::test]
+async fn tags_are_checked_again_when_the_directory_ignores_them() {
+    let mut tagged = listing("eu", &["add"]);
+    tagged["tags"] = json!({"region": "eu"});
+    let directory = directory(vec![tagged, listing("untagged", &["add"])]).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let query = CapabilityQuery::All(vec!["add".to_string()]);
+    let found = client.discover_filtered(query, tags(&[("region", "eu")]), &directory).await.unwrap();
+    assert_eq!(ids(found), ["eu"]);
+}
+
+#[tokio::test]
+async fn pages_walk_every_match_once() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    for n in 1..=7 {
+        let mut agent = A2AAgent::new(&format!("calc-{}", n), "Calc", vec!["add".to_string()]);
+        agent.register(&format!("http://calc-{}.invalid", n), &directory).await.unwrap();
+    }
+    let mut other = A2AAgent::new("search", "Search", vec!["find".to_string()]);
+    other.register("http://search.invalid", &directory).await.unwrap();
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let add = || CapabilityQuery::All(vec!["add".to_string()]);
+
+    let mut pages = Vec::new();
+    let mut options = PageOptions::new(3);
+    loop {
+        let page = client.discover_page(add(), options.clone(), &directory).await.unwrap();
+        pages.push(ids(page.items));
+        match page.next_cursor {
+            Some(cursor) => options = PageOptions::new(3).after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(pages, [vec!["calc-1", "calc-2", "calc-3"], vec!["calc-4", "calc-5", "calc-6"], vec!["calc-7"]]);
+
+    let streamed: Vec<AgentInfo> = client.discover_stream(add(), 2, &directory).try_collect().await.unwrap();
+    let expected: Vec<String> = (1..=7).map(|n| format!("calc-{}", n)).collect();
+    assert_eq!(ids(streamed), expected);
+}
+
+#[tokio::test]
+async fn round_robin_rotates_across_calls() {
+    let directory = directory(["calc-3", "calc-1", "calc-2"].map(|agent_id| listing(agent_id, &["add"])).to_vec()).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let picks = |strategy| {
+        let (client, directory) = (&client, &directory);
+        async move {
+            let mut picks = Vec::new();
+            for _ in 0..4 {
+                let add = CapabilityQuery::All(vec!["add".to_string()]);
+                let agent = client.discover_select(add, strategy, directory).await.unwrap().unwrap();
+                picks.push(agent.agent_id);
+            }
+            picks
+        }
+    };
+
+    assert_eq!(picks(SelectionStrategy::RoundRobin).await, ["calc-1", "calc-2", "calc-3", "calc-1"]);
+    assert_eq!(picks(SelectionStrategy::First).await, ["calc-3", "calc-3", "calc-3", "calc-3"]);
+    assert_eq!(picks(SelectionStrategy::LeastRecentlyUsed).await, ["calc-3", "calc-1", "calc-2", "calc-3"]);
+    for agent_id in picks(SelectionStrategy::Random).await {
+        assert!(["calc-1", "calc-2", "calc-3"].contains(&agent_id.as_str()));
+    }
+
+    // Each query rotates on its own.
+    let mul = CapabilityQuery::All(vec!["mul".to_string()]);
+    let first = client.discover_select(mul, SelectionStrategy::RoundRobin, &directory).await.unwrap();
+    assert_eq!(first.map(|agent| agent.agent_id), Some("calc-1".to_string()));
+}
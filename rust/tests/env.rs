@@ -0,0 +1,88 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AAgentBuilder, A2AError, AuthConfig, RetryPolicy};
+use axum::routing::post;
+use axum::Router;
+use serde_json::json;
+use std::env;
+use std::time::Duration;
+
+const VARS: [&str; 7] = [
+    "A2A_AGENT_ID",
+    "A2A_AGENT_NAME",
+    "A2A_DIRECTORY_URL",
+    "A2A_CAPABILITIES",
+    "A2A_TIMEOUT_SECS",
+    "A2A_AUTH_TOKEN",
+    "A2A_FALLBACK_DIRECTORIES",
+];
+
+fn set(vars: &[(&str, &str)]) {
+    for name in VARS {
+        env::remove_var(name);
+    }
+    for (name, value) in vars {
+        env::set_var(name, value);
+    }
+}
+
+fn config_error(result: Result<A2AAgent, A2AError>) -> String {
+    match result {
+        Err(A2AError::Config(message)) => message,
+        other => panic!("expected a Config error, got {:?}", other.map(|agent| agent.agent_id)),
+    }
+}
+
+// One test, since the environment is shared by the whole process.
+#[tokio::test]
+async fn agents_are_configured_from_the_environment() {
+    let (directory, calls) = common::recording(json!({"agents": []})).await;
+    set(&[
+        ("A2A_AGENT_ID", "calc"),
+        ("A2A_DIRECTORY_URL", "http://127.0.0.1:1"),
+        ("A2A_CAPABILITIES", "add, mul,,"),
+        ("A2A_AUTH_TOKEN", " s3cret "),
+        ("A2A_FALLBACK_DIRECTORIES", &directory),
+        ("A2A_AGENT_NAME", "  "),
+    ]);
+    let agent = A2AAgent::from_env().unwrap().with_retry_policy(RetryPolicy::none());
+    assert_eq!((agent.agent_id.as_str(), agent.name.as_str()), ("calc", "calc"));
+    let names: Vec<&str> = agent.capabilities.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["add", "mul"]);
+    assert_eq!(agent.directory_url.as_deref(), Some("http://127.0.0.1:1"));
+
+    // The unreachable directory fails over, sending the token along.
+    agent.discover_all(vec!["add".to_string()], agent.directory_url.as_deref().unwrap()).await.unwrap();
+    assert_eq!(calls.lock().unwrap()[0].1["authorization"], "Bearer s3cret");
+
+    // Settings made in code win over the environment.
+    let agent = A2AAgentBuilder::from_env().unwrap().auth(AuthConfig::Bearer("override".to_string())).build().unwrap();
+    agent.discover_all(vec![], &directory).await.unwrap();
+    assert_eq!(calls.lock().unwrap()[1].1["authorization"], "Bearer override");
+
+    // A2A_TIMEOUT_SECS bounds every request.
+    let slow = Router::new().route(
+        "/a2a/discover",
+        post(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            "late"
+        }),
+    );
+    let slow = common::mock(slow).await;
+    set(&[("A2A_AGENT_ID", "calc"), ("A2A_DIRECTORY_URL", &slow), ("A2A_TIMEOUT_SECS", "0.2")]);
+    let agent = A2AAgent::from_env().unwrap().with_retry_policy(RetryPolicy::none());
+    let timed_out = agent.discover_all(vec![], &slow).await;
+    assert!(matches!(timed_out, Err(A2AError::Timeout(_))), "{:?}", timed_out);
+
+    set(&[("A2A_DIRECTORY_URL", &slow)]);
+    assert!(config_error(A2AAgent::from_env()).contains("A2A_AGENT_ID"));
+    set(&[("A2A_AGENT_ID", "calc")]);
+    assert!(config_error(A2AAgent::from_env()).contains("A2A_DIRECTORY_URL"));
+    for timeout in ["0", "-1", "soon"] {
+        set(&[("A2A_AGENT_ID", "calc"), ("A2A_DIRECTORY_URL", &slow), ("A2A_TIMEOUT_SECS", timeout)]);
+        assert!(config_error(A2AAgent::from_env()).contains("A2A_TIMEOUT_SECS"), "{}", timeout);
+    }
+    set(&[]);
+}
@@ -0,0 +1,43 @@
+#![cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, A2AServer};
+use serde_json::json;
+
+/// The value of the sample named `name` whose labels include all of
+/// `labels`, as the server's `/metrics` reports it.
+async fn sample(server: &str, name: &str, labels: &[&str]) -> Option<f64> {
+    let text = reqwest::get(format!("{}/metrics", server)).await.unwrap().text().await.unwrap();
+    text.lines()
+        .filter(|line| line.starts_with(&format!("{}{{", name)))
+        .find(|line| labels.iter().all(|label| line.contains(label)))
+        .and_then(|line| line.rsplit(' ').next()?.parse().ok())
+}
+
+#[tokio::test]
+async fn finished_tasks_are_counted_on_both_sides() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+    let mut server = A2AServer::new("counted", "Counted", vec!["counted".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    let endpoint = common::serve(&server).await;
+    A2AAgent::new("counted", "Counted", vec!["counted".to_string()]).register(&endpoint, &directory).await.unwrap();
+
+    // The action is unique to this test, as the registry is process-wide.
+    let action = r#"action="count-me""#;
+    assert_eq!(sample(&endpoint, "a2a_tasks_total", &[action]).await, None);
+
+    let client = A2AAgent::new("client", "Client", vec![]);
+    for expected in [1.0, 2.0] {
+        client.send_task("counted", "count-me", json!(1), &directory).await.unwrap();
+        for side in [r#"side="server""#, r#"side="client""#] {
+            let labels = [side, action, r#"status="completed""#];
+            assert_eq!(sample(&endpoint, "a2a_tasks_total", &labels).await, Some(expected), "{}", side);
+            let count = sample(&endpoint, "a2a_task_duration_seconds_count", &[side, action]).await;
+            assert_eq!(count, Some(expected), "{}", side);
+        }
+    }
+
+    let served = [r#"side="server""#, r#"method="a2a/task""#, r#"outcome="ok""#];
+    assert!(sample(&endpoint, "a2a_requests_total", &served).await >= Some(2.0));
+}
@@ -0,0 +1,82 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, A2AError, A2AServer, InMemoryTransport, RetryPolicy};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+const PREFIX: &str = "/api/v2/a2a";
+const NAMESPACE: &str = "acme.a2a";
+
+fn agent(agent_id: &str, capabilities: Vec<String>) -> A2AAgent {
+    A2AAgent::new(agent_id, agent_id, capabilities)
+        .with_path_prefix(PREFIX)
+        .with_method_namespace(NAMESPACE)
+        .with_retry_policy(RetryPolicy::none())
+}
+
+#[tokio::test]
+async fn the_crate_serves_a_custom_namespace_end_to_end() {
+    let directory = common::directory(A2ADirectory::new(0).with_path_prefix(PREFIX).with_method_namespace(NAMESPACE)).await;
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_method_namespace(NAMESPACE);
+    server.handle_task(|_ctx, input| input);
+    let endpoint = common::serve(&server).await;
+
+    let mut echo = agent("echo", vec!["echo".to_string()]);
+    echo.register(&endpoint, &directory).await.unwrap();
+
+    let client = agent("client", vec![]);
+    let found = client.discover(vec!["echo".to_string()], &directory).await.unwrap();
+    assert_eq!(found.map(|agent| agent.agent_id), Some("echo".to_string()));
+
+    let result = client.send_task("echo", "echo", json!("hi"), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+
+    let submitted = client.submit_task("echo", "echo", json!("later"), &directory).await.unwrap();
+    let result = client
+        .wait_for_task("echo", &submitted.task_id, &directory, Duration::from_millis(10), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(result.output, Some(json!("later")));
+
+    echo.deregister(&directory).await.unwrap();
+    assert!(client.discover(vec!["echo".to_string()], &directory).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn default_names_miss_a_custom_namespace() {
+    let directory = common::directory(A2ADirectory::new(0).with_path_prefix(PREFIX).with_method_namespace(NAMESPACE)).await;
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_method_namespace(NAMESPACE);
+    server.handle_task(|_ctx, input| input);
+    let endpoint = common::serve(&server).await;
+    agent("echo", vec!["echo".to_string()]).register(&endpoint, &directory).await.unwrap();
+
+    // Right paths, default method names.
+    let mut stray = A2AAgent::new("stray", "Stray", vec![]).with_path_prefix(PREFIX).with_retry_policy(RetryPolicy::none());
+    let refused = stray.register("http://127.0.0.1:1", &directory).await;
+    assert!(matches!(refused, Err(A2AError::Rpc { code: -32601, .. })), "{:?}", refused);
+    let refused = stray.send_task("echo", "echo", json!("hi"), &directory).await;
+    assert!(matches!(refused, Err(A2AError::Rpc { code: -32601, .. })), "{:?}", refused);
+
+    // Default paths are not served at all.
+    let lost = A2AAgent::new("lost", "Lost", vec![]).with_retry_policy(RetryPolicy::none());
+    let refused = lost.discover(vec![], &directory).await;
+    assert!(matches!(refused, Err(A2AError::HttpStatus { status, .. }) if status == 404), "{:?}", refused);
+}
+
+#[tokio::test]
+async fn in_memory_directories_answer_any_namespace() {
+    let transport = InMemoryTransport::new();
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_method_namespace(NAMESPACE);
+    server.handle_task(|_ctx, input| input);
+    transport.mount("mem://echo", &server);
+
+    let mut echo = agent("echo", vec!["echo".to_string()]).with_transport(Arc::new(transport.clone()));
+    echo.register("mem://echo", "mem://directory").await.unwrap();
+
+    let client = agent("client", vec![]).with_transport(Arc::new(transport));
+    let result = client.send_task("echo", "echo", json!("hi"), "mem://directory").await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+}
@@ -0,0 +1,108 @@
+#![cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AServer, TRACEPARENT};
+use opentelemetry::trace::{TraceContextExt, TracerProvider};
+use opentelemetry::Value as Attribute;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use serde_json::json;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Export the spans of this thread to memory until the guard is dropped.
+fn exporting() -> (InMemorySpanExporter, tracing::subscriber::DefaultGuard) {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("a2a-test"));
+    let guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+    (exporter, guard)
+}
+
+/// The exported span named `name` whose `a2a.method` is `method`.
+fn span<'a>(spans: &'a [SpanData], name: &str, method: &str) -> &'a SpanData {
+    let method = Attribute::from(method.to_string());
+    spans
+        .iter()
+        .find(|span| {
+            span.name == name && span.attributes.iter().any(|kv| kv.key.as_str() == "a2a.method" && kv.value == method)
+        })
+        .unwrap_or_else(|| panic!("no {} span for {}", name, method))
+}
+
+/// Whether `span` descends from `ancestor`.
+fn is_below(spans: &[SpanData], span: &SpanData, ancestor: &SpanData) -> bool {
+    let mut parent = span.parent_span_id;
+    while parent != ancestor.span_context.span_id() {
+        match spans.iter().find(|span| span.span_context.span_id() == parent) {
+            Some(span) => parent = span.parent_span_id,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The `traceparent` each recorded call was sent with, if any.
+fn sent(calls: &common::Calls) -> Vec<Option<String>> {
+    let calls = calls.lock().unwrap();
+    let header = |headers: &axum::http::HeaderMap| Some(headers.get(TRACEPARENT)?.to_str().unwrap().to_string());
+    calls.iter().map(|(_, headers, _)| header(headers)).collect()
+}
+
+#[tokio::test]
+async fn calls_send_a_traceparent_for_their_request_span() {
+    let (directory, calls) = common::recording(json!({"agents": []})).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let (exporter, guard) = exporting();
+
+    let work = tracing::info_span!("work");
+    let trace_id = work.context().span().span_context().trace_id();
+    let discover = client.discover_all(vec!["add".to_string()], &directory);
+    discover.instrument(work).await.unwrap();
+    let spans = exporter.get_finished_spans().unwrap();
+    drop(guard);
+    // Without a `tracing-opentelemetry` layer there is no trace to send.
+    client.discover_all(vec!["add".to_string()], &directory).await.unwrap();
+
+    let request = span(&spans, "a2a.request", "a2a/discover");
+    let work = spans.iter().find(|span| span.name == "work").unwrap();
+    assert_eq!(request.span_context.trace_id(), trace_id);
+    assert!(is_below(&spans, request, work));
+
+    let expected = format!("00-{}-{}-01", trace_id, request.span_context.span_id());
+    assert_eq!(sent(&calls), [Some(expected), None]);
+}
+
+#[tokio::test]
+async fn the_server_span_is_a_child_of_the_client_span() {
+    let (downstream, calls) = common::recording(json!({"agents": []})).await;
+    let mut server = A2AServer::new("planner", "Planner", vec!["plan".to_string()], 0);
+    server.handle_task_async(move |_ctx, _input| {
+        let downstream = downstream.clone();
+        async move {
+            // Calls made while handling the task stay in the same trace.
+            let helper = A2AAgent::new("planner", "Planner", vec![]);
+            helper.discover_all(vec!["search".to_string()], &downstream).await.unwrap();
+            json!("planned")
+        }
+    });
+    let directory = common::lookup(&common::serve(&server).await, json!(["plan"])).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let (exporter, _guard) = exporting();
+
+    let result = client.send_task("planner", "plan", json!({}), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("planned")));
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let call = span(&spans, "a2a.request", "a2a/task");
+    let served = span(&spans, "a2a.server", "a2a/task");
+    let onward = span(&spans, "a2a.request", "a2a/discover");
+    assert_eq!(served.parent_span_id, call.span_context.span_id());
+    assert!(served.parent_span_is_remote);
+    assert!(is_below(&spans, onward, served));
+    for span in [served, onward] {
+        assert_eq!(span.span_context.trace_id(), call.span_context.trace_id());
+    }
+    assert_eq!(sent(&calls).len(), 1);
+}
@@ -0,0 +1,836 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{
+    A2AAgent, A2ADirectory, A2AError, A2AServer, Attachment, AuthConfig, BearerAuthorizer, Capability, InputGuard,
+    OverloadPolicy, RetryPolicy, TaskError, TaskStatus,
+};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// POST a raw JSON-RPC request, returning the parsed response.
+async fn post(url: &str, body: &Value) -> Value {
+    let response = reqwest::Client::new().post(url).json(body).send().await.unwrap();
+    response.json().await.unwrap()
+}
+
+fn task(task_id: &str, sender: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "a2a/task",
+        "params": {"taskId": task_id, "action": "pay", "sender": sender, "input": {}},
+    })
+}
+
+#[tokio::test]
+async fn a_resent_task_id_runs_the_handler_once() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0).with_idempotency(Duration::from_secs(60), 100);
+    let counter = runs.clone();
+    server.handle_task_async(move |_ctx, _input| {
+        let counter = counter.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            json!(counter.fetch_add(1, Ordering::SeqCst))
+        }
+    });
+    let url = common::serve(&server).await;
+
+    // A duplicate arriving while the first copy runs gets its outcome too.
+    let request = task("t1", "client");
+    let (first, second) = tokio::join!(post(&url, &request), post(&url, &request));
+    let third = post(&url, &request).await;
+    assert_eq!(first["result"], second["result"]);
+    assert_eq!(first["result"], third["result"]);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    post(&url, &task("t1", "someone-else")).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn snake_case_task_ids_are_deduplicated_too() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut server = A2AServer::new("pay", "Pay", vec![], 0).with_idempotency(Duration::from_secs(60), 100);
+    let counter = runs.clone();
+    server.handle_task(move |ctx, _input| json!([ctx.task_id, counter.fetch_add(1, Ordering::SeqCst)]));
+    let url = common::serve(&server).await;
+
+    let camel = task("t1", "client");
+    let mut snake = camel.clone();
+    let params = snake["params"].as_object_mut().unwrap();
+    let task_id = params.remove("taskId").unwrap();
+    params.insert("task_id".to_string(), task_id);
+
+    let first = post(&url, &snake).await;
+    assert_eq!(first["result"]["output"], json!(["t1", 0]));
+    // Either spelling names the same task.
+    for resent in [&snake, &camel] {
+        assert_eq!(post(&url, resent).await["result"], first["result"]);
+    }
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn refused_tasks_run_when_resent_with_the_same_task_id() {
+    let (server, gate) = gated(OverloadPolicy::Reject);
+    let server = server.with_idempotency(Duration::from_secs(60), 100);
+    let url = common::serve(&server).await;
+
+    let running = tokio::spawn({
+        let url = url.clone();
+        async move { post(&url, &task("first", "client")).await }
+    });
+    until_in_flight(&server, 1).await;
+    let mut submit = task("t2", "client");
+    submit["method"] = json!("a2a/task/submit");
+    for call in [task("t1", "client"), submit.clone()] {
+        assert_eq!(post(&url, &call).await["error"]["code"], -32000);
+    }
+    gate.add_permits(1);
+    assert_eq!(running.await.unwrap()["result"]["output"], "first");
+    let mut late = task("t3", "client");
+    late["params"]["deadlineMs"] = json!(0);
+    assert_eq!(post(&url, &late).await["error"]["code"], -32002);
+
+    // Neither refusal was remembered, so each copy sent again runs.
+    gate.add_permits(3);
+    assert_eq!(post(&url, &task("t1", "client")).await["result"]["output"], "t1");
+    assert_eq!(post(&url, &submit).await["result"]["status"], "accepted");
+    assert_eq!(post(&url, &task("t3", "client")).await["result"]["output"], "t3");
+    // Outcomes are: with no permit left, running t1 again would hang.
+    let resent = task("t1", "client");
+    let resent = tokio::time::timeout(Duration::from_secs(5), post(&url, &resent)).await.unwrap();
+    assert_eq!(resent["result"]["output"], "t1");
+}
+
+#[tokio::test]
+async fn handler_errors_reach_the_client_as_rpc_errors() {
+    let mut server = A2AServer::new("calc", "Calc", vec!["add".to_string()], 0);
+    server.handle_task(|ctx, _input| match ctx.action.as_str() {
+        "add" => Ok(json!(3)),
+        _ => Err(TaskError::new(-32042, "only add is supported")),
+    });
+    let directory = common::listed(&server, "calc").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let result = client.send_task("calc", "add", json!({}), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!(3)));
+
+    let refused = client.send_task("calc", "sub", json!({}), &directory).await;
+    match refused {
+        Err(A2AError::Rpc { code, message, .. }) => {
+            assert_eq!(code, -32042);
+            assert_eq!(message, "only add is supported");
+        }
+        other => panic!("expected an RPC error, got {:?}", other),
+    }
+
+    let raw = post(&common::serve(&server).await, &task("t1", "client")).await;
+    assert!(raw.get("result").is_none());
+    assert_eq!(raw["error"]["code"], -32042);
+}
+
+#[tokio::test]
+async fn streaming_handlers_send_chunks_in_order() {
+    let mut server = A2AServer::new("count", "Count", vec!["count".to_string()], 0);
+    server.handle_task_stream(|ctx, input| {
+        let to = input["to"].as_u64().unwrap_or(3);
+        let mut chunks: Vec<_> = (1..=to).map(|n| Ok(json!(n))).collect();
+        if ctx.action == "fail" {
+            chunks.push(Err(TaskError::new(-32050, "ran out")));
+            chunks.push(Ok(json!("never sent")));
+        }
+        futures::stream::iter(chunks)
+    });
+    let directory = common::listed(&server, "count").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let chunks: Vec<_> = client.send_task_stream("count", "count", json!({"to": 3}), &directory).await.unwrap().collect().await;
+    let chunks: Vec<_> = chunks.into_iter().map(Result::unwrap).collect();
+    assert_eq!(chunks.iter().map(|chunk| chunk.data.clone()).collect::<Vec<_>>(), [json!(1), json!(2), json!(3)]);
+    assert_eq!(chunks.iter().map(|chunk| chunk.index).collect::<Vec<_>>(), [0, 1, 2]);
+
+    let chunks: Vec<_> = client.send_task_stream("count", "fail", json!({"to": 1}), &directory).await.unwrap().collect().await;
+    assert_eq!(chunks.len(), 2);
+    assert!(matches!(chunks[1], Err(A2AError::Rpc { code: -32050, .. })), "{:?}", chunks[1]);
+}
+
+#[tokio::test]
+async fn batches_answer_each_task_on_its_own() {
+    let mut server = A2AServer::new("calc", "Calc", vec!["add".to_string()], 0);
+    server.handle_task(|ctx, input| match ctx.action.as_str() {
+        "add" => Ok(json!(input["a"].as_i64().unwrap_or(0) + 1)),
+        _ => Err(TaskError::new(-32077, "unsupported")),
+    });
+    let directory = common::listed(&server, "calc").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let results = client
+        .send_tasks_batch(
+            vec![
+                ("calc".to_string(), "add".to_string(), json!({"a": 1})),
+                ("calc".to_string(), "sub".to_string(), json!({"a": 1})),
+                ("nobody".to_string(), "add".to_string(), json!({})),
+                ("calc".to_string(), "add".to_string(), json!({"a": 5})),
+            ],
+            &directory,
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap().output, Some(json!(2)));
+    assert!(matches!(results[1], Err(A2AError::Rpc { code: -32077, .. })), "{:?}", results[1]);
+    assert!(matches!(results[2], Err(A2AError::AgentNotFound(_))), "{:?}", results[2]);
+    assert_eq!(results[3].as_ref().unwrap().output, Some(json!(6)));
+
+    // On the wire: one array in, one array out.
+    let mut add = task("t2", "client");
+    add["id"] = json!(2);
+    add["params"]["action"] = json!("add");
+    let answers = post(&common::serve(&server).await, &json!([task("t1", "client"), add])).await;
+    let answer = |id: i64| answers.as_array().unwrap().iter().find(|answer| answer["id"] == id).unwrap().clone();
+    assert_eq!(answer(1)["error"]["code"], -32077);
+    assert_eq!(answer(2)["result"]["output"], 1);
+}
+
+#[tokio::test]
+async fn ping_reaches_the_health_endpoint() {
+    let server = A2AServer::new("calc", "Calc", vec![], 0);
+    let url = common::serve(&server).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let rtt = client.ping(&url).await.unwrap();
+    assert!(rtt < Duration::from_secs(5));
+
+    let guarded = A2AServer::new("calc", "Calc", vec![], 0).with_health_auth("probe");
+    let url = common::serve(&guarded).await;
+    assert!(client.ping(&url).await.is_err());
+    client.with_auth(AuthConfig::Bearer("probe".to_string())).ping(&url).await.unwrap();
+
+    let down = A2AAgent::new("client", "Client", vec![]).with_retry_policy(RetryPolicy::none());
+    assert!(down.ping("http://127.0.0.1:1").await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_running_tasks_then_returns() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let counter = finished.clone();
+    let mut server = A2AServer::new("slow", "Slow", vec![], 0);
+    server.handle_task_async(move |_ctx, _input| {
+        let counter = counter.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            counter.fetch_add(1, Ordering::SeqCst);
+            json!("done")
+        }
+    });
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let (addr, serving) = server
+        .bind_with_shutdown(async {
+            stopped.await.ok();
+        })
+        .await
+        .unwrap();
+    let serving = tokio::spawn(serving);
+    let url = format!("http://127.0.0.1:{}", addr.port());
+
+    let call = tokio::spawn(async move { post(&url, &task("t1", "client")).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    stop.send(()).unwrap();
+
+    // The call in flight is answered before the server stops.
+    let answer = call.await.unwrap();
+    assert_eq!(answer["result"]["output"], "done");
+    tokio::time::timeout(Duration::from_secs(3), serving).await.unwrap().unwrap().unwrap();
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+    assert!(reqwest::get(format!("http://127.0.0.1:{}/health", addr.port())).await.is_err());
+}
+
+#[tokio::test]
+async fn correlation_ids_follow_tasks_across_hops() {
+    let directory = common::directory(A2ADirectory::new(0)).await;
+
+    let mut c = A2AServer::new("c", "C", vec!["leaf".to_string()], 0);
+    c.handle_task(|ctx, _input| json!({"c": ctx.correlation_id}));
+    let mut b = A2AServer::new("b", "B", vec!["relay".to_string()], 0);
+    let onward = directory.clone();
+    b.handle_task_async(move |ctx, _input| {
+        let directory = onward.clone();
+        async move {
+            let agent = A2AAgent::new("b", "B", vec![]);
+            let leaf = agent.send_task("c", "leaf", json!({}), &directory).await.unwrap();
+            json!({"b": ctx.correlation_id, "c": leaf.output.unwrap()["c"]})
+        }
+    });
+    for (agent_id, server) in [("b", &b), ("c", &c)] {
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec![]);
+        agent.register(&common::serve(server).await, &directory).await.unwrap();
+    }
+
+    let a = A2AAgent::new("a", "A", vec![]);
+    let chained = a2a::with_correlation_id("trace-1", a.send_task("b", "relay", json!({}), &directory)).await;
+    assert_eq!(chained.unwrap().output, Some(json!({"b": "trace-1", "c": "trace-1"})));
+
+    // Outside a scope each task gets a fresh ID, still shared down the chain.
+    let first = a.send_task("b", "relay", json!({}), &directory).await.unwrap().output.unwrap();
+    let second = a.send_task("b", "relay", json!({}), &directory).await.unwrap().output.unwrap();
+    assert_eq!(first["b"], first["c"]);
+    assert_ne!(first["b"], second["b"]);
+    assert!(!first["b"].as_str().unwrap().is_empty());
+}
+
+/// A server running one task at a time, whose handler holds its slot
+/// until `gate` hands it a permit.
+fn gated(policy: OverloadPolicy) -> (A2AServer, Arc<tokio::sync::Semaphore>) {
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let held = gate.clone();
+    let mut server = A2AServer::new("gated", "Gated", vec![], 0).with_max_concurrent_tasks(1, policy);
+    server.handle_task_async(move |ctx, _input| {
+        let held = held.clone();
+        async move {
+            held.acquire().await.unwrap().forget();
+            json!(ctx.task_id)
+        }
+    });
+    (server, gate)
+}
+
+/// Wait until `server` runs `count` handlers.
+async fn until_in_flight(server: &A2AServer, count: usize) {
+    while server.in_flight_tasks() != count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test]
+async fn tasks_over_the_limit_are_rejected() {
+    let (server, gate) = gated(OverloadPolicy::Reject);
+    let url = common::serve(&server).await;
+
+    let running = tokio::spawn({
+        let url = url.clone();
+        async move { post(&url, &task("t1", "client")).await }
+    });
+    until_in_flight(&server, 1).await;
+    let refused = post(&url, &task("t2", "client")).await;
+    assert_eq!(refused["error"]["code"], -32000);
+
+    gate.add_permits(1);
+    assert_eq!(running.await.unwrap()["result"]["output"], "t1");
+    assert_eq!(server.in_flight_tasks(), 0);
+}
+
+#[tokio::test]
+async fn tasks_over_the_limit_queue_for_a_slot() {
+    let (server, gate) = gated(OverloadPolicy::Queue);
+    let url = common::serve(&server).await;
+
+    let calls: Vec<_> = ["t1", "t2"]
+        .into_iter()
+        .map(|task_id| {
+            let url = url.clone();
+            tokio::spawn(async move { post(&url, &task(task_id, "client")).await })
+        })
+        .collect();
+    until_in_flight(&server, 1).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // The second task waits rather than running alongside the first.
+    assert_eq!(server.in_flight_tasks(), 1);
+
+    gate.add_permits(2);
+    for (call, task_id) in calls.into_iter().zip(["t1", "t2"]) {
+        assert_eq!(call.await.unwrap()["result"]["output"], task_id);
+    }
+}
+
+#[tokio::test]
+async fn cancelled_handlers_return_early() {
+    let mut server = A2AServer::new("slow", "Slow", vec![], 0);
+    server.handle_task_async(|ctx, _input| async move {
+        tokio::select! {
+            _ = ctx.cancellation.cancelled() => json!("stopped early"),
+            _ = tokio::time::sleep(Duration::from_secs(30)) => json!("ran to the end"),
+        }
+    });
+    let directory = common::listed(&server, "slow").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let submitted = client.submit_task("slow", "work", json!({}), &directory).await.unwrap();
+    assert_eq!(submitted.status, TaskStatus::Accepted);
+    client.cancel_task("slow", &submitted.task_id, &directory).await.unwrap();
+
+    let finished = client
+        .wait_for_task("slow", &submitted.task_id, &directory, Duration::from_millis(20), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(finished.status, TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn requests_must_declare_json_rpc_2() {
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0);
+    server.handle_task(|_ctx, _input| json!("paid"));
+    let url = common::serve(&server).await;
+
+    assert_eq!(post(&url, &task("t1", "client")).await["result"]["output"], "paid");
+    let mut wrong = task("t2", "client");
+    wrong["jsonrpc"] = json!("1.0");
+    let mut missing = task("t3", "client");
+    missing.as_object_mut().unwrap().remove("jsonrpc");
+    for request in [wrong, missing] {
+        let answer = post(&url, &request).await;
+        assert_eq!(answer["error"]["code"], -32600, "{}", answer);
+        assert!(answer.get("result").is_none());
+    }
+}
+
+#[tokio::test]
+async fn running_servers_hand_out_their_card() {
+    let schema = json!({"type": "object", "required": ["a", "b"]});
+    let add = Capability::new("add").with_description("Adds two numbers").with_input_schema(schema.clone());
+    let server = A2AServer::new("calc", "Calculator", vec![], 0).with_capabilities(vec![add, Capability::new("echo")]);
+    let endpoint = common::serve(&server).await;
+
+    let card = A2AAgent::new("client", "Client", vec![]).fetch_card(&endpoint).await.unwrap();
+    assert_eq!(card.agent_id, "calc");
+    assert_eq!(card.name, "Calculator");
+    let names: Vec<&str> = card.capabilities.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["add", "echo"]);
+    assert_eq!(card.capabilities[0].description.as_deref(), Some("Adds two numbers"));
+    assert_eq!(card.capabilities[0].input_schema, Some(schema));
+
+    let down = A2AAgent::new("client", "Client", vec![]).with_retry_policy(RetryPolicy::none());
+    assert!(down.fetch_card("http://127.0.0.1:1").await.is_err());
+}
+
+#[tokio::test]
+async fn expired_deadlines_are_refused_before_the_handler_runs() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0);
+    server.handle_task(move |ctx, _input| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let budget_ms = a2a::remaining_budget().map(|left| left.as_millis() as u64);
+        json!({"hasDeadline": ctx.deadline.is_some(), "budgetMs": budget_ms})
+    });
+    let directory = common::listed(&server, "pay").await;
+    let url = common::serve(&server).await;
+
+    let mut expired = task("t1", "client");
+    expired["params"]["deadlineMs"] = json!(0);
+    assert_eq!(post(&url, &expired).await["error"]["code"], -32002);
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let refused = a2a::with_deadline(Duration::ZERO, client.send_task("pay", "pay", json!({}), &directory)).await;
+    assert!(matches!(refused, Err(A2AError::DeadlineExceeded(_))), "{:?}", refused);
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    // A live deadline reaches the handler, less the time already spent.
+    let budget = Duration::from_secs(5);
+    let result = a2a::with_deadline(budget, client.send_task("pay", "pay", json!({}), &directory)).await;
+    let output = result.unwrap().output.unwrap();
+    assert_eq!(output["hasDeadline"], true);
+    let left = output["budgetMs"].as_u64().unwrap();
+    assert!(left > 0 && left < 5000, "{}", left);
+
+    let unbounded = client.send_task("pay", "pay", json!({}), &directory).await.unwrap().output.unwrap();
+    assert_eq!(unbounded, json!({"hasDeadline": false, "budgetMs": null}));
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn custom_methods_are_routed_by_name() {
+    let mut server = A2AServer::new("stats", "Stats", vec![], 0);
+    server.handle_task(|_ctx, _input| json!("task"));
+    server.register_method("a2a/stats", |ctx, params| async move {
+        json!({"method": ctx.method, "echo": params["window"]})
+    });
+    server.register_method("a2a/fail", |_ctx, _params| async { Err::<Value, _>(TaskError::new(-32050, "no stats")) });
+    let url = common::serve(&server).await;
+    let call = |method: &str| json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": {"window": "1h"}});
+
+    let answer = post(&url, &call("a2a/stats")).await;
+    assert_eq!(answer["id"], 7);
+    assert_eq!(answer["result"], json!({"method": "a2a/stats", "echo": "1h"}));
+    assert_eq!(post(&url, &call("a2a/fail")).await["error"]["code"], -32050);
+    assert_eq!(post(&url, &call("a2a/unknown")).await["error"]["code"], -32601);
+    // The task method is still served alongside.
+    assert_eq!(post(&url, &task("t1", "client")).await["result"]["output"], "task");
+}
+
+#[tokio::test]
+async fn binding_port_zero_reports_the_chosen_port() {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    let (addr, serving) = server.bind().await.unwrap();
+    assert_ne!(addr.port(), 0);
+
+    let endpoint = format!("http://127.0.0.1:{}", addr.port());
+    tokio::spawn(serving);
+    let directory = common::lookup(&endpoint, json!(["echo"])).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let result = client.send_task("echo", "echo", json!("hi"), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+
+    // The port stays taken while the server runs.
+    assert!(std::net::TcpListener::bind(("127.0.0.1", addr.port())).is_err());
+}
+
+#[tokio::test]
+async fn actions_route_to_their_own_handlers() {
+    let mut server = A2AServer::new("calc", "Calc", vec!["add".to_string(), "echo".to_string()], 0);
+    server.on_action("add", |_ctx, input| json!(input["a"].as_i64().unwrap() + input["b"].as_i64().unwrap()));
+    server.on_action_async("echo", |_ctx, input| async move { input });
+    let directory = common::listed(&server, "calc").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let sum = client.send_task("calc", "add", json!({"a": 2, "b": 3}), &directory).await.unwrap();
+    assert_eq!(sum.output, Some(json!(5)));
+    let echoed = client.send_task("calc", "echo", json!({"a": 2, "b": 3}), &directory).await.unwrap();
+    assert_eq!(echoed.output, Some(json!({"a": 2, "b": 3})));
+    match client.send_task("calc", "mul", json!({}), &directory).await {
+        Err(A2AError::Rpc { code, message, .. }) => {
+            assert_eq!(code, -32601);
+            assert!(message.contains("Unknown action"), "{}", message);
+        }
+        other => panic!("expected an unknown action error, got {:?}", other),
+    }
+
+    // A catch-all takes whatever no action handler matches.
+    server.handle_task(|ctx, _input| json!(format!("fallback for {}", ctx.action)));
+    let directory = common::listed(&server, "calc").await;
+    let fallback = client.send_task("calc", "mul", json!({}), &directory).await.unwrap();
+    assert_eq!(fallback.output, Some(json!("fallback for mul")));
+    let sum = client.send_task("calc", "add", json!({"a": 1, "b": 1}), &directory).await.unwrap();
+    assert_eq!(sum.output, Some(json!(2)));
+}
+
+/// [`task`] with `input` in place of `{}`.
+fn task_with_input(task_id: &str, input: Value) -> Value {
+    let mut call = task(task_id, "client");
+    call["params"]["input"] = input;
+    call
+}
+
+#[tokio::test]
+async fn guards_refuse_misshapen_and_oversized_input() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let guard = InputGuard::new().require_object().max_bytes(64);
+    let mut server = A2AServer::new("pay", "Pay", vec![], 0).with_input_guard(guard);
+    server.handle_task(move |_ctx, _input| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        json!("paid")
+    });
+    let url = common::serve(&server).await;
+
+    let array = post(&url, &task_with_input("t1", json!([1, 2]))).await;
+    assert_eq!(array["error"]["code"], -32602);
+    assert!(array["error"]["message"].as_str().unwrap().contains("JSON object"), "{}", array);
+    let oversized = post(&url, &task_with_input("t2", json!({"memo": "x".repeat(100)}))).await;
+    assert_eq!(oversized["error"]["code"], -32602);
+    assert!(oversized["error"]["message"].as_str().unwrap().contains("64 bytes"), "{}", oversized);
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    let fine = post(&url, &task_with_input("t3", json!({"memo": "x"}))).await;
+    assert_eq!(fine["result"]["output"], "paid");
+}
+
+#[tokio::test]
+async fn sleeping_handlers_trip_the_timeout() {
+    let mut server = A2AServer::new("sleepy", "Sleepy", vec![], 0)
+        .with_handler_timeout(Duration::from_millis(100))
+        .with_action_timeout("patient", Duration::from_secs(5));
+    server.handle_task_async(|_ctx, input| async move {
+        tokio::time::sleep(Duration::from_millis(input["ms"].as_u64().unwrap())).await;
+        json!("woke")
+    });
+    let url = common::serve(&server).await;
+
+    let started = std::time::Instant::now();
+    let timed_out = post(&url, &task_with_input("t1", json!({"ms": 2000}))).await;
+    assert_eq!(timed_out["error"]["code"], -32001, "{}", timed_out);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(server.in_flight_tasks(), 0);
+
+    assert_eq!(post(&url, &task_with_input("t2", json!({"ms": 10}))).await["result"]["output"], "woke");
+    let mut patient = task_with_input("t3", json!({"ms": 300}));
+    patient["params"]["action"] = json!("patient");
+    assert_eq!(post(&url, &patient).await["result"]["output"], "woke");
+}
+
+#[tokio::test]
+async fn attachments_reach_the_handler_as_sent() {
+    let mut server = A2AServer::new("sizer", "Sizer", vec![], 0).with_max_attachment_bytes(1024);
+    server.handle_task(|ctx, _input| {
+        let parts: Vec<Value> = ctx
+            .attachments
+            .iter()
+            .map(|a| json!({"name": a.name, "type": a.content_type, "len": a.bytes.len(), "first": a.bytes[0]}))
+            .collect();
+        json!(parts)
+    });
+    let directory = common::listed(&server, "sizer").await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+
+    let image = Attachment::new("pixel.png", "image/png", vec![0x89, b'P', b'N', b'G', 0, 0xff]);
+    let notes = Attachment::new("notes.txt", "text/plain", b"hello".to_vec());
+    let result = client
+        .send_task_with_attachments("sizer", "measure", json!({}), vec![image, notes], &directory)
+        .await
+        .unwrap();
+    let expected = json!([
+        {"name": "pixel.png", "type": "image/png", "len": 6, "first": 0x89},
+        {"name": "notes.txt", "type": "text/plain", "len": 5, "first": b'h'},
+    ]);
+    assert_eq!(result.output, Some(expected));
+
+    let too_big = Attachment::new("big.bin", "application/octet-stream", vec![0; 2048]);
+    let refused = client.send_task_with_attachments("sizer", "measure", json!({}), vec![too_big], &directory).await;
+    assert!(matches!(refused, Err(A2AError::Rpc { code: -32600, .. })), "{:?}", refused);
+    let unsafe_name = Attachment::new("a\r\nb", "text/plain", b"x".to_vec());
+    let refused = client.send_task_with_attachments("sizer", "measure", json!({}), vec![unsafe_name], &directory).await;
+    assert!(matches!(refused, Err(A2AError::InvalidAttachment(_))), "{:?}", refused);
+}
+
+#[tokio::test]
+async fn notifications_run_without_an_answer() {
+    let invalidated = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut server = A2AServer::new("cache", "Cache", vec![], 0);
+    let seen = invalidated.clone();
+    server.register_method("a2a/invalidate", move |_ctx, params| {
+        let seen = seen.clone();
+        async move {
+            seen.lock().unwrap().push(params["key"].clone());
+            Err::<Value, _>(TaskError::new(-32050, "never sent back"))
+        }
+    });
+    let url = common::serve(&server).await;
+
+    let client = A2AAgent::new("client", "Client", vec![]);
+    client.notify(&url, "a2a/invalidate", json!({"key": "rates"})).await.unwrap();
+    assert_eq!(*invalidated.lock().unwrap(), [json!("rates")]);
+
+    // Without an `id` the server answers with no body, even for errors.
+    for method in ["a2a/invalidate", "a2a/unknown"] {
+        let notification = json!({"jsonrpc": "2.0", "method": method, "params": {"key": "fx"}});
+        let response = reqwest::Client::new().post(&url).json(&notification).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+        assert!(response.bytes().await.unwrap().is_empty());
+    }
+    assert_eq!(*invalidated.lock().unwrap(), [json!("rates"), json!("fx")]);
+
+    // Failing to deliver one is still an error.
+    assert!(client.notify("http://127.0.0.1:1", "a2a/invalidate", json!({})).await.is_err());
+}
+
+#[tokio::test]
+async fn unauthenticated_tasks_are_refused_before_the_handler() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let authorizer = BearerAuthorizer::new("s3cret").with_sender_token("planner", "planner-token");
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0).with_authorizer(authorizer);
+    let counter = runs.clone();
+    server.handle_task(move |_ctx, _input| json!(counter.fetch_add(1, Ordering::SeqCst)));
+    let directory = common::lookup(&common::serve(&server).await, json!(["pay"])).await;
+    let send = |agent_id: &str, auth: Option<&str>| {
+        let mut agent = A2AAgent::new(agent_id, agent_id, vec![]);
+        if let Some(token) = auth {
+            agent = agent.with_auth(AuthConfig::Bearer(token.to_string()));
+        }
+        let directory = directory.clone();
+        async move { agent.send_task("pay", "pay", json!({}), &directory).await }
+    };
+
+    for (sender, token) in [("client", None), ("client", Some("guess")), ("client", Some("planner-token"))] {
+        match send(sender, token).await {
+            Err(A2AError::Rpc { code: -32003, .. }) => {}
+            other => panic!("expected -32003 for {:?}, got {:?}", token, other),
+        }
+    }
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    assert_eq!(send("client", Some("s3cret")).await.unwrap().output, Some(json!(0)));
+    assert_eq!(send("planner", Some("planner-token")).await.unwrap().output, Some(json!(1)));
+}
+
+#[tokio::test]
+async fn only_the_authorized_sender_reads_or_cancels_its_tasks() {
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let authorizer = BearerAuthorizer::new("s3cret").with_sender_token("planner", "planner-token");
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0).with_authorizer(authorizer);
+    let held = gate.clone();
+    server.handle_task_async(move |_ctx, _input| {
+        let held = held.clone();
+        async move {
+            held.acquire().await.unwrap().forget();
+            json!("paid")
+        }
+    });
+    let url = common::serve(&server).await;
+    let directory = common::lookup(&url, json!(["pay"])).await;
+    let agent = |agent_id: &str, token: &str| {
+        A2AAgent::new(agent_id, agent_id, vec![]).with_auth(AuthConfig::Bearer(token.to_string()))
+    };
+    let client = agent("client", "s3cret");
+    let task_id = client.submit_task("pay", "pay", json!({}), &directory).await.unwrap().task_id;
+
+    for method in ["a2a/task/status", "a2a/task/cancel"] {
+        let params = json!({"taskId": task_id, "sender": "client"});
+        let call = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        let refused = post(&url, &call).await;
+        assert_eq!(refused["error"]["code"], -32003, "{}: {}", method, refused);
+    }
+    let impostor = agent("client", "planner-token");
+    match impostor.get_task_status("pay", &task_id, &directory).await {
+        Err(A2AError::Rpc { code: -32003, .. }) => {}
+        other => panic!("expected -32003, got {:?}", other),
+    }
+
+    // Another sender, even one the server accepts, knows nothing of it.
+    let other = agent("auditor", "s3cret");
+    for looked_up in [
+        other.get_task_status("pay", &task_id, &directory).await,
+        other.cancel_task("pay", &task_id, &directory).await,
+    ] {
+        match looked_up {
+            Err(A2AError::Rpc { code: -32602, message, .. }) => {
+                assert!(message.contains("Unknown task"), "{}", message)
+            }
+            other => panic!("expected an unknown task, got {:?}", other),
+        }
+    }
+    assert_eq!(client.get_task_status("pay", &task_id, &directory).await.unwrap().status, TaskStatus::Running);
+
+    assert_eq!(client.cancel_task("pay", &task_id, &directory).await.unwrap().status, TaskStatus::Cancelled);
+    gate.add_permits(1);
+    let done = client.wait_for_task("pay", &task_id, &directory, Duration::from_millis(10), Duration::from_secs(5));
+    assert_eq!(done.await.unwrap().status, TaskStatus::Cancelled);
+}
+
+/// Records each lifecycle event it sees, prefixed with its own label.
+struct Lifecycle {
+    label: &'static str,
+    events: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl Lifecycle {
+    fn push(&self, event: String) {
+        self.events.lock().unwrap().push(format!("{} {}", self.label, event));
+    }
+}
+
+#[async_trait::async_trait]
+impl a2a::TaskObserver for Lifecycle {
+    async fn on_received(&self, ctx: &a2a::TaskContext) {
+        self.push(format!("received {}", ctx.task_id));
+    }
+
+    async fn on_started(&self, ctx: &a2a::TaskContext) {
+        self.push(format!("started {}", ctx.task_id));
+    }
+
+    async fn on_completed(&self, result: &a2a::TaskResult) {
+        self.push(format!("completed {} {:?}", result.task_id, result.output));
+    }
+
+    async fn on_failed(&self, task_id: &str, error: &TaskError) {
+        self.push(format!("failed {} {}", task_id, error.code));
+    }
+}
+
+#[tokio::test]
+async fn observers_see_each_step_of_a_task() {
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let observer = |label| Lifecycle { label, events: events.clone() };
+    let mut server = A2AServer::new("pay", "Pay", vec!["pay".to_string()], 0)
+        .with_observer(observer("audit"))
+        .with_observer(observer("metrics"));
+    server.handle_task(|ctx, _input| match ctx.task_id.as_str() {
+        "t1" => Ok(json!("paid")),
+        _ => Err(TaskError::new(-32050, "declined")),
+    });
+    let url = common::serve(&server).await;
+
+    assert_eq!(post(&url, &task("t1", "client")).await["result"]["output"], "paid");
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "audit received t1",
+            "metrics received t1",
+            "audit started t1",
+            "metrics started t1",
+            "audit completed t1 Some(String(\"paid\"))",
+            "metrics completed t1 Some(String(\"paid\"))",
+        ]
+    );
+
+    events.lock().unwrap().clear();
+    assert_eq!(post(&url, &task("t2", "client")).await["error"]["code"], -32050);
+    let seen = events.lock().unwrap().clone();
+    assert_eq!(seen[4..], ["audit failed t2 -32050", "metrics failed t2 -32050"]);
+}
+
+#[tokio::test]
+async fn queued_tasks_of_higher_priority_run_first() {
+    let started = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut server = A2AServer::new("pay", "Pay", vec![], 0)
+        .with_max_concurrent_tasks(1, OverloadPolicy::Queue)
+        .with_observer(Lifecycle { label: "queue", events: events.clone() });
+    let (seen, held) = (started.clone(), gate.clone());
+    server.handle_task_async(move |ctx, _input| {
+        let (seen, held) = (seen.clone(), held.clone());
+        async move {
+            seen.lock().unwrap().push((ctx.task_id.clone(), ctx.priority));
+            held.acquire().await.unwrap().forget();
+            json!(ctx.task_id)
+        }
+    });
+    let url = common::serve(&server).await;
+    let directory = common::lookup(&url, json!([])).await;
+
+    let running = tokio::spawn({
+        let url = url.clone();
+        async move { post(&url, &task("first", "client")).await }
+    });
+    until_in_flight(&server, 1).await;
+    // Low priority queues first, then high priority from a client.
+    let mut low = task("low", "client");
+    low["params"]["priority"] = json!(10);
+    let low = tokio::spawn({
+        let url = url.clone();
+        async move { post(&url, &low).await }
+    });
+    let received = |count| {
+        let events = events.clone();
+        async move {
+            while events.lock().unwrap().iter().filter(|e| e.contains("received")).count() < count {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            // Queued as soon as it is received; give that a moment to finish.
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+    received(2).await;
+    let high = tokio::spawn(async move {
+        let client = A2AAgent::new("client", "Client", vec![]);
+        a2a::with_priority(250, client.send_task("pay", "pay", json!({}), &directory)).await
+    });
+    received(3).await;
+    assert_eq!(server.in_flight_tasks(), 1);
+
+    gate.add_permits(3);
+    assert_eq!(running.await.unwrap()["result"]["output"], "first");
+    assert_eq!(low.await.unwrap()["result"]["output"], "low");
+    let high = high.await.unwrap().unwrap();
+    let started = started.lock().unwrap().clone();
+    assert_eq!(
+        started,
+        [("first".to_string(), a2a::DEFAULT_PRIORITY), (high.task_id, 250), ("low".to_string(), 10)]
+    );
+}
@@ -0,0 +1,73 @@
+#![cfg(all(feature = "signing", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AError, A2AServer, SigningKey};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+fn signed_echo(key: SigningKey) -> A2AServer {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0).with_signing_key(key);
+    server.handle_task(|_ctx, input| input);
+    server
+}
+
+/// A peer relaying calls to `upstream` and swapping the output of every
+/// result on the way back.
+async fn tampering(upstream: String) -> String {
+    let router = Router::new().route(
+        "/",
+        post(move |Json(call): Json<Value>| {
+            let upstream = upstream.clone();
+            async move {
+                let mut answer: Value =
+                    reqwest::Client::new().post(&upstream).json(&call).send().await.unwrap().json().await.unwrap();
+                answer["result"]["output"] = json!("forged");
+                Json(answer)
+            }
+        }),
+    );
+    common::mock(router).await
+}
+
+#[tokio::test]
+async fn signed_results_verify_against_the_servers_key() {
+    let key = SigningKey::generate("echo-1").unwrap();
+    let verifying_key = key.verifying_key();
+    let endpoint = common::serve(&signed_echo(key)).await;
+    let directory = common::lookup(&endpoint, json!(["echo"])).await;
+
+    let client = A2AAgent::new("client", "Client", vec![]).with_verifying_key("echo", verifying_key.clone());
+    let result = client.send_task("echo", "echo", json!("hi"), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+    assert_eq!(result.signature.map(|signature| signature.key_id), Some("echo-1".to_string()));
+
+    let relayed = common::lookup(&tampering(endpoint).await, json!(["echo"])).await;
+    let refused = client.send_task("echo", "echo", json!("hi"), &relayed).await;
+    assert!(matches!(refused, Err(A2AError::SignatureInvalid { .. })), "{:?}", refused);
+}
+
+#[tokio::test]
+async fn results_signed_by_another_key_or_unsigned_are_refused() {
+    let expected = SigningKey::generate("echo-1").unwrap().verifying_key();
+    let client = A2AAgent::new("client", "Client", vec![]).with_verifying_key("echo", expected);
+
+    let impostor = common::serve(&signed_echo(SigningKey::generate("echo-1").unwrap())).await;
+    let mut unsigned = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    unsigned.handle_task(|_ctx, input| input);
+    let unsigned = common::serve(&unsigned).await;
+
+    for endpoint in [impostor, unsigned] {
+        let directory = common::lookup(&endpoint, json!(["echo"])).await;
+        match client.send_task("echo", "echo", json!("hi"), &directory).await {
+            Err(A2AError::SignatureInvalid { agent_id, .. }) => assert_eq!(agent_id, "echo"),
+            other => panic!("expected SignatureInvalid, got {:?}", other),
+        }
+    }
+
+    // Agents without a key for the target take results as they come.
+    let endpoint = common::serve(&signed_echo(SigningKey::generate("echo-2").unwrap())).await;
+    let directory = common::lookup(&endpoint, json!(["echo"])).await;
+    A2AAgent::new("client", "Client", vec![]).send_task("echo", "echo", json!("hi"), &directory).await.unwrap();
+}
@@ -0,0 +1,96 @@
+#![cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2ADirectory, A2AAgent, AgentInfo, RegistryStore, SqliteRegistryStore, StoredAgent};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::oneshot;
+
+/// A database file of its own, removed when dropped.
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("a2a-test-{}.db", uuid::Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn agent(agent_id: &str) -> AgentInfo {
+    serde_json::from_value(json!({
+        "agentId": agent_id,
+        "name": agent_id,
+        "capabilities": ["search"],
+        "endpoint": "http://127.0.0.1:9001",
+        "tags": {"region": "eu"},
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn registrations_survive_a_directory_restart() {
+    let db = TempDb::new();
+
+    let (stop, stopped) = oneshot::channel::<()>();
+    let store = Arc::new(SqliteRegistryStore::open(&db.0).unwrap());
+    let (addr, serving) = A2ADirectory::new(0)
+        .with_store(store)
+        .bind_with_shutdown(async {
+            let _ = stopped.await;
+        })
+        .await
+        .unwrap();
+    let serving = tokio::spawn(serving);
+    let mut search = A2AAgent::new("search", "Search", vec!["search".to_string()]);
+    search
+        .register("http://127.0.0.1:9001", &format!("http://127.0.0.1:{}", addr.port()))
+        .await
+        .unwrap();
+    stop.send(()).unwrap();
+    serving.await.unwrap().unwrap();
+
+    // A new directory on a new connection to the same file.
+    let store = Arc::new(SqliteRegistryStore::open(&db.0).unwrap());
+    let directory = common::directory(A2ADirectory::new(0).with_store(store)).await;
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let found = client.discover(vec!["search".to_string()], &directory).await.unwrap().unwrap();
+    assert_eq!(found.agent_id, "search");
+    assert_eq!(found.endpoint, "http://127.0.0.1:9001");
+}
+
+#[tokio::test]
+async fn the_store_reads_back_what_it_wrote() {
+    let db = TempDb::new();
+    let store = SqliteRegistryStore::open(&db.0).unwrap();
+    let now = SystemTime::now();
+    store.put(StoredAgent { agent: agent("b"), registered: now }).await.unwrap();
+    store
+        .put(StoredAgent { agent: agent("a"), registered: now - Duration::from_secs(100) })
+        .await
+        .unwrap();
+
+    let b = store.get("b").await.unwrap().unwrap();
+    assert_eq!(b.agent.tags["region"], "eu");
+    let millis = |time: SystemTime| time.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis();
+    assert_eq!(millis(b.registered), millis(now));
+    assert!(store.get("c").await.unwrap().is_none());
+
+    let ids: Vec<String> = store.list().await.unwrap().into_iter().map(|stored| stored.agent.agent_id).collect();
+    assert_eq!(ids, ["a", "b"]);
+
+    // Replacing keeps one row per agent.
+    store.put(StoredAgent { agent: agent("b"), registered: now }).await.unwrap();
+    assert_eq!(store.list().await.unwrap().len(), 2);
+
+    assert_eq!(store.purge(now - Duration::from_secs(10)).await.unwrap(), 1);
+    store.remove("b").await.unwrap();
+    assert!(store.list().await.unwrap().is_empty());
+}
@@ -0,0 +1,55 @@
+#![cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+
+use a2a::testing::{MockAgent, MockDirectory};
+use a2a::{A2AAgent, A2AServer, AgentInfo, CapabilityQuery, SelectionStrategy};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn the_harness_serves_real_agents_and_a_directory() {
+    let directory = MockDirectory::start().await.unwrap();
+    let echo = MockAgent::echo("echo", &["echo"]).await.unwrap();
+    echo.register_with(&directory).await.unwrap();
+    assert_ne!(echo.url(), directory.url());
+
+    let mut server = A2AServer::new("calc", "Calc", vec!["math".to_string()], 0);
+    server.handle_task(|_ctx, input| json!(input["a"].as_i64().unwrap_or(0) + input["b"].as_i64().unwrap_or(0)));
+    let calc = MockAgent::start(&server).await.unwrap();
+    assert_eq!(calc.info().agent_id, "calc");
+    let mut calc_agent = A2AAgent::new("calc", "Calc", vec!["math".to_string()]);
+    calc_agent.register(calc.url(), directory.url()).await.unwrap();
+
+    // A seeded agent needs no server behind it.
+    let dead = AgentInfo::new("abacus", "Abacus", vec!["math".to_string()], "http://127.0.0.1:1").unwrap();
+    directory.seed(dead).await.unwrap();
+    let ids: Vec<String> = directory.agents().await.into_iter().map(|agent| agent.agent_id).collect();
+    assert_eq!(ids, ["abacus", "calc", "echo"]);
+    assert!(directory.agents().await.iter().all(|agent| agent.registered_at.is_some()));
+
+    let client = A2AAgent::new("client", "Client", vec![]);
+    let echoed = client.send_task("echo", "echo", json!("hi"), directory.url()).await.unwrap();
+    assert_eq!(echoed.output, Some(json!("hi")));
+    let math = CapabilityQuery::All(vec!["math".to_string()]);
+    let sum = client.send_task_any(math, SelectionStrategy::First, "add", json!({"a": 2, "b": 3}), directory.url());
+    assert_eq!(sum.await.unwrap().output, Some(json!(5)));
+
+    // Stopping waits for the shutdown; dropping only starts it.
+    calc.stop().await.unwrap();
+    assert!(client.send_task("calc", "add", json!({}), directory.url()).await.is_err());
+
+    let endpoint = echo.url().to_string();
+    drop(echo);
+    let mut refused = false;
+    for _ in 0..50 {
+        if client.ping(&endpoint).await.is_err() {
+            refused = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(refused, "{} still answers after its harness was dropped", endpoint);
+
+    let url = directory.url().to_string();
+    directory.stop().await.unwrap();
+    assert!(client.discover(vec!["math".to_string()], &url).await.is_err());
+}
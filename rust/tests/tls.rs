@@ -0,0 +1,109 @@
+#![cfg(all(feature = "tls", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AError, A2AServer, ServerTlsConfig, TlsConfig};
+use serde_json::json;
+
+const CA: &[u8] = include_bytes!("fixtures/tls/ca.pem");
+const SERVER_CERT: &[u8] = include_bytes!("fixtures/tls/server.pem");
+const SERVER_KEY: &[u8] = include_bytes!("fixtures/tls/server.key");
+const CLIENT_CERT: &[u8] = include_bytes!("fixtures/tls/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("fixtures/tls/client.key");
+
+/// Serve `server` over HTTPS with the test certificate, returning its
+/// base URL.
+async fn serve_https(server: A2AServer) -> String {
+    let server = server.with_tls(ServerTlsConfig::from_pem(SERVER_CERT, SERVER_KEY));
+    let (addr, serving) = server.bind().await.unwrap();
+    tokio::spawn(serving);
+    format!("https://localhost:{}", addr.port())
+}
+
+fn echo() -> A2AServer {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    server
+}
+
+#[tokio::test]
+async fn agents_trusting_the_ca_reach_https_peers() {
+    let endpoint = serve_https(echo()).await;
+    let directory = common::lookup(&endpoint, json!(["echo"])).await;
+    let tls = TlsConfig::new()
+        .with_identity_pem(CLIENT_CERT, CLIENT_KEY)
+        .unwrap()
+        .with_root_certificates_pem(CA)
+        .unwrap();
+    let client = A2AAgent::builder("client", "Client").tls(tls).max_retries(0).build().unwrap();
+
+    client.ping(&endpoint).await.unwrap();
+    let result = client.send_task("echo", "echo", json!("hi"), &directory).await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+}
+
+#[tokio::test]
+async fn untrusted_certificates_fail_as_tls_errors() {
+    let endpoint = serve_https(echo()).await;
+    let client = A2AAgent::builder("client", "Client").max_retries(0).build().unwrap();
+
+    let refused = client.ping(&endpoint).await;
+    assert!(matches!(refused, Err(A2AError::Tls(_))), "{:?}", refused);
+}
+
+#[test]
+fn bad_identities_and_roots_are_refused() {
+    assert!(matches!(TlsConfig::new().with_identity_pem(CLIENT_CERT, b"not a key"), Err(A2AError::Tls(_))));
+    assert!(matches!(TlsConfig::new().with_identity_pkcs12(b"not an archive", ""), Err(A2AError::Tls(_))));
+    let garbled = b"-----BEGIN CERTIFICATE-----\nnope\n-----END CERTIFICATE-----\n";
+    assert!(matches!(TlsConfig::new().with_root_certificates_pem(garbled), Err(A2AError::Tls(_))));
+
+    let debug = format!("{:?}", TlsConfig::new().with_identity_pem(CLIENT_CERT, CLIENT_KEY).unwrap());
+    assert!(!debug.contains("PRIVATE KEY"), "{}", debug);
+}
+
+#[tokio::test]
+async fn servers_load_their_certificate_from_files() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls");
+    let tls = ServerTlsConfig::from_pem_files(format!("{}/server.pem", fixtures), format!("{}/server.key", fixtures));
+    let (addr, serving) = echo().with_tls(tls).bind().await.unwrap();
+    tokio::spawn(serving);
+
+    let tls = TlsConfig::new().with_root_certificates_pem(CA).unwrap();
+    let client = A2AAgent::builder("client", "Client").tls(tls).max_retries(0).build().unwrap();
+    client.ping(&format!("https://localhost:{}", addr.port())).await.unwrap();
+    // Plain HTTP gets no answer from an HTTPS port.
+    assert!(client.ping(&format!("http://127.0.0.1:{}", addr.port())).await.is_err());
+}
+
+#[tokio::test]
+async fn bad_server_certificates_fail_at_bind() {
+    let garbled = ServerTlsConfig::from_pem(SERVER_CERT, b"not a key".to_vec());
+    let missing = ServerTlsConfig::from_pem_files("/nonexistent/server.pem", "/nonexistent/server.key");
+    for tls in [garbled, missing] {
+        match echo().with_tls(tls).bind().await {
+            Err(A2AError::TlsSetup(message)) => assert!(!message.is_empty()),
+            Err(other) => panic!("expected TlsSetup, got {:?}", other),
+            Ok(_) => panic!("expected TlsSetup, got a bound server"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn https_servers_advertise_an_https_endpoint() {
+    let endpoint = serve_https(echo()).await;
+    let tls = TlsConfig::new().with_root_certificates_pem(CA).unwrap();
+    let client = A2AAgent::builder("client", "Client").tls(tls).max_retries(0).build().unwrap();
+
+    let card = client.fetch_card(&endpoint).await.unwrap();
+    assert_eq!(card.endpoint, endpoint);
+    client.ping(&card.endpoint).await.unwrap();
+}
+
+#[test]
+fn mismatched_identities_fail_the_build() {
+    // Each half parses; only the client built from them finds the key is not the certificate's.
+    let tls = TlsConfig::new().with_identity_pem(CLIENT_CERT, SERVER_KEY).unwrap();
+    let built = A2AAgent::builder("client", "Client").tls(tls).build();
+    assert!(matches!(built, Err(A2AError::Tls(_))), "{:?}", built.err());
+}
@@ -0,0 +1,99 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AError, A2AServer, InMemoryTransport, RetryPolicy, Transport};
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// Routes every call to handlers in this process, recording the methods.
+#[derive(Default)]
+struct InMemory {
+    methods: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl Transport for InMemory {
+    async fn call(&self, _url: &str, method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+        self.methods.lock().unwrap().push(method.to_string());
+        let params = params.unwrap_or_default();
+        Ok(json!({"taskId": params["taskId"], "status": "completed", "output": params["input"]}))
+    }
+
+    async fn get(&self, url: &str) -> Result<Option<Value>, A2AError> {
+        Ok(url.ends_with("/a2a/agents/echo").then(|| {
+            json!({"agentId": "echo", "name": "Echo", "capabilities": ["say"], "endpoint": "mem://echo"})
+        }))
+    }
+}
+
+#[tokio::test]
+async fn tasks_go_through_an_installed_transport() {
+    let transport = Arc::new(InMemory::default());
+    let agent = A2AAgent::new("client", "Client", vec![]).with_transport(transport.clone());
+
+    let result = agent.send_task("echo", "say", json!("hi"), "mem://directory").await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+    assert_eq!(*transport.methods.lock().unwrap(), ["a2a/task"]);
+
+    let missing = agent.send_task("nobody", "say", json!("hi"), "mem://directory").await;
+    assert!(matches!(missing, Err(A2AError::AgentNotFound(id)) if id == "nobody"));
+}
+
+#[tokio::test]
+async fn only_a_404_means_the_agent_is_unknown() {
+    let directory = common::mock(
+        Router::new()
+            .route("/a2a/agents/gone", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/a2a/agents/guarded", get(|| async { (StatusCode::UNAUTHORIZED, "log in first") }))
+            .route("/a2a/agents/hidden", get(|| async { StatusCode::FORBIDDEN })),
+    )
+    .await;
+    let agent = A2AAgent::new("client", "Client", vec![]).with_retry_policy(RetryPolicy::none());
+
+    let gone = agent.send_task("gone", "say", json!("hi"), &directory).await;
+    assert!(matches!(gone, Err(A2AError::AgentNotFound(id)) if id == "gone"));
+
+    match agent.send_task("guarded", "say", json!("hi"), &directory).await {
+        Err(A2AError::HttpStatus { status, body }) => {
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(body, "log in first");
+        }
+        other => panic!("expected a 401, got {:?}", other),
+    }
+    let hidden = agent.send_task("hidden", "say", json!("hi"), &directory).await;
+    assert!(matches!(hidden, Err(A2AError::HttpStatus { status, .. }) if status == StatusCode::FORBIDDEN));
+}
+
+#[tokio::test]
+async fn agents_exchange_tasks_in_process() {
+    let transport = InMemoryTransport::new();
+    let mut server = A2AServer::new("calc", "Calculator", vec!["add".to_string()], 0);
+    server.handle_task(|ctx, input| {
+        let sum = input["a"].as_i64().unwrap() + input["b"].as_i64().unwrap();
+        json!({"sum": sum, "from": ctx.sender})
+    });
+    transport.mount("mem://calc", &server);
+
+    let mut calc =
+        A2AAgent::new("calc", "Calculator", vec!["add".to_string()]).with_transport(Arc::new(transport.clone()));
+    calc.register("mem://calc", "mem://directory").await.unwrap();
+    let ids: Vec<String> = transport.agents().await.into_iter().map(|agent| agent.agent_id).collect();
+    assert_eq!(ids, ["calc"]);
+
+    let client = A2AAgent::new("client", "Client", vec![])
+        .with_transport(Arc::new(transport.clone()))
+        .with_retry_policy(RetryPolicy::none());
+    let found = client.discover(vec!["add".to_string()], "mem://directory").await.unwrap();
+    assert_eq!(found.map(|agent| agent.endpoint), Some("mem://calc".to_string()));
+    let result = client.send_task("calc", "add", json!({"a": 2, "b": 3}), "mem://directory").await.unwrap();
+    assert_eq!(result.output, Some(json!({"sum": 5, "from": "client"})));
+
+    transport.unmount("mem://calc");
+    let down = client.send_task("calc", "add", json!({"a": 2, "b": 3}), "mem://directory").await;
+    assert!(matches!(down, Err(A2AError::Io(_))), "{:?}", down);
+}
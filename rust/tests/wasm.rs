@@ -0,0 +1,58 @@
+//! The client on `wasm32`, run with `wasm-bindgen-test` under Node:
+//!
+//! ```sh
+//! cargo test --target wasm32-unknown-unknown --no-default-features --features wasm --test wasm
+//! ```
+
+#![cfg(target_arch = "wasm32")]
+
+use a2a::{A2AAgent, A2AError, RetryPolicy, TaskStatus, Transport};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Answers every task with its input, as a page-local stand-in.
+struct Echo;
+
+#[async_trait(?Send)]
+impl Transport for Echo {
+    async fn call(&self, _url: &str, _method: &str, params: Option<Value>) -> Result<Value, A2AError> {
+        let params = params.unwrap_or_default();
+        Ok(json!({"taskId": params["taskId"], "status": "completed", "output": params["input"]}))
+    }
+
+    async fn get(&self, _url: &str) -> Result<Option<Value>, A2AError> {
+        Ok(Some(json!({"agentId": "echo", "name": "Echo", "capabilities": ["say"], "endpoint": "mem://echo"})))
+    }
+}
+
+#[wasm_bindgen_test]
+async fn tasks_go_through_a_non_send_transport() {
+    let agent = A2AAgent::new("ui", "UI", vec![]).with_transport(Arc::new(Echo));
+    let result = agent.send_task("echo", "say", json!("hi"), "mem://directory").await.unwrap();
+    assert_eq!(result.output, Some(json!("hi")));
+}
+
+#[wasm_bindgen_test]
+async fn fetch_failures_are_retried_then_reported() {
+    let agent = A2AAgent::builder("ui", "UI")
+        .timeout(Duration::from_secs(2))
+        .retry_policy(RetryPolicy::new(2, Duration::from_millis(20)))
+        .build().unwrap();
+
+    // Nothing listens on port 1; `fetch` rejects and the agent backs off
+    // on `setTimeout` between attempts.
+    let started = js_sys::Date::now();
+    let result = agent.discover(vec![], "http://127.0.0.1:1").await;
+    assert!(matches!(result, Err(A2AError::Http(_))), "{:?}", result);
+    assert!(js_sys::Date::now() - started >= 30.0);
+}
+
+#[wasm_bindgen_test]
+async fn dry_runs_send_nothing() {
+    let agent = A2AAgent::new("ui", "UI", vec![]).with_transport(Arc::new(Echo)).with_dry_run(true);
+    let result = agent.send_task("echo", "say", json!("hi"), "mem://directory").await.unwrap();
+    assert_eq!(result.status, TaskStatus::DryRun);
+}
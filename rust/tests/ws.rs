@@ -0,0 +1,131 @@
+#![cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+
+mod common;
+
+use a2a::{A2AAgent, A2AError, A2AServer};
+use axum::extract::ws::{Message as ServerMessage, WebSocketUpgrade};
+use axum::routing::get;
+use axum::Router;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Serve an echo and a counting stream handler, returning the `ws://` URL.
+async fn server() -> String {
+    let mut server = A2AServer::new("echo", "Echo", vec!["echo".to_string()], 0);
+    server.handle_task(|_ctx, input| input);
+    server.handle_task_stream(|_ctx, input| {
+        let to = input["to"].as_u64().unwrap_or(3);
+        futures::stream::iter((1..=to).map(|n| Ok(json!(n))))
+    });
+    format!("{}/ws", common::serve(&server).await.replacen("http", "ws", 1))
+}
+
+fn call(id: u64, input: Value) -> Message {
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "a2a/task",
+        "params": {"taskId": format!("t{}", id), "action": "echo", "sender": "client", "input": input},
+    });
+    Message::text(call.to_string())
+}
+
+#[tokio::test]
+async fn text_frames_carry_json_rpc_calls() {
+    let (mut socket, _) = tokio_tungstenite::connect_async(server().await).await.unwrap();
+
+    socket.send(call(1, json!("one"))).await.unwrap();
+    socket.send(call(2, json!("two"))).await.unwrap();
+    socket.send(Message::text("{not json")).await.unwrap();
+
+    let mut answers = Vec::new();
+    while answers.len() < 3 {
+        let Message::Text(text) = socket.next().await.unwrap().unwrap() else { continue };
+        answers.push(serde_json::from_str::<Value>(&text).unwrap());
+    }
+    let answer = |id: Value| answers.iter().find(|answer| answer["id"] == id).unwrap().clone();
+    assert_eq!(answer(json!(1))["result"]["output"], json!("one"));
+    assert_eq!(answer(json!(2))["result"]["output"], json!("two"));
+    assert_eq!(answer(Value::Null)["error"]["code"], json!(-32700));
+}
+
+#[tokio::test]
+async fn sessions_send_tasks_and_receive_chunks() {
+    let session = A2AAgent::new("client", "Client", vec![]).connect_ws(&server().await).await.unwrap();
+
+    let result = session.send_task("echo", json!({"hello": "world"})).await.unwrap();
+    assert_eq!(result.output, Some(json!({"hello": "world"})));
+
+    let chunks = async {
+        let mut data = Vec::new();
+        for _ in 0..3 {
+            let notification = session.recv().await?;
+            assert_eq!(notification.method, "a2a/task/chunk");
+            data.push(notification.params["data"].clone());
+        }
+        Ok::<_, A2AError>(data)
+    };
+    let (result, chunks) = tokio::join!(session.stream_task("count", json!({"to": 3})), chunks);
+    result.unwrap();
+    assert_eq!(chunks.unwrap(), [json!(1), json!(2), json!(3)]);
+}
+
+#[tokio::test]
+async fn pings_are_answered() {
+    let (mut socket, _) = tokio_tungstenite::connect_async(server().await).await.unwrap();
+    socket.send(Message::Ping(b"are you there".to_vec().into())).await.unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Pong(payload) => assert_eq!(&payload[..], b"are you there"),
+        other => panic!("expected a pong, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn sessions_reconnect_after_the_server_hangs_up() {
+    // Answers one call per connection, then closes it.
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let router = Router::new().route(
+        "/ws",
+        get(move |upgrade: WebSocketUpgrade| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                upgrade.on_upgrade(|mut socket| async move {
+                    while let Some(Ok(message)) = socket.next().await {
+                        let ServerMessage::Text(text) = message else { continue };
+                        let call: Value = serde_json::from_str(&text).unwrap();
+                        let result = json!({"taskId": call["params"]["taskId"], "status": "completed", "output": "ok"});
+                        let answer = json!({"jsonrpc": "2.0", "id": call["id"], "result": result});
+                        socket.send(ServerMessage::text(answer.to_string())).await.unwrap();
+                        let _ = socket.send(ServerMessage::Close(None)).await;
+                        break;
+                    }
+                })
+            }
+        }),
+    );
+    let url = format!("{}/ws", common::mock(router).await.replacen("http", "ws", 1));
+
+    let session = A2AAgent::new("client", "Client", vec![]).connect_ws(&url).await.unwrap();
+    assert_eq!(session.send_task("echo", json!(1)).await.unwrap().output, Some(json!("ok")));
+    // The server's close has to arrive before the session notices.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while session.is_connected().await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(session.send_task("echo", json!(2)).await.unwrap().output, Some(json!("ok")));
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+    session.close().await;
+    assert!(!session.is_connected().await);
+    let refused = A2AAgent::new("client", "Client", vec![]).connect_ws("ws://127.0.0.1:1/ws").await;
+    assert!(matches!(refused, Err(A2AError::Io(_))), "{:?}", refused.err());
+}